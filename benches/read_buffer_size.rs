@@ -0,0 +1,70 @@
+//! Measures the conversion time of a large generated CSV file for several read buffer sizes.
+//!
+//! Run it with `cargo bench --bench read_buffer_size`. The number of rows defaults to 3 million,
+//! about 126 MB, and can be changed with the `CC2P_BENCH_ROWS` environment variable.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use cc2p::{convert_to_parquet, ConvertOptions};
+
+/// Buffer sizes compared, the first is the default.
+const READ_BUFFER_SIZES: [usize; 4] = [8192, 65536, 1048576, 8388608];
+
+/// Number of runs of every buffer size, the best one is reported.
+const RUNS: usize = 3;
+
+/// Writes a CSV file of the given number of rows and five columns.
+fn generate(path: &Path, rows: usize) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "id,name,amount,country,created")?;
+    for row in 0..rows {
+        writeln!(
+            writer,
+            "{},customer {},{}.{:02},{},2024-01-{:02}",
+            row,
+            row % 1000,
+            row % 10000,
+            row % 100,
+            ["DE", "FR", "NL", "US"][row % 4],
+            row % 28 + 1
+        )?;
+    }
+    writer.flush()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rows = std::env::var("CC2P_BENCH_ROWS")
+        .ok()
+        .map(|rows| rows.parse())
+        .transpose()?
+        .unwrap_or(3_000_000);
+    let dir = std::env::temp_dir().join("cc2p_bench_read_buffer_size");
+    fs::create_dir_all(&dir)?;
+    let input = dir.join("big.csv");
+    let output = dir.join("big.parquet");
+    generate(&input, rows)?;
+    println!("{} rows, {} bytes", rows, fs::metadata(&input)?.len());
+
+    for read_buffer_size in READ_BUFFER_SIZES {
+        let options = ConvertOptions::new()
+            .read_buffer_size(read_buffer_size)
+            .output(&output);
+        let mut best = Duration::MAX;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            convert_to_parquet(&input, &options)?;
+            best = best.min(start.elapsed());
+        }
+        println!(
+            "read-buffer-size {:>8}: {:>8.3} s",
+            read_buffer_size,
+            best.as_secs_f64()
+        );
+    }
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use encoding_rs::{Decoder, DecoderResult, Encoding, UTF_8};
+use std::io::{BufRead, Read};
+
+/// A reader which transcodes its input to UTF-8.
+///
+/// Without a decoder the bytes are passed through unchanged. Malformed input is reported as an
+/// [`std::io::ErrorKind::InvalidData`] error instead of being replaced.
+pub(crate) struct DecodeReader<R> {
+    inner: R,
+    decoder: Option<Decoder>,
+    buffer: Vec<u8>,
+    position: usize,
+    bytes_read: u64,
+    finished: bool,
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    /// Creates a reader decoding the input from the given encoding, or passing it through if `None`.
+    ///
+    /// A byte order mark at the start of the input takes precedence over the given encoding: a UTF-8
+    /// byte order mark is skipped, while UTF-16 input is transcoded to UTF-8.
+    pub(crate) fn new(mut inner: R, encoding: Option<&'static Encoding>) -> std::io::Result<Self> {
+        let decoder = match Encoding::for_bom(inner.fill_buf()?) {
+            Some((bom_encoding, bom_length)) if bom_encoding == UTF_8 => {
+                inner.consume(bom_length);
+                // an explicit encoding still validates the UTF-8 data
+                encoding.map(|_| UTF_8.new_decoder_without_bom_handling())
+            }
+            Some((bom_encoding, _)) => Some(bom_encoding.new_decoder_with_bom_removal()),
+            None => encoding.map(|encoding| encoding.new_decoder_without_bom_handling()),
+        };
+
+        Ok(Self {
+            inner,
+            decoder,
+            buffer: vec![],
+            position: 0,
+            bytes_read: 0,
+            finished: false,
+        })
+    }
+
+    /// Decodes the next chunk of the input into the buffer.
+    fn decode_chunk(&mut self) -> std::io::Result<()> {
+        let Some(decoder) = &mut self.decoder else {
+            return Ok(());
+        };
+
+        let input = self.inner.fill_buf()?;
+        let last = input.is_empty();
+        let capacity = decoder
+            .max_utf8_buffer_length_without_replacement(input.len())
+            .unwrap_or(input.len() * 3)
+            .max(4);
+        self.buffer.resize(capacity, 0);
+        self.position = 0;
+
+        let (result, read, written) =
+            decoder.decode_to_utf8_without_replacement(input, &mut self.buffer, last);
+        self.inner.consume(read);
+        self.bytes_read += read as u64;
+        self.buffer.truncate(written);
+
+        match result {
+            DecoderResult::Malformed(_, _) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "invalid {} data near byte {}",
+                    decoder.encoding().name(),
+                    self.bytes_read
+                ),
+            )),
+            DecoderResult::InputEmpty => {
+                self.finished = last;
+                Ok(())
+            }
+            DecoderResult::OutputFull => Ok(()),
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.decoder.is_none() {
+            return self.inner.read(buf);
+        }
+
+        while self.position == self.buffer.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.decode_chunk()?;
+        }
+
+        let length = buf.len().min(self.buffer.len() - self.position);
+        buf[..length].copy_from_slice(&self.buffer[self.position..self.position + length]);
+        self.position += length;
+
+        Ok(length)
+    }
+}
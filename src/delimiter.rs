@@ -0,0 +1,208 @@
+use std::io::{BufRead, Read};
+
+/// The byte a multi-character delimiter is replaced with before the data is tokenized.
+pub(crate) const UNIT_SEPARATOR: u8 = 0x1f;
+
+/// A reader which replaces a multi-character delimiter outside quoted fields with [`UNIT_SEPARATOR`].
+///
+/// The CSV tokenizer only supports single-byte delimiters, so this pass turns a delimiter such as
+/// `||` into one byte. Quoted fields follow the rules of the tokenizer: a quote only opens a quoted
+/// field at the start of a field, and a doubled quote inside it is an escaped quote. Without a
+/// delimiter the bytes are passed through unchanged.
+pub(crate) struct DelimiterReader<R> {
+    inner: R,
+    delimiter: Option<Vec<u8>>,
+    quote: Option<u8>,
+    state: State,
+    matched: usize,
+    buffer: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+/// Where the reader is within the current field.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// At the start of a field, where a quote opens a quoted field.
+    FieldStart,
+    /// Within an unquoted field.
+    Field,
+    /// Within a quoted field.
+    Quoted,
+    /// Right after a quote within a quoted field, which either closes it or escapes a quote.
+    AfterQuote,
+}
+
+impl<R: BufRead> DelimiterReader<R> {
+    /// Creates a reader replacing `delimiter`, or passing the bytes through if `None`.
+    ///
+    /// `quote` is the quote character of the tokenizer, or `None` if quoting is disabled.
+    pub(crate) fn new(inner: R, delimiter: Option<&str>, quote: Option<u8>) -> Self {
+        Self {
+            inner,
+            delimiter: delimiter.map(|delimiter| delimiter.as_bytes().to_vec()),
+            quote,
+            state: State::FieldStart,
+            matched: 0,
+            buffer: vec![],
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Replaces the delimiters of the next chunk of the input into the buffer.
+    fn split_chunk(&mut self, delimiter: &[u8]) -> std::io::Result<()> {
+        let input = self.inner.fill_buf()?.to_vec();
+        self.buffer.clear();
+        self.position = 0;
+
+        if input.is_empty() {
+            // a partial delimiter at the end of the input is data
+            self.buffer.extend_from_slice(&delimiter[..self.matched]);
+            self.matched = 0;
+            self.finished = true;
+            return Ok(());
+        }
+
+        for &byte in &input {
+            self.split_byte(byte, delimiter);
+        }
+        self.inner.consume(input.len());
+
+        Ok(())
+    }
+
+    /// Appends the byte to the buffer, or a separator once the whole delimiter has been matched.
+    fn split_byte(&mut self, byte: u8, delimiter: &[u8]) {
+        match self.state {
+            State::Quoted => {
+                if Some(byte) == self.quote {
+                    self.state = State::AfterQuote;
+                }
+                self.buffer.push(byte);
+                return;
+            }
+            State::AfterQuote if Some(byte) == self.quote => {
+                self.state = State::Quoted;
+                self.buffer.push(byte);
+                return;
+            }
+            State::AfterQuote => self.state = State::Field,
+            State::FieldStart | State::Field => {}
+        }
+
+        if byte == delimiter[self.matched] {
+            self.matched += 1;
+            if self.matched == delimiter.len() {
+                self.buffer.push(UNIT_SEPARATOR);
+                self.matched = 0;
+                self.state = State::FieldStart;
+            }
+            return;
+        }
+
+        if self.matched > 0 {
+            // the partial match is data, apart from a delimiter starting within it
+            let matched = std::mem::take(&mut self.matched);
+            self.buffer.push(delimiter[0]);
+            self.state = State::Field;
+            for &byte in &delimiter[1..matched] {
+                self.split_byte(byte, delimiter);
+            }
+            self.split_byte(byte, delimiter);
+            return;
+        }
+
+        self.state = match byte {
+            b'\n' | b'\r' => State::FieldStart,
+            _ if self.state == State::FieldStart && Some(byte) == self.quote => State::Quoted,
+            _ => State::Field,
+        };
+        self.buffer.push(byte);
+    }
+}
+
+impl<R: BufRead> Read for DelimiterReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some(delimiter) = self.delimiter.take() else {
+            return self.inner.read(buf);
+        };
+
+        let mut result = Ok(());
+        while self.position == self.buffer.len() && !self.finished && result.is_ok() {
+            result = self.split_chunk(&delimiter);
+        }
+        self.delimiter = Some(delimiter);
+        result?;
+
+        let length = buf.len().min(self.buffer.len() - self.position);
+        buf[..length].copy_from_slice(&self.buffer[self.position..self.position + length]);
+        self.position += length;
+
+        Ok(length)
+    }
+}
+
+/// The delimiters considered by [`detect_delimiter`], in order of preference.
+pub(crate) const CANDIDATES: [char; 4] = [',', ';', '\t', '|'];
+
+/// The maximum number of lines of the sample inspected by [`detect_delimiter`].
+const DETECTION_LINES: usize = 20;
+
+/// Detects the delimiter from the first lines of the data.
+///
+/// A candidate is accepted if it occurs outside quotes on every line, the same number of times.
+/// Returns `None` if no candidate or more than one candidate is consistent, as the delimiter is
+/// then ambiguous.
+pub(crate) fn detect_delimiter(
+    sample: &[u8],
+    quote: Option<u8>,
+    comment: Option<u8>,
+) -> Option<char> {
+    // lines end with \n, \r\n or a lone \r
+    let mut lines: Vec<&[u8]> = sample
+        .split(|&byte| byte == b'\n' || byte == b'\r')
+        .filter(|line| !line.is_empty())
+        .filter(|line| comment.map_or(true, |comment| line[0] != comment))
+        .take(DETECTION_LINES + 1)
+        .collect();
+    // the last line may be cut off by the end of the sample
+    if lines.len() > 1 && !sample.ends_with(b"\n") && !sample.ends_with(b"\r") {
+        lines.pop();
+    }
+    lines.truncate(DETECTION_LINES);
+
+    let consistent: Vec<char> = CANDIDATES
+        .iter()
+        .copied()
+        .filter(|&candidate| {
+            let mut counts = lines
+                .iter()
+                .map(|line| count_unquoted(line, candidate as u8, quote));
+            match counts.next() {
+                Some(first) if first > 0 => counts.all(|count| count == first),
+                _ => false,
+            }
+        })
+        .collect();
+
+    match consistent[..] {
+        [delimiter] => Some(delimiter),
+        _ => None,
+    }
+}
+
+/// Counts the occurrences of the byte in the line outside quoted sections.
+fn count_unquoted(line: &[u8], byte: u8, quote: Option<u8>) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for &b in line {
+        if Some(b) == quote {
+            quoted = !quoted;
+        } else if b == byte && !quoted {
+            count += 1;
+        }
+    }
+
+    count
+}
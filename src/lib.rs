@@ -1,299 +1,7667 @@
+mod decode;
+mod delimiter;
+mod reader;
+mod sha256;
+mod zip;
+
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit, DECIMAL128_MAX_PRECISION};
+use encoding_rs::Encoding;
+use flate2::read::MultiGzDecoder;
 use glob::{glob_with, MatchOptions};
-use parquet::basic::Compression;
-use parquet::file::properties::WriterProperties;
-use std::collections::HashMap;
+use parquet::arrow::arrow_to_parquet_schema;
+use parquet::arrow::arrow_writer::{compute_leaves, get_column_writers, ArrowColumnChunk};
+use parquet::basic::{BrotliLevel, Compression, ZstdLevel};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::{
+    EnabledStatistics, WriterProperties, WriterPropertiesPtr, WriterVersion,
+    DEFAULT_MAX_ROW_GROUP_SIZE, DEFAULT_PAGE_SIZE,
+};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::ColumnPath;
+use parquet::schema::types::SchemaDescriptor;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::fs::File;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
+use walkdir::WalkDir;
 
-/// Converts a CSV file to Parquet format.
-///
-/// # Arguments
-///
-/// * `file_path` - The path of the CSV file to be converted.
-/// * `delimiter` - The delimiter character used in the CSV file.
-/// * `has_header` - Indicates whether the CSV file has a header row.
-/// * `sampling_size` - The number of rows to sample for inferring the schema.
-///
-/// # Returns
+use reader::{integer_range, minimal_integer_type, BatchBuilder, CsvSource, IntegerOverflow};
+
+/// Path that stands for standard input instead of a CSV file.
+pub const STDIN_PATH: &str = "-";
+
+/// Name of the Parquet file written when the CSV data is read from standard input.
+pub const STDIN_OUTPUT_NAME: &str = "stdin.parquet";
+
+/// Default extension of the Parquet files written, see [`ConvertOptions::output_extension`].
+pub const DEFAULT_OUTPUT_EXTENSION: &str = "parquet";
+
+/// Default maximum number of partitions written for a single CSV file.
+pub const DEFAULT_MAX_PARTITIONS: usize = 1000;
+
+/// Key of the Parquet key-value metadata listing the source file of every row group of a merged
+/// file, see [`ConvertOptions::row_group_per_file`].
+pub const ROW_GROUP_FILES_KEY: &str = "cc2p:row_group_files";
+
+/// Name of the partition directory of rows whose partition value is null, as used by Hive.
+pub const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Default capacity in bytes of the buffers the CSV data is read through, see [`ConvertOptions::read_buffer_size`].
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Default number of rows read and converted at a time, see [`ConvertOptions::batch_size`].
+pub const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// The error of a conversion stopped by [`ConvertOptions::cancel_flag`].
+pub(crate) const CANCELLED_ERROR: &str = "the conversion was cancelled";
+
+/// Default precision of the `decimal` type and of [`ConvertOptions::decimal_columns`].
+pub const DEFAULT_DECIMAL_PRECISION: u8 = 18;
+
+/// Default scale of the `decimal` type and of [`ConvertOptions::decimal_columns`].
+pub const DEFAULT_DECIMAL_SCALE: i8 = 2;
+
+/// Default tokens of true values inferred with [`ConvertOptions::parse_bools`].
+pub const DEFAULT_TRUE_VALUES: [&str; 4] = ["true", "yes", "y", "1"];
+
+/// Default tokens of false values inferred with [`ConvertOptions::parse_bools`].
+pub const DEFAULT_FALSE_VALUES: [&str; 4] = ["false", "no", "n", "0"];
+
+/// Options controlling how a CSV file is converted to Parquet.
 ///
-/// Returns `Ok` if the conversion is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+/// Options are set with builder methods; every option has a default matching the command line defaults.
 ///
 /// # Example
 ///
 /// ```
-/// use std::path::PathBuf;
-///
-/// fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     use cc2p::convert_to_parquet;
-///
-///     let file_path = PathBuf::from("testdata/sample.csv");
-///     let delimiter = ',';
-///     let has_header = true;
-///
-///     convert_to_parquet(&file_path, delimiter, has_header, 10)?;
+/// use cc2p::ConvertOptions;
+/// use parquet::basic::Compression;
 ///
-///     Ok(())
-/// }
+/// let options = ConvertOptions::new()
+///     .delimiter(';')
+///     .has_header(false)
+///     .sampling(10)
+///     .compression(Compression::SNAPPY);
 /// ```
-
-pub fn convert_to_parquet(
-    file_path: &PathBuf,
-    delimiter: char,
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    delimiter: Option<Delimiter>,
     has_header: bool,
-    sampling_size: u16,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open(file_path)?;
+    sampling: u16,
+    sample_strategy: SampleStrategy,
+    null_values: Vec<String>,
+    number_format: Option<NumberFormat>,
+    compression: Compression,
+    output: Option<PathBuf>,
+    output_extension: String,
+    if_exists: IfExists,
+    column_types: HashMap<String, DataType>,
+    decimal_columns: Vec<String>,
+    decimal_precision: u8,
+    decimal_scale: i8,
+    quote: char,
+    quoting: bool,
+    row_group_size: usize,
+    flush_every: Option<usize>,
+    batch_size: usize,
+    read_buffer_size: usize,
+    dictionary: bool,
+    dictionary_columns: HashMap<String, bool>,
+    page_size: usize,
+    parquet_version: WriterVersion,
+    statistics: EnabledStatistics,
+    comment: Option<char>,
+    skip_rows: usize,
+    header_row: usize,
+    skip_footer: usize,
+    limit: Option<u64>,
+    input_compression: InputCompression,
+    encoding: Option<&'static Encoding>,
+    date_format: Option<String>,
+    parallelism: usize,
+    trim: Trim,
+    normalize_headers: Vec<NormalizeHeader>,
+    partition_by: Option<String>,
+    keep_partition_column: bool,
+    max_partitions: usize,
+    columns: Vec<String>,
+    exclude_columns: Vec<String>,
+    renames: HashMap<String, String>,
+    column_prefix: String,
+    header_names: Vec<String>,
+    emit_schema: bool,
+    row_group_per_file: bool,
+    metadata: bool,
+    reproducible: bool,
+    schema: Option<SchemaRef>,
+    all_strings: bool,
+    null_column_type: DataType,
+    on_ragged: OnRagged,
+    on_invalid_utf8: OnInvalidUtf8,
+    lenient_newlines: bool,
+    on_duplicate_header: OnDuplicateHeader,
+    empty_as: EmptyAs,
+    on_empty: OnEmpty,
+    int_sizing: IntSizing,
+    strict_leading_zeros: bool,
+    promote_on_overflow: bool,
+    parse_bools: bool,
+    true_values: Vec<String>,
+    false_values: Vec<String>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    progress: Option<ProgressCallback>,
+    on_schema: Option<SchemaCallback>,
+}
 
-    let (csv_schema, _) = arrow_csv::reader::Format::default()
-        .with_header(has_header)
-        .with_delimiter(delimiter as u8)
-        .infer_schema(file, Some(sampling_size as usize))?;
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            has_header: true,
+            sampling: 100,
+            sample_strategy: SampleStrategy::Head,
+            null_values: vec![],
+            number_format: None,
+            compression: Compression::LZ4_RAW,
+            output: None,
+            output_extension: DEFAULT_OUTPUT_EXTENSION.to_string(),
+            if_exists: IfExists::Overwrite,
+            column_types: HashMap::new(),
+            decimal_columns: vec![],
+            decimal_precision: DEFAULT_DECIMAL_PRECISION,
+            decimal_scale: DEFAULT_DECIMAL_SCALE,
+            quote: '"',
+            quoting: true,
+            row_group_size: DEFAULT_MAX_ROW_GROUP_SIZE,
+            flush_every: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            dictionary: true,
+            dictionary_columns: HashMap::new(),
+            page_size: DEFAULT_PAGE_SIZE,
+            parquet_version: WriterVersion::PARQUET_1_0,
+            statistics: EnabledStatistics::Page,
+            comment: None,
+            skip_rows: 0,
+            header_row: 0,
+            skip_footer: 0,
+            limit: None,
+            input_compression: InputCompression::Auto,
+            encoding: None,
+            date_format: None,
+            parallelism: 1,
+            trim: Trim::None,
+            normalize_headers: vec![],
+            partition_by: None,
+            keep_partition_column: false,
+            max_partitions: DEFAULT_MAX_PARTITIONS,
+            columns: vec![],
+            exclude_columns: vec![],
+            renames: HashMap::new(),
+            column_prefix: String::from("column_"),
+            header_names: vec![],
+            emit_schema: false,
+            row_group_per_file: false,
+            metadata: true,
+            reproducible: false,
+            schema: None,
+            all_strings: false,
+            null_column_type: DataType::Utf8,
+            on_ragged: OnRagged::Error,
+            on_invalid_utf8: OnInvalidUtf8::Error,
+            lenient_newlines: false,
+            on_duplicate_header: OnDuplicateHeader::Rename,
+            empty_as: EmptyAs::Null,
+            on_empty: OnEmpty::WriteEmpty,
+            int_sizing: IntSizing::Fixed64,
+            strict_leading_zeros: false,
+            promote_on_overflow: false,
+            parse_bools: false,
+            true_values: DEFAULT_TRUE_VALUES.iter().map(|v| v.to_string()).collect(),
+            false_values: DEFAULT_FALSE_VALUES.iter().map(|v| v.to_string()).collect(),
+            cancel_flag: None,
+            progress: None,
+            on_schema: None,
+        }
+    }
+}
 
-    let schema_ref = remove_deduplicate_columns(csv_schema);
+impl ConvertOptions {
+    /// Creates options with the default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    let file = File::open(file_path)?;
-    let mut csv = arrow_csv::ReaderBuilder::new(schema_ref.clone())
-        .with_delimiter(delimiter as u8)
-        .with_header(has_header)
-        .build(file)?;
+    /// Returns the delimiter, a comma unless it is set.
+    fn delimiter_or_default(&self) -> Delimiter {
+        self.delimiter.clone().unwrap_or(Delimiter::Char(','))
+    }
 
-    let target_file = file_path.with_extension("parquet");
+    /// Returns the maximum number of rows of a row group, the smaller of the row group size and
+    /// the number of rows written between flushes.
+    fn max_row_group_size(&self) -> usize {
+        self.flush_every
+            .map_or(self.row_group_size, |rows| rows.min(self.row_group_size))
+    }
 
-    // delete it if exist
-    delete_if_exist(target_file.to_str().unwrap())?;
+    /// Returns an error if the cancel flag is set, see [`ConvertOptions::cancel_flag`].
+    fn check_cancelled(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match &self.cancel_flag {
+            Some(cancel_flag) if cancel_flag.load(Ordering::Relaxed) => Err(CANCELLED_ERROR.into()),
+            _ => Ok(()),
+        }
+    }
 
-    let mut file = File::create(target_file).unwrap();
-    let props = WriterProperties::builder()
-        .set_compression(Compression::LZ4_RAW)
-        .set_created_by("cc2p".to_string())
-        .build();
+    /// Returns the options for reading the given file, with a tab delimiter for a `.tsv` file
+    /// unless the delimiter is set.
+    fn for_file(&self, file_path: &Path) -> std::borrow::Cow<'_, Self> {
+        if self.delimiter.is_none() && is_tsv_file(file_path) {
+            std::borrow::Cow::Owned(self.clone().delimiter('\t'))
+        } else {
+            std::borrow::Cow::Borrowed(self)
+        }
+    }
 
-    let mut parquet_writer =
-        parquet::arrow::ArrowWriter::try_new(&mut file, schema_ref, Some(props))?;
+    /// Sets the delimiter used in the CSV file, a character or a string such as `||`, defaults to `,`,
+    /// or to a tab for files with a `.tsv` extension.
+    ///
+    /// A delimiter of several characters is replaced by a single separator before the data is
+    /// tokenized, so field values must not contain the unit separator control character `\x1F`.
+    pub fn delimiter(mut self, delimiter: impl Into<Delimiter>) -> Self {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
 
-    for batch in csv.by_ref() {
-        match batch {
-            Ok(batch) => parquet_writer.write(&batch)?,
-            Err(_error) => {
-                return Err(Box::new(_error));
-            }
+    /// Sets whether the CSV file has a header row, defaults to `true`.
+    ///
+    /// Without a header, the columns are named from [`ConvertOptions::header_names`], or
+    /// `column_0`, `column_1` and so on, see [`ConvertOptions::column_prefix`].
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Sets the number of rows to sample for inferring the schema, defaults to `100`.
+    pub fn sampling(mut self, sampling: u16) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Sets which rows are sampled for inferring the schema, defaults to [`SampleStrategy::Head`].
+    ///
+    /// The other strategies read a CSV file twice, so they are not supported for data read from a
+    /// reader such as standard input.
+    pub fn sample_strategy(mut self, sample_strategy: SampleStrategy) -> Self {
+        self.sample_strategy = sample_strategy;
+        self
+    }
+
+    /// Sets tokens which are written as null, in addition to empty fields, defaults to none.
+    ///
+    /// The tokens are also ignored during schema inference.
+    pub fn null_values(mut self, null_values: Vec<String>) -> Self {
+        self.null_values = null_values;
+        self
+    }
+
+    /// Sets the format of the numbers, with a thousands separator or a decimal comma, defaults to
+    /// plain numbers such as `1234.56`.
+    ///
+    /// Values in the format, such as `1,234.56` or `1.234,56`, are inferred and written as numbers.
+    /// Scientific notation such as `1.2E3` is accepted either way. A number which does not match
+    /// the format, such as `1.5` for `1.234,56`, infers a string column and fails a numeric column
+    /// of [`ConvertOptions::column_types`] rather than being read as a plain number. Unless quoting is disabled, the
+    /// separator may equal the delimiter, as such numbers are then quoted.
+    pub fn number_format(mut self, number_format: Option<NumberFormat>) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    /// Sets the delimiter and the number format to the preset of a locale, such as [`Locale::Eu`]
+    /// for `;` and numbers such as `1.234,56`.
+    ///
+    /// It is a shorthand for [`ConvertOptions::delimiter`] and [`ConvertOptions::number_format`],
+    /// so either of them set afterwards overrides the preset.
+    pub fn locale(self, locale: Locale) -> Self {
+        self.delimiter(locale.delimiter())
+            .number_format(Some(locale.number_format()))
+    }
+
+    /// Sets the compression codec used when writing the Parquet data, defaults to LZ4.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the path of the Parquet file to write.
+    ///
+    /// If not set, the CSV file path with the [`ConvertOptions::output_extension`] is used.
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    /// Sets the extension of the Parquet file written when no output is set, without the leading
+    /// dot, such as `pq`, defaults to [`DEFAULT_OUTPUT_EXTENSION`].
+    ///
+    /// See [`parse_output_extension`] to check an extension given by the user.
+    pub fn output_extension(mut self, output_extension: impl Into<String>) -> Self {
+        self.output_extension = output_extension.into();
+        self
+    }
+
+    /// Sets what happens when the Parquet file already exists, defaults to [`IfExists::Overwrite`].
+    pub fn if_exists(mut self, if_exists: IfExists) -> Self {
+        self.if_exists = if_exists;
+        self
+    }
+
+    /// Sets column types which override the inferred schema, keyed by column name, defaults to none.
+    ///
+    /// Names which do not match any column are reported in [`ConversionSummary::warnings`].
+    pub fn column_types(mut self, column_types: HashMap<String, DataType>) -> Self {
+        self.column_types = column_types;
+        self
+    }
+
+    /// Sets columns written as decimals of the given precision and scale, defaults to none.
+    ///
+    /// Values with more integer digits than the precision and scale allow, or with more fractional
+    /// digits than the scale, are an error rather than being rounded.
+    /// [`ConvertOptions::column_types`] take precedence over these columns. See [`DEFAULT_DECIMAL_PRECISION`] and [`DEFAULT_DECIMAL_SCALE`] for common defaults.
+    pub fn decimal_columns(mut self, columns: Vec<String>, precision: u8, scale: i8) -> Self {
+        self.decimal_columns = columns;
+        self.decimal_precision = precision;
+        self.decimal_scale = scale;
+        self
+    }
+
+    /// Sets the quote character enclosing fields which contain delimiters, quotes or line breaks, defaults to `"`.
+    ///
+    /// A quote inside a quoted field is escaped by doubling it, as in RFC 4180.
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Sets whether quote characters are interpreted, defaults to `true`.
+    ///
+    /// When disabled, quote characters are kept as part of the field values.
+    pub fn quoting(mut self, quoting: bool) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Sets the maximum number of rows in each Parquet row group, defaults to `1048576`.
+    ///
+    /// The rows of a row group are buffered in memory until the group is complete, so larger groups
+    /// use more memory while writing. A size of `0` is taken as `1`.
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    /// Sets a number of rows after which a row group is closed and flushed to the output,
+    /// defaults to `None`.
+    ///
+    /// Row groups then hold at most the smaller of this and [`ConvertOptions::row_group_size`]
+    /// rows, and every row group is flushed to the output as soon as it is closed, so the output
+    /// of a long conversion grows steadily instead of in large steps. A Parquet file is only
+    /// readable once its footer is written at the end, and files are written to a temporary file
+    /// until then. It is ignored with [`ConvertOptions::row_group_per_file`].
+    pub fn flush_every(mut self, flush_every: Option<usize>) -> Self {
+        self.flush_every = flush_every.map(|rows| rows.max(1));
+        self
+    }
+
+    /// Sets the number of rows read and converted at a time, defaults to [`DEFAULT_BATCH_SIZE`].
+    ///
+    /// The CSV data is read in batches of this size, which are passed to the Parquet writer, so
+    /// memory use is bounded by the batch size and [`ConvertOptions::row_group_size`] rather than
+    /// by the size of the file.
+    ///
+    /// The batch size does not change the layout of the Parquet file: the writer collects batches
+    /// until a row group is complete and splits a batch larger than the remaining rows of the
+    /// group, so a smaller batch size only lowers the memory used for the raw records of very wide
+    /// files, at the cost of more, smaller conversions.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the capacity in bytes of the buffers the CSV data is read through, defaults to [`DEFAULT_READ_BUFFER_SIZE`].
+    ///
+    /// A larger buffer reads the file in fewer system calls, which may speed up the conversion
+    /// of large files on fast disks. It applies to the file, the gzip decoder and the CSV parser.
+    pub fn read_buffer_size(mut self, read_buffer_size: usize) -> Self {
+        self.read_buffer_size = read_buffer_size.max(1);
+        self
+    }
+
+    /// Sets whether columns are dictionary encoded, defaults to `true`.
+    ///
+    /// Dictionary encoding stores every distinct value once, which shrinks columns with few distinct
+    /// values, such as country codes. The writer falls back to plain encoding for a column chunk
+    /// whose dictionary grows too large.
+    pub fn dictionary(mut self, dictionary: bool) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Sets whether the named columns are dictionary encoded, overriding [`ConvertOptions::dictionary`],
+    /// defaults to none.
+    ///
+    /// Names which do not match any column are reported in [`ConversionSummary::warnings`].
+    pub fn dictionary_columns(mut self, dictionary_columns: HashMap<String, bool>) -> Self {
+        self.dictionary_columns = dictionary_columns;
+        self
+    }
+
+    /// Sets the approximate maximum size in bytes of a data page, defaults to `1048576`.
+    ///
+    /// A page is the unit a reader decompresses at once, so smaller pages allow finer-grained reads
+    /// at the cost of more page headers.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Sets the version of the Parquet format written, defaults to `1.0`, see [`parse_parquet_version`].
+    ///
+    /// Version `1.0` files are readable by most readers. Version `2.0` writes data page v2 headers and
+    /// delta encodings for columns which are not dictionary encoded, which are often more compact but
+    /// not supported by older readers.
+    pub fn parquet_version(mut self, parquet_version: WriterVersion) -> Self {
+        self.parquet_version = parquet_version;
+        self
+    }
+
+    /// Sets the level of the column statistics written, defaults to [`EnabledStatistics::Page`].
+    ///
+    /// Query engines use the minimum, maximum and null count of column chunks and pages to skip
+    /// data. [`EnabledStatistics::None`] omits them, as the minimum and maximum reveal values of
+    /// the column.
+    pub fn statistics(mut self, statistics: EnabledStatistics) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    /// Sets the character which starts a comment line, defaults to none.
+    ///
+    /// Lines beginning with this character are skipped anywhere in the file, before the header as
+    /// well as between data rows. It must differ from the delimiter and the quote character, see
+    /// [`validate_special_characters`].
+    pub fn comment(mut self, comment: Option<char>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Sets the number of lines discarded at the start of the file, before the header is read, defaults to `0`.
+    pub fn skip_rows(mut self, skip_rows: usize) -> Self {
+        self.skip_rows = skip_rows;
+        self
+    }
+
+    /// Sets the 0-based row holding the column names, counted after the lines of
+    /// [`ConvertOptions::skip_rows`], defaults to `0`.
+    ///
+    /// The rows above the header row, such as the titles of a spreadsheet export, are discarded.
+    /// Unlike the skipped lines they are parsed as CSV rows, so a title with a quoted line break is
+    /// a single row. It requires [`ConvertOptions::has_header`].
+    pub fn header_row(mut self, header_row: usize) -> Self {
+        self.header_row = header_row;
+        self
+    }
+
+    /// Sets the number of data rows discarded at the end of the file, such as a totals line, defaults to `0`.
+    ///
+    /// Skipped rows are not used for schema inference. The conversion fails if skipping leaves no data rows.
+    pub fn skip_footer(mut self, skip_footer: usize) -> Self {
+        self.skip_footer = skip_footer;
+        self
+    }
+
+    /// Sets the maximum number of data rows converted, defaults to `None` for all rows.
+    ///
+    /// Reading stops once the limit is reached, and only the converted rows are used for schema inference.
+    pub fn limit(mut self, limit: Option<u64>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the compression of the CSV input, defaults to [`InputCompression::Auto`].
+    pub fn input_compression(mut self, input_compression: InputCompression) -> Self {
+        self.input_compression = input_compression;
+        self
+    }
+
+    /// Sets the character encoding of the CSV input, which is transcoded to UTF-8, defaults to UTF-8.
+    ///
+    /// Bytes which are invalid in the encoding fail the conversion. A byte order mark at the start of
+    /// the input takes precedence over the encoding.
+    pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Sets a strftime-style format, such as `%d/%m/%Y`, used to infer date and timestamp columns, defaults to none.
+    ///
+    /// A column whose sampled values all match the format is written as a date, or as a timestamp
+    /// if the format has a time. If only some of the values match, the column is written as strings.
+    /// ISO 8601 dates and timestamps are inferred without a format.
+    pub fn date_format(mut self, date_format: Option<String>) -> Self {
+        self.date_format = date_format;
+        self
+    }
+
+    /// Sets the number of row groups of a file which are converted and encoded in parallel, defaults to `1`.
+    ///
+    /// The CSV data is still parsed sequentially. Each row group in flight is held in memory, so
+    /// memory use grows with the parallelism times the row group size. Parallel files do not embed
+    /// the Arrow schema, readers derive it from the Parquet schema instead.
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Sets whether leading and trailing whitespace is removed from field values and header names, defaults to [`Trim::None`].
+    ///
+    /// Fields are trimmed before null tokens are matched and before the schema is inferred.
+    pub fn trim(mut self, trim: Trim) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets the normalizations applied to the header names, defaults to none.
+    ///
+    /// The names are normalized right after the header is read, so all options referring to
+    /// columns by name use the normalized names. Names which become equal are duplicates, handled
+    /// as set with [`ConvertOptions::on_duplicate_header`]. The generated names of a file without a
+    /// header are not normalized.
+    pub fn normalize_headers(mut self, normalize_headers: Vec<NormalizeHeader>) -> Self {
+        self.normalize_headers = normalize_headers;
+        self
+    }
+
+    /// Sets the column whose values split the rows into Hive-style partitions, defaults to none.
+    ///
+    /// The rows of each distinct value are written to their own Parquet file in a `column=value`
+    /// directory, such as `out/region=US/data.parquet`. Null values are written to the
+    /// `__HIVE_DEFAULT_PARTITION__` directory. Partitioned files are written sequentially, regardless
+    /// of the parallelism.
+    pub fn partition_by(mut self, partition_by: Option<String>) -> Self {
+        self.partition_by = partition_by;
+        self
+    }
+
+    /// Sets whether the partition column is kept in the partitioned files, defaults to `false`.
+    ///
+    /// Following the Hive convention, the column is dropped by default as its value is part of the path.
+    pub fn keep_partition_column(mut self, keep_partition_column: bool) -> Self {
+        self.keep_partition_column = keep_partition_column;
+        self
+    }
+
+    /// Sets the maximum number of partitions of a file, defaults to [`DEFAULT_MAX_PARTITIONS`].
+    ///
+    /// The conversion fails once the partition column has more distinct values, instead of creating
+    /// a large number of tiny files.
+    pub fn max_partitions(mut self, max_partitions: usize) -> Self {
+        self.max_partitions = max_partitions;
+        self
+    }
+
+    /// Sets the columns written to the Parquet file, in this order, defaults to all columns.
+    ///
+    /// The names refer to the header of the CSV file, and a number which is not a header name is the
+    /// 0-based index of a column, which also selects columns of files without a header. Only the
+    /// selected columns are inferred and converted, and a name or index which does not exist fails
+    /// the conversion.
+    pub fn columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Sets the columns left out of the Parquet file, defaults to none.
+    ///
+    /// The columns are names or indexes, as for [`ConvertOptions::columns`], and are ignored if
+    /// columns are selected. A name or index which does not exist fails the conversion.
+    pub fn exclude_columns(mut self, exclude_columns: Vec<String>) -> Self {
+        self.exclude_columns = exclude_columns;
+        self
+    }
+
+    /// Sets new names for columns, keyed by the name in the CSV header, defaults to none.
+    ///
+    /// The columns are renamed right after the header is read, so [`ConvertOptions::columns`]
+    /// refer to the original names while [`ConvertOptions::column_types`] refer to the new ones.
+    /// Without a header, the generated names are renamed. A name which does not exist, or
+    /// renaming which results in duplicate names, fails the conversion.
+    pub fn renames(mut self, renames: HashMap<String, String>) -> Self {
+        self.renames = renames;
+        self
+    }
+
+    /// Sets the prefix of the column names generated for a file without a header, defaults to `column_`.
+    ///
+    /// The prefix is followed by the position of the column starting at `0`, such as `column_0`.
+    pub fn column_prefix(mut self, column_prefix: impl Into<String>) -> Self {
+        self.column_prefix = column_prefix.into();
+        self
+    }
+
+    /// Sets the column names of a file without a header, defaults to generated names.
+    ///
+    /// It is ignored if the file has a header. The conversion fails if the number of names differs
+    /// from the number of columns in the first row.
+    pub fn header_names(mut self, header_names: Vec<String>) -> Self {
+        self.header_names = header_names;
+        self
+    }
+
+    /// Sets whether a JSON document describing the schema is written next to every Parquet file, defaults to `false`.
+    ///
+    /// See [`write_schema_file`] for the name and format of the document.
+    pub fn emit_schema(mut self, emit_schema: bool) -> Self {
+        self.emit_schema = emit_schema;
+        self
+    }
+
+    /// Sets whether [`merge_to_parquet`] writes every CSV file as exactly one row group, defaults to `false`.
+    ///
+    /// The [`ConvertOptions::row_group_size`] is then ignored, so a whole file is buffered before its
+    /// row group is written. The source file of every row group is listed in the key-value metadata
+    /// under [`ROW_GROUP_FILES_KEY`], as a JSON array of paths in row group order. Files without
+    /// rows have no row group and are not listed.
+    pub fn row_group_per_file(mut self, row_group_per_file: bool) -> Self {
+        self.row_group_per_file = row_group_per_file;
+        self
+    }
+
+    /// Sets whether the conversion is recorded in the key-value metadata of the Parquet file, defaults to `true`.
+    ///
+    /// The metadata holds the path of the CSV file under `cc2p:source`, the time of the conversion
+    /// under `cc2p:converted_at`, the version of cc2p under `cc2p:version`, the delimiter the data was
+    /// read with under `cc2p:delimiter` and the number of rows under `cc2p:rows`. The source is left
+    /// out when converting from a reader, and the time with [`ConvertOptions::reproducible`].
+    pub fn metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets whether converting the same data twice writes byte-identical Parquet files, defaults to `false`.
+    ///
+    /// The time of the conversion is left out of the metadata, see [`ConvertOptions::metadata`], and
+    /// row groups are encoded on a single thread whatever the [`ConvertOptions::parallelism`], so
+    /// the output does not depend on the settings of the machine either.
+    pub fn reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Sets a declared schema used instead of inferring one from a sample, defaults to none.
+    ///
+    /// No rows are sampled. The declared columns are matched by position and their names replace
+    /// the names of the header, so the file must have as many columns as the schema. A value which
+    /// cannot be parsed as the declared type fails the conversion with its line and column. See
+    /// [`read_schema_file`] to read the schema from a JSON document.
+    pub fn schema(mut self, schema: Option<SchemaRef>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Sets whether every column is written as a string column without inferring types, defaults to `false`.
+    ///
+    /// No rows are sampled, so the data is read once whatever the [`SampleStrategy`], and values
+    /// are written as they are in the CSV file. Null values and [`ConvertOptions::column_types`]
+    /// still apply.
+    pub fn all_strings(mut self, all_strings: bool) -> Self {
+        self.all_strings = all_strings;
+        self
+    }
+
+    /// Sets the type of the columns without any value in the sample, defaults to [`DataType::Utf8`].
+    ///
+    /// A column which is empty in the sample has no type to infer, and values further down the
+    /// file are parsed with this type. [`DataType::Null`] keeps such columns null, dropping any
+    /// later values. [`ConvertOptions::column_types`] override it for single columns, and a
+    /// declared [`ConvertOptions::schema`] is used as it is. When merging, a column takes the type
+    /// of the files with values, and only columns empty in every file get this type.
+    pub fn null_column_type(mut self, null_column_type: DataType) -> Self {
+        self.null_column_type = null_column_type;
+        self
+    }
+
+    /// Sets what happens to rows with more or fewer fields than the header, defaults to [`OnRagged::Error`].
+    ///
+    /// Without a header, the first row sets the expected number of fields. Handled rows are counted
+    /// in [`ConversionSummary::ragged_rows`].
+    pub fn on_ragged(mut self, on_ragged: OnRagged) -> Self {
+        self.on_ragged = on_ragged;
+        self
+    }
+
+    /// Sets what happens to rows with invalid UTF-8 data, defaults to [`OnInvalidUtf8::Error`].
+    ///
+    /// It applies to UTF-8 input, data transcoded from another [`ConvertOptions::encoding`] is
+    /// always validated. The header and the rows above it are never skipped, their invalid bytes
+    /// are replaced instead. Handled data rows are counted in
+    /// [`ConversionSummary::invalid_utf8_rows`].
+    pub fn on_invalid_utf8(mut self, on_invalid_utf8: OnInvalidUtf8) -> Self {
+        self.on_invalid_utf8 = on_invalid_utf8;
+        self
+    }
+
+    /// Sets whether a row with fewer fields than the header is joined with the following rows,
+    /// defaults to `false`.
+    ///
+    /// This recovers rows of broken exports with line breaks inside unquoted fields: the last field
+    /// of a row and the first field of the next one become one field, separated by `\n`, as long as
+    /// the joined row has no more fields than the header. A row which still has too few fields is
+    /// handled by [`ConvertOptions::on_ragged`].
+    pub fn lenient_newlines(mut self, lenient_newlines: bool) -> Self {
+        self.lenient_newlines = lenient_newlines;
+        self
+    }
+
+    /// Sets what happens to columns whose name repeats an earlier column name, defaults to
+    /// [`OnDuplicateHeader::Rename`].
+    ///
+    /// Renamed and dropped columns are reported in [`ConversionSummary::warnings`]. The policy does
+    /// not apply to a declared schema, whose names replace the header.
+    pub fn on_duplicate_header(mut self, on_duplicate_header: OnDuplicateHeader) -> Self {
+        self.on_duplicate_header = on_duplicate_header;
+        self
+    }
+
+    /// Sets how empty fields of string columns are written, defaults to [`EmptyAs::Null`].
+    ///
+    /// Empty fields of other columns, such as integers or dates, are always null and are ignored
+    /// during schema inference.
+    pub fn empty_as(mut self, empty_as: EmptyAs) -> Self {
+        self.empty_as = empty_as;
+        self
+    }
+
+    /// Sets what [`convert_to_parquet`] does with a CSV file without data rows, such as an empty
+    /// file or one with only a header, defaults to [`OnEmpty::WriteEmpty`].
+    ///
+    /// Data written to a writer, such as with [`convert_to_writer`], is always written.
+    pub fn on_empty(mut self, on_empty: OnEmpty) -> Self {
+        self.on_empty = on_empty;
+        self
+    }
+
+    /// Sets the type of integer columns, defaults to [`IntSizing::Fixed64`].
+    pub fn int_sizing(mut self, int_sizing: IntSizing) -> Self {
+        self.int_sizing = int_sizing;
+        self
+    }
+
+    /// Sets whether a sampled number with a leading zero, such as `007`, infers a string column, defaults to `false`.
+    ///
+    /// Codes and identifiers such as `00123` otherwise lose their leading zeros as integers. A zero
+    /// on its own and numbers such as `0.5` do not count. [`ConvertOptions::column_types`] still
+    /// override the inferred type.
+    pub fn strict_leading_zeros(mut self, strict_leading_zeros: bool) -> Self {
+        self.strict_leading_zeros = strict_leading_zeros;
+        self
+    }
+
+    /// Sets whether a column is converted again as `int64` when one of its values does not fit the
+    /// integer type inferred with [`IntSizing::Minimal`], defaults to `false`, which fails the conversion.
+    ///
+    /// Only [`convert_to_parquet`] converts a file again, as the data of a writer cannot be taken back.
+    pub fn promote_on_overflow(mut self, promote_on_overflow: bool) -> Self {
+        self.promote_on_overflow = promote_on_overflow;
+        self
+    }
+
+    /// Sets whether columns of boolean tokens, such as `yes` and `no`, are inferred as booleans, defaults to `false`.
+    ///
+    /// A column is a boolean column if every sampled value is one of the true or false values, see
+    /// [`ConvertOptions::bool_values`]. Without it, only `true` and `false` are inferred as booleans.
+    pub fn parse_bools(mut self, parse_bools: bool) -> Self {
+        self.parse_bools = parse_bools;
+        self
+    }
+
+    /// Sets the tokens of true and false values used with [`ConvertOptions::parse_bools`], compared
+    /// case-insensitively, defaults to [`DEFAULT_TRUE_VALUES`] and [`DEFAULT_FALSE_VALUES`].
+    pub fn bool_values(mut self, true_values: Vec<String>, false_values: Vec<String>) -> Self {
+        self.true_values = true_values;
+        self.false_values = false_values;
+        self
+    }
+
+    /// Sets a flag which stops the conversion with an error once it is set, such as after a timeout.
+    ///
+    /// The flag is checked before every row is read, so a conversion stops shortly after it is set.
+    pub fn cancel_flag(mut self, cancel_flag: Arc<AtomicBool>) -> Self {
+        self.cancel_flag = Some(cancel_flag);
+        self
+    }
+
+    /// Sets a callback receiving the number of bytes of the CSV file read so far, such as to drive a
+    /// progress bar with the size of the file as its length.
+    ///
+    /// The bytes are counted as stored on disk, before a gzip file is decompressed. The count starts
+    /// again from zero when the file is read a second time, such as for the `random` and `full`
+    /// sample strategies. Data read from a reader is not reported.
+    pub fn progress(mut self, progress: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Callback(Arc::new(progress)));
+        self
+    }
+
+    /// Sets a callback receiving the schema of the Parquet file once it is inferred, before any row
+    /// is converted, such as to log why a column is not of the expected type.
+    ///
+    /// The schema includes the column types set in the options. The callback is called again when
+    /// the file is converted again, see [`ConvertOptions::promote_on_overflow`].
+    pub fn on_schema(mut self, on_schema: impl Fn(&Schema) + Send + Sync + 'static) -> Self {
+        self.on_schema = Some(Callback(Arc::new(on_schema)));
+        self
+    }
+}
+
+/// A callback set in the options, such as with [`ConvertOptions::progress`].
+struct Callback<F: ?Sized>(Arc<F>);
+
+/// The callback set with [`ConvertOptions::progress`].
+type ProgressCallback = Callback<dyn Fn(u64) + Send + Sync>;
+
+/// The callback set with [`ConvertOptions::on_schema`].
+type SchemaCallback = Callback<dyn Fn(&Schema) + Send + Sync>;
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> std::fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// A reader which reports the number of bytes read so far to the progress callback.
+struct ProgressReader<R> {
+    inner: R,
+    position: u64,
+    progress: ProgressCallback,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        if length > 0 {
+            self.position += length as u64;
+            (self.progress.0)(self.position);
         }
+
+        Ok(length)
     }
+}
 
-    parquet_writer.close()?;
+/// The separator between the fields of a CSV row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delimiter {
+    /// A single character, such as `,` or a tab.
+    Char(char),
+    /// A string of several characters, such as `||` or `::`.
+    Str(String),
+    /// Detect the delimiter of every file from its first lines, among `,`, `;`, a tab and `|`.
+    ///
+    /// A delimiter is detected if it is the only candidate occurring the same number of times on
+    /// every line. Otherwise a comma is used and a warning is added to the summary.
+    Auto,
+}
 
-    Ok(())
+impl From<char> for Delimiter {
+    fn from(delimiter: char) -> Self {
+        Delimiter::Char(delimiter)
+    }
 }
 
-/// Deletes a file if it exists.
-///
-/// # Arguments
-///
-/// * `filename` - The name of the file to delete.
-///
-/// # Errors
-///
-/// Returns `Err` if there is an error accessing the file or deleting it.
-///
-pub fn delete_if_exist(filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-    if fs::metadata(filename).is_ok() {
-        fs::remove_file(filename)?;
+impl From<&str> for Delimiter {
+    /// Converts a string with a single character to [`Delimiter::Char`].
+    fn from(delimiter: &str) -> Self {
+        let mut chars = delimiter.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Delimiter::Char(c),
+            _ => Delimiter::Str(delimiter.to_string()),
+        }
     }
+}
 
-    Ok(())
+impl std::fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Delimiter::Char(c) => write!(f, "{}", c),
+            Delimiter::Str(s) => write!(f, "{}", s),
+            Delimiter::Auto => write!(f, "auto"),
+        }
+    }
 }
 
-struct Empty {}
+/// Policy for a Parquet output file that already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfExists {
+    /// Replace the existing file.
+    Overwrite,
+    /// Keep the existing file and skip the conversion.
+    Skip,
+    /// Keep the existing file and fail the conversion.
+    Error,
+}
 
-/// Removes duplicate columns from a given Arrow schema, and returns a new schema with deduplicated columns.
-///
-/// # Arguments
-///
-/// * `sc` - The input Arrow schema.
-///
-/// # Returns
-///
-/// Returns an `Arc` containing the deduplicated schema.
-pub fn remove_deduplicate_columns(sc: arrow_schema::Schema) -> Arc<arrow_schema::Schema> {
-    let mut index = 1;
-    let mut deduplicated_fields = Vec::new();
-    let mut names = HashMap::new();
-    for field in sc.fields() {
-        let field_name = field.name().as_str();
-        let field_name = clean_column_name(field_name);
+impl std::str::FromStr for IfExists {
+    type Err = String;
 
-        if let std::collections::hash_map::Entry::Vacant(e) = names.entry(field_name.clone()) {
-            e.insert(Empty {});
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "overwrite" => Ok(IfExists::Overwrite),
+            "skip" => Ok(IfExists::Skip),
+            "error" => Ok(IfExists::Error),
+            _ => Err(format!(
+                "unknown policy '{}', expected one of: overwrite, skip, error",
+                value
+            )),
+        }
+    }
+}
 
-            if field.name().is_empty() {
-                let name = format!("column_{}", index);
-                index += 1;
-                let new_field =
-                    <arrow_schema::Field as Clone>::clone(&(*field).clone()).with_name(name);
-                deduplicated_fields.push(Arc::new(new_field));
-            } else {
-                deduplicated_fields.push(field.clone());
-            }
-        } else {
-            let name = format!("{}_{}", field_name, index);
-            index += 1;
-            let new_field =
-                <arrow_schema::Field as Clone>::clone(&(*field).clone()).with_name(name);
-            deduplicated_fields.push(Arc::new(new_field));
+/// Compression of the CSV input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputCompression {
+    /// Detect gzip from a `.gz` file extension, or from the gzip magic bytes when reading from a reader.
+    Auto,
+    /// The input is gzip compressed.
+    Gzip,
+    /// The input is not compressed.
+    None,
+}
+
+impl std::str::FromStr for InputCompression {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(InputCompression::Auto),
+            "gzip" | "gz" => Ok(InputCompression::Gzip),
+            "none" => Ok(InputCompression::None),
+            _ => Err(format!(
+                "unknown input compression '{}', expected one of: auto, gzip, none",
+                value
+            )),
         }
     }
+}
+
+/// Which values have leading and trailing whitespace removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trim {
+    /// Keep all whitespace.
+    None,
+    /// Trim the field values of the data rows.
+    Fields,
+    /// Trim the header names.
+    Headers,
+    /// Trim both the field values and the header names.
+    Both,
+}
+
+impl Trim {
+    fn fields(self) -> bool {
+        matches!(self, Trim::Fields | Trim::Both)
+    }
+
+    fn headers(self) -> bool {
+        matches!(self, Trim::Headers | Trim::Both)
+    }
+}
+
+impl std::str::FromStr for Trim {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(Trim::None),
+            "fields" => Ok(Trim::Fields),
+            "headers" => Ok(Trim::Headers),
+            "both" => Ok(Trim::Both),
+            _ => Err(format!(
+                "unknown trim mode '{}', expected one of: none, fields, headers, both",
+                value
+            )),
+        }
+    }
+}
+
+/// A normalization of the header names, see [`ConvertOptions::normalize_headers`].
+///
+/// Several normalizations are applied in the order trim, snake, lower, whichever order they are given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeHeader {
+    /// Lowercase the names.
+    Lower,
+    /// Convert the names to lowercase snake case, such as `First Name` and `firstName` to `first_name`.
+    Snake,
+    /// Remove leading and trailing whitespace.
+    Trim,
+}
+
+impl std::str::FromStr for NormalizeHeader {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "lower" => Ok(NormalizeHeader::Lower),
+            "snake" => Ok(NormalizeHeader::Snake),
+            "trim" => Ok(NormalizeHeader::Trim),
+            _ => Err(format!(
+                "unknown header normalization '{}', expected one of: lower, snake, trim",
+                value
+            )),
+        }
+    }
+}
+
+/// Policy for a row with more or fewer fields than the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnRagged {
+    /// Drop the row.
+    Skip,
+    /// Fail the conversion.
+    Error,
+    /// Fill the missing trailing fields of a shorter row with nulls. A longer row fails the conversion.
+    Pad,
+    /// Drop the extra fields of a longer row. A shorter row fails the conversion.
+    Truncate,
+}
+
+impl std::str::FromStr for OnRagged {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "skip" => Ok(OnRagged::Skip),
+            "error" => Ok(OnRagged::Error),
+            "pad" => Ok(OnRagged::Pad),
+            "truncate" => Ok(OnRagged::Truncate),
+            _ => Err(format!(
+                "unknown ragged row policy '{}', expected one of: skip, error, pad, truncate",
+                value
+            )),
+        }
+    }
+}
+
+/// Policy for a row with invalid UTF-8 data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnInvalidUtf8 {
+    /// Fail the conversion.
+    Error,
+    /// Replace every invalid byte sequence with the replacement character U+FFFD.
+    Replace,
+    /// Drop the row.
+    SkipRow,
+}
+
+impl std::str::FromStr for OnInvalidUtf8 {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "error" => Ok(OnInvalidUtf8::Error),
+            "replace" => Ok(OnInvalidUtf8::Replace),
+            "skip-row" => Ok(OnInvalidUtf8::SkipRow),
+            _ => Err(format!(
+                "unknown invalid UTF-8 policy '{}', expected one of: error, replace, skip-row",
+                value
+            )),
+        }
+    }
+}
+
+/// Policy for a column whose name repeats an earlier column name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDuplicateHeader {
+    /// Fail the conversion.
+    Error,
+    /// Append a suffix such as `_1` to the name of the later column.
+    Rename,
+    /// Keep the first column and drop the later columns with the same name.
+    First,
+}
+
+impl std::str::FromStr for OnDuplicateHeader {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "error" => Ok(OnDuplicateHeader::Error),
+            "rename" => Ok(OnDuplicateHeader::Rename),
+            "first" => Ok(OnDuplicateHeader::First),
+            _ => Err(format!(
+                "unknown duplicate header policy '{}', expected one of: error, rename, first",
+                value
+            )),
+        }
+    }
+}
+
+/// Which rows of a CSV file are sampled for inferring the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// Sample the first `sampling` rows.
+    Head,
+    /// Sample `sampling` rows picked at random from the whole file, in a pass before the conversion.
+    Random,
+    /// Inspect every row of the file, in a pass before the conversion.
+    Full,
+}
+
+impl std::str::FromStr for SampleStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "head" => Ok(SampleStrategy::Head),
+            "random" => Ok(SampleStrategy::Random),
+            "full" => Ok(SampleStrategy::Full),
+            _ => Err(format!(
+                "unknown sample strategy '{}', expected one of: head, random, full",
+                value
+            )),
+        }
+    }
+}
+
+/// How the type of an integer column is inferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntSizing {
+    /// Every integer column is `int64`.
+    Fixed64,
+    /// The narrowest type covering the sampled values, such as `uint8` for values from 0 to 200
+    /// or `int16` for values from -200 to 200.
+    Minimal,
+}
+
+impl std::str::FromStr for IntSizing {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "fixed64" => Ok(IntSizing::Fixed64),
+            "minimal" => Ok(IntSizing::Minimal),
+            _ => Err(format!(
+                "unknown integer sizing '{}', expected one of: fixed64, minimal",
+                value
+            )),
+        }
+    }
+}
+
+/// How an empty field of a string column is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyAs {
+    /// Write a null.
+    Null,
+    /// Write an empty string. A column whose sampled values are all empty is a string column.
+    String,
+}
+
+impl std::str::FromStr for EmptyAs {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "null" => Ok(EmptyAs::Null),
+            "string" => Ok(EmptyAs::String),
+            _ => Err(format!(
+                "unknown empty field policy '{}', expected one of: null, string",
+                value
+            )),
+        }
+    }
+}
+
+/// What is done with a CSV file without data rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnEmpty {
+    /// Skip the file without writing a Parquet file, the summary is marked as skipped.
+    Skip,
+    /// Fail the conversion.
+    Error,
+    /// Write a Parquet file with the schema of the header and no rows.
+    WriteEmpty,
+}
+
+impl std::str::FromStr for OnEmpty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "skip" => Ok(OnEmpty::Skip),
+            "error" => Ok(OnEmpty::Error),
+            "write-empty" => Ok(OnEmpty::WriteEmpty),
+            _ => Err(format!(
+                "unknown empty file policy '{}', expected one of: skip, error, write-empty",
+                value
+            )),
+        }
+    }
+}
+
+/// The error of a CSV file without data rows, unless [`OnEmpty::WriteEmpty`] is used.
+#[derive(Debug)]
+struct EmptyInput;
+
+impl std::fmt::Display for EmptyInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("the file has no data rows")
+    }
+}
+
+impl std::error::Error for EmptyInput {}
+
+/// Statistics about a finished conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionSummary {
+    /// The number of data rows written to the Parquet file.
+    pub rows_written: u64,
+
+    /// The number of columns in the Parquet schema.
+    pub columns: usize,
+
+    /// The schema of the Parquet file, empty if the conversion was skipped.
+    pub schema: SchemaRef,
+
+    /// The path of the Parquet file. Empty when the data was written to a sink instead of a file.
+    pub output_path: PathBuf,
+
+    /// The size of the Parquet data in bytes.
+    pub bytes_written: u64,
+
+    /// Whether the conversion was skipped because the Parquet file already exists, or because the
+    /// CSV file has no data rows with [`OnEmpty::Skip`], which adds a warning.
+    pub skipped: bool,
+
+    /// The number of rows with more or fewer fields than the header, which were skipped, padded or truncated.
+    pub ragged_rows: u64,
+
+    /// The number of rows with invalid UTF-8 data, which were replaced or skipped, see
+    /// [`ConvertOptions::on_invalid_utf8`].
+    pub invalid_utf8_rows: u64,
+
+    /// The delimiter detected from the data with [`Delimiter::Auto`], `None` if it was not detected.
+    pub detected_delimiter: Option<char>,
+
+    /// Problems which did not stop the conversion, such as unknown column names in the options.
+    pub warnings: Vec<String>,
+}
+
+/// Converts a CSV file to Parquet format.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file to be converted.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns a [`ConversionSummary`] if the conversion is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     use cc2p::{convert_to_parquet, ConvertOptions};
+///     use parquet::basic::Compression;
+///
+///     let file_path = PathBuf::from("testdata/sample.csv");
+///     let options = ConvertOptions::new()
+///         .delimiter(',')
+///         .sampling(10)
+///         .compression(Compression::SNAPPY);
+///
+///     convert_to_parquet(&file_path, &options)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn convert_to_parquet(
+    file_path: &Path,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    let mut options = std::borrow::Cow::Borrowed(options);
+    let mut promoted = vec![];
+    loop {
+        let error = match convert_file(file_path, &options) {
+            Ok(mut summary) => {
+                summary.warnings.extend(promoted.iter().map(|column| {
+                    format!("column '{column}' was converted again as int64, as a value does not fit the inferred type")
+                }));
+                return Ok(summary);
+            }
+            Err(error) => error,
+        };
+
+        let Some(overflow) = integer_overflow(error.as_ref()) else {
+            return Err(error);
+        };
+        // a column with a declared type is never promoted
+        if !options.promote_on_overflow || options.column_types.contains_key(&overflow.column) {
+            return Err(overflow.to_string().into());
+        }
+        let column = overflow.column.clone();
+        log::info!(
+            "converting {} again with column '{}' as int64",
+            file_path.display(),
+            column
+        );
+        options
+            .to_mut()
+            .column_types
+            .insert(column.clone(), DataType::Int64);
+        promoted.push(column);
+    }
+}
+
+/// Returns the integer value which caused the error, if it does not fit the integer type of its column.
+fn integer_overflow<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a IntegerOverflow> {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(overflow) = error.downcast_ref::<IntegerOverflow>() {
+            return Some(overflow);
+        }
+        source = error.source();
+    }
+
+    None
+}
+
+/// Converts a CSV file to a Parquet file, see [`convert_to_parquet`].
+fn convert_file(
+    file_path: &Path,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    match write_file(file_path, options) {
+        Err(error) if error.is::<EmptyInput>() && options.on_empty == OnEmpty::Skip => {
+            Ok(ConversionSummary {
+                warnings: vec![EmptyInput.to_string()],
+                ..skipped_summary(target_file_of(file_path, options))
+            })
+        }
+        result => result,
+    }
+}
+
+/// Returns an [`EmptyInput`] error if nothing was converted and [`OnEmpty::WriteEmpty`] is not used.
+fn check_empty(
+    summary: &ConversionSummary,
+    options: &ConvertOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if summary.rows_written == 0 && options.on_empty != OnEmpty::WriteEmpty {
+        return Err(Box::new(EmptyInput));
+    }
+
+    Ok(())
+}
+
+/// Returns the Parquet file of a CSV file, the output of the options or the file next to it.
+fn target_file_of(file_path: &Path, options: &ConvertOptions) -> PathBuf {
+    match &options.output {
+        Some(output_file) => output_file.clone(),
+        None => parquet_file_path(file_path, &options.output_extension),
+    }
+}
+
+/// Writes the Parquet file of a CSV file, see [`convert_to_parquet`].
+fn write_file(
+    file_path: &Path,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    let target_file = target_file_of(file_path, options);
+
+    if let Some(partition_column) = &options.partition_by {
+        let options = &options.for_file(file_path);
+        let summary = write_partitioned(
+            open_csv_file(file_path, options)?,
+            Some(&|| open_csv_file(file_path, options)),
+            Some(file_path),
+            partition_column,
+            &target_file,
+            options,
+        )?;
+        check_empty(&summary, options)?;
+        return Ok(summary);
+    }
+
+    if !prepare_target(&target_file, options)? {
+        return Ok(skipped_summary(target_file));
+    }
+
+    let summary = write_file_atomically(&target_file, |file| {
+        let summary = convert_to_writer(file_path, options, file)?;
+        options.check_cancelled()?;
+        // the temporary file is removed, so nothing is written for an empty file
+        check_empty(&summary, options)?;
+        Ok(summary)
+    })?;
+    if options.emit_schema {
+        write_schema_file(&target_file, &summary.schema)?;
+    }
+
+    Ok(ConversionSummary {
+        output_path: target_file,
+        ..summary
+    })
+}
+
+/// Converts a CSV file to Parquet format and writes the Parquet data to the given sink.
+///
+/// The `output` option is ignored, the data is written to `writer` instead.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file to be converted.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+/// * `writer` - The sink receiving the Parquet data, for example a file or standard output.
+///
+/// # Returns
+///
+/// Returns a [`ConversionSummary`] if the conversion is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     use cc2p::{convert_to_writer, ConvertOptions};
+///
+///     let file_path = PathBuf::from("testdata/sample.csv");
+///     let mut buffer = Vec::new();
+///
+///     convert_to_writer(&file_path, &ConvertOptions::new(), &mut buffer)?;
+///     assert!(buffer.starts_with(b"PAR1"));
+///
+///     Ok(())
+/// }
+/// ```
+pub fn convert_to_writer<W: Write + Send>(
+    file_path: &Path,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    let options = &options.for_file(file_path);
+    write_parquet(
+        open_csv_file(file_path, options)?,
+        Some(&|| open_csv_file(file_path, options)),
+        Some(file_path),
+        options,
+        writer,
+    )
+}
+
+/// Converts CSV data read from any reader, such as standard input, to Parquet data written to the given sink.
+///
+/// The reader is consumed only once: the first `sampling` rows are buffered in memory to
+/// infer the schema and are then replayed in front of the remaining data. The `output` option
+/// is ignored, the data is written to `writer` instead.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the CSV data, for example a `Cursor<Vec<u8>>` or standard input.
+/// * `writer` - The sink receiving the Parquet data, for example a file or standard output.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns a [`ConversionSummary`] if the conversion is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     use cc2p::{convert_reader_to_writer, ConvertOptions};
+///
+///     let reader = Cursor::new("id,name\n1,Jane\n2,John\n");
+///     let mut writer = Cursor::new(Vec::new());
+///
+///     let summary = convert_reader_to_writer(reader, &mut writer, &ConvertOptions::new())?;
+///     assert_eq!(summary.rows_written, 2);
+///     assert!(writer.get_ref().starts_with(b"PAR1"));
+///
+///     Ok(())
+/// }
+/// ```
+pub fn convert_reader_to_writer<R: Read, W: Write + Send>(
+    reader: R,
+    writer: W,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    write_parquet(
+        decompress_reader(reader, options)?,
+        None,
+        None,
+        options,
+        writer,
+    )
+}
+
+/// Converts CSV data held in memory, such as the body of an HTTP request, to Parquet data written
+/// to the given sink.
+///
+/// Unlike [`convert_reader_to_writer`], the data can be read twice, so the random and full
+/// [`SampleStrategy`] are supported as for files. The `output` option is ignored, the data is
+/// written to `writer` instead.
+///
+/// # Arguments
+///
+/// * `data` - The CSV data, gzip compressed if detected or set with [`ConvertOptions::input_compression`].
+/// * `writer` - The sink receiving the Parquet data, for example a `Vec<u8>`.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns a [`ConversionSummary`] if the conversion is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     use cc2p::{convert_bytes_to_parquet, ConvertOptions, SampleStrategy};
+///
+///     let data = b"id,code\n1,10\n2,A7\n";
+///     let options = ConvertOptions::new()
+///         .sampling(1)
+///         .sample_strategy(SampleStrategy::Full);
+///     let mut parquet = Vec::new();
+///
+///     let summary = convert_bytes_to_parquet(data, &mut parquet, &options)?;
+///     assert_eq!(summary.rows_written, 2);
+///     assert!(parquet.starts_with(b"PAR1"));
+///
+///     Ok(())
+/// }
+/// ```
+pub fn convert_bytes_to_parquet<W: Write + Send>(
+    data: &[u8],
+    writer: W,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    write_parquet(
+        decompress_reader(data, options)?,
+        Some(&|| decompress_reader(data, options)),
+        None,
+        options,
+        writer,
+    )
+}
+
+/// The schema inferred from the sample of a CSV file, without converting it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvInspection {
+    /// The schema the Parquet file would have, after deduplicating names and applying column types.
+    pub schema: Arc<Schema>,
+
+    /// The number of data rows used for inferring the schema.
+    pub sampled_rows: usize,
+
+    /// The delimiter detected from the data with [`Delimiter::Auto`], `None` if it was not detected.
+    pub detected_delimiter: Option<char>,
+
+    /// The number of data rows in the file, estimated from the size of the sampled rows. Exact if
+    /// the whole file was sampled, and `None` if the size of the data is unknown.
+    pub estimated_rows: Option<u64>,
+
+    /// Problems which would not stop the conversion, such as unknown column names in the options.
+    pub warnings: Vec<String>,
+}
+
+/// Infers the schema of a CSV file from its sample, without writing any Parquet data.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file to inspect.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns a [`CsvInspection`] if the sample could be read, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use cc2p::{inspect_csv, ConvertOptions};
+///
+/// let inspection = inspect_csv(Path::new("testdata/sample.csv"), &ConvertOptions::new()).unwrap();
+/// assert_eq!(inspection.schema.fields().len(), 3);
+/// assert_eq!(inspection.estimated_rows, Some(5));
+/// ```
+pub fn inspect_csv(
+    file_path: &Path,
+    options: &ConvertOptions,
+) -> Result<CsvInspection, Box<dyn std::error::Error>> {
+    // the rows are estimated from the size of the file, which only matches the size of the
+    // parsed data if the file is neither compressed nor transcoded
+    let size = if is_gzip_input(file_path, options) || options.encoding.is_some() {
+        None
+    } else {
+        Some(source_size(file_path)?)
+    };
+
+    let options = &options.for_file(file_path);
+    inspect(
+        open_csv_file(file_path, options)?,
+        Some(&|| open_csv_file(file_path, options)),
+        options,
+        size,
+    )
+}
+
+/// Infers the Arrow schema a CSV file would be converted with, without writing any Parquet data.
+///
+/// Only the sampling and inference stage of [`convert_to_parquet`] runs, with the same options,
+/// so the schema is the one the Parquet file gets, after deduplicating names and applying column
+/// types. See [`inspect_csv`] for the sampled and estimated rows and the warnings as well.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns the [`Schema`] if the sample could be read, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use arrow_schema::DataType;
+/// use cc2p::{infer_schema, ConvertOptions};
+///
+/// let schema = infer_schema(Path::new("testdata/sample.csv"), &ConvertOptions::new()).unwrap();
+/// assert_eq!(schema.field_with_name("age").unwrap().data_type(), &DataType::Int64);
+/// ```
+pub fn infer_schema(
+    file_path: &Path,
+    options: &ConvertOptions,
+) -> Result<Schema, Box<dyn std::error::Error>> {
+    let inspection = inspect_csv(file_path, options)?;
+
+    Ok(Arc::try_unwrap(inspection.schema).unwrap_or_else(|schema| schema.as_ref().clone()))
+}
+
+/// Infers the schema of CSV data read from any reader, such as standard input, without writing any Parquet data.
+///
+/// Only the sample is read. As the size of the data is unknown, the number of rows is only known
+/// if the whole data fits in the sample.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the CSV data.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+///
+/// # Returns
+///
+/// Returns a [`CsvInspection`] if the sample could be read, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+pub fn inspect_reader<R: Read>(
+    reader: R,
+    options: &ConvertOptions,
+) -> Result<CsvInspection, Box<dyn std::error::Error>> {
+    inspect(decompress_reader(reader, options)?, None, options, None)
+}
+
+/// Infers the schema from the sample, estimating the rows from `size`, the number of bytes of the data.
+fn inspect<R: Read>(
+    reader: R,
+    reopen: Reopen,
+    options: &ConvertOptions,
+    size: Option<u64>,
+) -> Result<CsvInspection, Box<dyn std::error::Error>> {
+    let mut csv = CsvSource::new(reader, options)?;
+
+    let mut warnings = vec![];
+    let schema = conversion_schema(&mut csv, reopen, options, &mut warnings)?;
+
+    Ok(CsvInspection {
+        schema,
+        sampled_rows: csv.sample_size(),
+        detected_delimiter: csv.detected_delimiter(),
+        estimated_rows: csv.estimate_rows(size),
+        warnings,
+    })
+}
+
+/// Converts the first `rows` rows of a CSV file in memory and formats the Parquet data read back
+/// as a table, to check the conversion of a file without writing it.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file to preview.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`].
+/// * `rows` - The maximum number of rows of the table.
+///
+/// # Returns
+///
+/// Returns the table, see [`preview_parquet`], otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use cc2p::{preview_csv, ConvertOptions};
+///
+/// let table = preview_csv(Path::new("testdata/sample.csv"), &ConvertOptions::new(), 1).unwrap();
+/// assert_eq!(
+///     table,
+///     "+------+-----+----------+\n\
+///      | name | age | job      |\n\
+///      +------+-----+----------+\n\
+///      | John | 25  | Engineer |\n\
+///      +------+-----+----------+\n"
+/// );
+/// ```
+pub fn preview_csv(
+    file_path: &Path,
+    options: &ConvertOptions,
+    rows: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let limit = options
+        .limit
+        .map_or(rows as u64, |limit| limit.min(rows as u64));
+    let mut data = vec![];
+    convert_to_writer(file_path, &options.clone().limit(Some(limit)), &mut data)?;
+
+    preview_parquet(bytes::Bytes::from(data), rows)
+}
+
+/// Reads the first `rows` rows of Parquet data and formats them as a table with aligned columns.
+///
+/// The table has a header row with the column names. Values are formatted as Arrow displays them,
+/// with nulls left empty and line breaks escaped as `\n`, so every row takes one line.
+///
+/// # Arguments
+///
+/// * `data` - The Parquet data, for example a [`File`].
+/// * `rows` - The maximum number of rows of the table.
+///
+/// # Returns
+///
+/// Returns the table, otherwise returns an `Err` with a `Box<dyn std::error::Error>` if the data is no valid Parquet.
+pub fn preview_parquet<R: parquet::file::reader::ChunkReader + 'static>(
+    data: R,
+    rows: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let builder = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(data)?;
+    let schema = builder.schema().clone();
+    let reader = builder
+        .with_batch_size(rows.max(1))
+        .with_limit(rows)
+        .build()?;
+
+    let mut table: Vec<Vec<String>> = vec![schema
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect()];
+    let format_options = arrow_cast::display::FormatOptions::default();
+    for batch in reader {
+        let batch = batch?;
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|column| arrow_cast::display::ArrayFormatter::try_new(column, &format_options))
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in 0..batch.num_rows() {
+            table.push(
+                formatters
+                    .iter()
+                    .map(|formatter| {
+                        formatter
+                            .value(row)
+                            .to_string()
+                            .replace('\r', "\\r")
+                            .replace('\n', "\\n")
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    let widths: Vec<usize> = (0..schema.fields().len())
+        .map(|column| {
+            table
+                .iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let separator: String = widths
+        .iter()
+        .map(|width| format!("+{}", "-".repeat(width + 2)))
+        .chain(std::iter::once(String::from("+\n")))
+        .collect();
+    let format_row = |row: &[String]| -> String {
+        row.iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("| {:<width$} ", value, width = width))
+            .chain(std::iter::once(String::from("|\n")))
+            .collect()
+    };
+
+    let mut output = separator.clone();
+    output.push_str(&format_row(&table[0]));
+    output.push_str(&separator);
+    for row in &table[1..] {
+        output.push_str(&format_row(row));
+    }
+    if table.len() > 1 {
+        output.push_str(&separator);
+    }
+
+    Ok(output)
+}
+
+/// The outcome of merging several CSV files into a single Parquet file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Statistics about the merged Parquet file.
+    pub summary: ConversionSummary,
+
+    /// The merged CSV files with the number of rows written from each, in the order they were appended.
+    pub merged: Vec<(PathBuf, u64)>,
+
+    /// The CSV files left out because their schema does not match, with the reason.
+    pub excluded: Vec<(PathBuf, String)>,
+}
+
+/// Merges CSV files sharing the same columns into a single Parquet file.
+///
+/// The schema of every file is inferred first and compared with the schema of the first file:
+/// the column names must be the same and in the same order, and every column must have the same
+/// type, except columns without any sampled value which take the type of the other files. Files
+/// with a different schema are excluded and listed in [`MergeSummary::excluded`], or fail the
+/// whole merge if `abort_on_mismatch` is set. The matching files are then appended to the Parquet
+/// file one after the other, each file starting a new row group, or forming exactly one with
+/// [`ConvertOptions::row_group_per_file`].
+///
+/// # Arguments
+///
+/// * `file_paths` - The paths of the CSV files to merge, in the order they are appended.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`]. The `output` option is required.
+/// * `abort_on_mismatch` - Whether a file with a different schema fails the merge instead of being excluded.
+///
+/// # Returns
+///
+/// Returns a [`MergeSummary`] if the merge is successful, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     use cc2p::{merge_to_parquet, ConvertOptions};
+///
+///     let files = vec![PathBuf::from("testdata/sample.csv")];
+///     let output = std::env::temp_dir().join("cc2p_doc_merge.parquet");
+///
+///     let merge = merge_to_parquet(&files, &ConvertOptions::new().output(&output), false)?;
+///     assert_eq!(merge.summary.rows_written, 5);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn merge_to_parquet(
+    file_paths: &[PathBuf],
+    options: &ConvertOptions,
+    abort_on_mismatch: bool,
+) -> Result<MergeSummary, Box<dyn std::error::Error>> {
+    let target_file = options
+        .output
+        .clone()
+        .ok_or("merging requires an output file")?;
+    if file_paths.is_empty() {
+        return Err("no CSV files to merge".into());
+    }
+    if options.partition_by.is_some() {
+        return Err("partitioned output cannot be merged".into());
+    }
+
+    let options = &if options.row_group_per_file {
+        options.clone().row_group_size(usize::MAX).flush_every(None)
+    } else {
+        options.clone()
+    };
+
+    let mut warnings = vec![];
+    let mut fields: Vec<Field> = vec![];
+    let mut included = vec![];
+    let mut excluded = vec![];
+    for file_path in file_paths {
+        let options = &options.for_file(file_path);
+        let reopen = || open_csv_file(file_path, options);
+        let mut csv = CsvSource::new(reopen()?, options)?;
+        let mut file_warnings = vec![];
+        let schema = parquet_schema(&mut csv, Some(&reopen), options, &mut file_warnings)?;
+
+        if included.is_empty() {
+            fields = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+            warnings = file_warnings;
+            included.push(file_path);
+            continue;
+        }
+
+        match merge_fields(&mut fields, &schema) {
+            Ok(()) => included.push(file_path),
+            Err(reason) if abort_on_mismatch => {
+                return Err(format!(
+                    "{} cannot be merged with {}: {}",
+                    file_path.display(),
+                    included[0].display(),
+                    reason
+                )
+                .into());
+            }
+            Err(reason) => excluded.push((file_path.clone(), reason)),
+        }
+    }
+
+    if !prepare_target(&target_file, options)? {
+        return Ok(MergeSummary {
+            summary: skipped_summary(target_file),
+            merged: vec![],
+            excluded,
+        });
+    }
+
+    // a column takes the type of the files with values, so only columns empty in every file remain
+    let schema = resolve_null_columns(Arc::new(Schema::new(fields)), options);
+    let props = writer_properties(options, &schema, &mut warnings);
+    let mut merged = vec![];
+    let mut row_group_files = vec![];
+    let mut ragged_rows = 0;
+    let mut invalid_utf8_rows = 0;
+    let writer = write_file_atomically(&target_file, |file| {
+        let mut parquet_writer = parquet::arrow::ArrowWriter::try_new(
+            CountingWriter::new(file),
+            schema.clone(),
+            Some(props),
+        )?;
+        for file_path in included {
+            let options = &options.for_file(file_path);
+            let reopen = || open_csv_file(file_path, options);
+            let mut csv = CsvSource::new(reopen()?, options)?;
+            // inferring again prepares the builder for the date columns of this file
+            parquet_schema(&mut csv, Some(&reopen), options, &mut vec![])?;
+
+            let mut rows = 0;
+            while let Some(batch) = csv
+                .next_batch(&schema)
+                .map_err(|e| format!("{}: {}", file_path.display(), e))?
+            {
+                parquet_writer.write(&batch)?;
+                rows += batch.num_rows() as u64;
+            }
+            // row groups never span two files
+            parquet_writer.flush()?;
+            if rows > 0 {
+                row_group_files.push(file_path.display().to_string());
+            }
+            merged.push((file_path.clone(), rows));
+            ragged_rows += csv.ragged_rows();
+            invalid_utf8_rows += csv.invalid_utf8_rows();
+        }
+        if options.row_group_per_file {
+            parquet_writer.append_key_value_metadata(KeyValue::new(
+                ROW_GROUP_FILES_KEY.to_string(),
+                serde_json::to_string(&row_group_files)?,
+            ));
+        }
+        let writer = parquet_writer.into_inner()?;
+        options.check_cancelled()?;
+        Ok(writer)
+    })?;
+    if options.emit_schema {
+        write_schema_file(&target_file, &schema)?;
+    }
+
+    Ok(MergeSummary {
+        summary: ConversionSummary {
+            rows_written: merged.iter().map(|(_, rows)| rows).sum(),
+            columns: schema.fields().len(),
+            schema: schema.clone(),
+            output_path: target_file,
+            bytes_written: writer.bytes_written,
+            skipped: false,
+            ragged_rows,
+            invalid_utf8_rows,
+            detected_delimiter: None,
+            warnings,
+        },
+        merged,
+        excluded,
+    })
+}
+
+/// Checks that the schema of a file matches the merged fields, filling in the types of columns
+/// which had no sampled values so far and widening integer types to hold the values of both.
+fn merge_fields(fields: &mut [Field], schema: &Schema) -> Result<(), String> {
+    let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+    if names.len() != fields.len() || fields.iter().zip(&names).any(|(f, n)| f.name() != *n) {
+        return Err(format!(
+            "columns {:?} differ from {:?}",
+            names,
+            fields.iter().map(|f| f.name()).collect::<Vec<_>>()
+        ));
+    }
+
+    for (field, other) in fields.iter().zip(schema.fields()) {
+        let (data_type, other_type) = (field.data_type(), other.data_type());
+        if data_type != other_type
+            && *data_type != DataType::Null
+            && *other_type != DataType::Null
+            && common_integer_type(data_type, other_type).is_none()
+        {
+            return Err(format!(
+                "column '{}' is {} instead of {}",
+                field.name(),
+                other_type,
+                data_type
+            ));
+        }
+    }
+    for (field, other) in fields.iter_mut().zip(schema.fields()) {
+        if *field.data_type() == DataType::Null {
+            *field = field.clone().with_data_type(other.data_type().clone());
+        } else if let Some(data_type) = common_integer_type(field.data_type(), other.data_type()) {
+            *field = field.clone().with_data_type(data_type);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the narrowest integer type holding the values of both integer types, such as `int16`
+/// for `uint8` and `int8`, or `None` if one of them is not an integer type.
+fn common_integer_type(data_type: &DataType, other_type: &DataType) -> Option<DataType> {
+    let range = |data_type: &DataType| match data_type {
+        DataType::Int64 => Some((i64::MIN, i64::MAX)),
+        data_type => integer_range(data_type),
+    };
+    let (min, max) = range(data_type)?;
+    let (other_min, other_max) = range(other_type)?;
+
+    Some(minimal_integer_type(min.min(other_min), max.max(other_max)))
+}
+
+/// The part files of a Parquet dataset directory, see [`read_dataset`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    /// The highest number of the part files, `0` if the directory has none.
+    pub last_part: u64,
+
+    /// The schema of the first part file, `None` if the directory has none.
+    pub schema: Option<SchemaRef>,
+}
+
+/// Returns the name of the part file with the given number in a dataset directory, such as `part-0001.parquet`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::part_file_name;
+///
+/// assert_eq!(part_file_name(1), "part-0001.parquet");
+/// assert_eq!(part_file_name(12345), "part-12345.parquet");
+/// ```
+pub fn part_file_name(number: u64) -> String {
+    format!("part-{:04}.parquet", number)
+}
+
+/// Reads the part files of a Parquet dataset directory, named as with [`part_file_name`].
+///
+/// Other files in the directory are ignored. A missing directory is an empty dataset.
+///
+/// # Arguments
+///
+/// * `dataset_dir` - The dataset directory.
+///
+/// # Returns
+///
+/// Returns the [`Dataset`], or an error if the directory or the first part file cannot be read.
+pub fn read_dataset(dataset_dir: &Path) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let mut parts = vec![];
+    if dataset_dir.exists() {
+        for entry in fs::read_dir(dataset_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let number = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("part-"))
+                .and_then(|name| name.strip_suffix(".parquet"))
+                .and_then(|number| number.parse::<u64>().ok());
+            if let Some(number) = number {
+                parts.push((number, entry.path()));
+            }
+        }
+    }
+    parts.sort();
+
+    let schema = match parts.first() {
+        Some((_, first_part)) => {
+            let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+                File::open(first_part)?,
+            )
+            .map_err(|e| format!("{}: {}", first_part.display(), e))?;
+            Some(reader.schema().clone())
+        }
+        None => None,
+    };
+
+    Ok(Dataset {
+        last_part: parts.last().map_or(0, |(number, _)| *number),
+        schema,
+    })
+}
+
+/// Converts a CSV file to a new part file of a Parquet dataset, written with the schema of the dataset.
+///
+/// The schema inferred from the CSV file has to fit the dataset: the same column names in the same
+/// order, and types which are converted without loss, such as a narrower integer type or any type
+/// for a string column of the dataset. An existing part file is never overwritten.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file.
+/// * `part_file` - The path of the new part file.
+/// * `schema` - The schema of the dataset.
+/// * `options` - The options controlling the conversion, see [`ConvertOptions`]. The `output`,
+///   `schema` and `if_exists` options are replaced.
+///
+/// # Returns
+///
+/// Returns a [`ConversionSummary`] if the conversion is successful, otherwise returns an `Err`
+/// describing the first column which does not fit the dataset.
+pub fn append_to_dataset(
+    file_path: &Path,
+    part_file: &Path,
+    schema: &SchemaRef,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    // a column without sampled values fits any column of the dataset
+    let inferred =
+        inspect_csv(file_path, &options.clone().null_column_type(DataType::Null))?.schema;
+    fits_dataset(schema, &inferred)
+        .map_err(|reason| format!("the schema does not match the dataset: {}", reason))?;
+
+    convert_to_parquet(
+        file_path,
+        &options
+            .clone()
+            .output(part_file)
+            .schema(Some(schema.clone()))
+            .if_exists(IfExists::Error),
+    )
+}
+
+/// Checks that the columns of the schema can be written with the schema of the dataset.
+fn fits_dataset(dataset: &Schema, schema: &Schema) -> Result<(), String> {
+    let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+    let dataset_names: Vec<&String> = dataset.fields().iter().map(|f| f.name()).collect();
+    if names != dataset_names {
+        return Err(format!(
+            "columns {:?} differ from {:?}",
+            names, dataset_names
+        ));
+    }
+
+    for (field, dataset_field) in schema.fields().iter().zip(dataset.fields()) {
+        let (data_type, dataset_type) = (field.data_type(), dataset_field.data_type());
+        let fits = data_type == dataset_type
+            || *data_type == DataType::Null
+            || *dataset_type == DataType::Utf8
+            || (*dataset_type == DataType::Float64 && data_type.is_integer())
+            || common_integer_type(data_type, dataset_type).as_ref() == Some(dataset_type);
+        if !fits {
+            return Err(format!(
+                "column '{}' is {} instead of {}",
+                field.name(),
+                data_type,
+                dataset_type
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the `if_exists` policy to an existing Parquet file and deletes it if it is overwritten.
+///
+/// Returns `false` if the conversion should be skipped.
+fn prepare_target(
+    target_file: &Path,
+    options: &ConvertOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if target_file.exists() {
+        match options.if_exists {
+            IfExists::Overwrite => {}
+            IfExists::Skip => return Ok(false),
+            IfExists::Error => {
+                return Err(format!("output file {} already exists", target_file.display()).into());
+            }
+        }
+    }
+
+    // an existing file is replaced once the new one is complete
+    Ok(true)
+}
+
+/// Returns the path of the temporary file a Parquet file is written to until it is complete.
+///
+/// The temporary file is hidden in the same directory, so it can be renamed atomically.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use cc2p::temporary_file_path;
+///
+/// let temporary_file = temporary_file_path(Path::new("out/data.parquet"));
+/// assert_eq!(temporary_file.parent(), Some(Path::new("out")));
+/// assert!(temporary_file.to_str().unwrap().ends_with(".tmp"));
+/// ```
+pub fn temporary_file_path(target_file: &Path) -> PathBuf {
+    let file_name = target_file
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    target_file.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+/// Writes a file through a temporary file, which is renamed to `target_file` once `write` succeeds.
+///
+/// On error the temporary file is deleted and an existing `target_file` is left unchanged, so an
+/// incomplete file never appears at `target_file`.
+///
+/// # Arguments
+///
+/// * `target_file` - The path of the file to write, see [`temporary_file_path`].
+/// * `write` - Writes the contents to the temporary file.
+///
+/// # Returns
+///
+/// Returns the result of `write`, or an `Err` if it or the rename failed.
+pub fn write_file_atomically<T>(
+    target_file: &Path,
+    write: impl FnOnce(File) -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let temporary_file = temporary_file_path(target_file);
+    let result = File::create(&temporary_file)
+        .map_err(|e| e.into())
+        .and_then(write)
+        .and_then(|value| {
+            fs::rename(&temporary_file, target_file)?;
+            Ok(value)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary_file);
+    }
+
+    result
+}
+
+/// The summary of a conversion skipped because the Parquet file already exists.
+fn skipped_summary(output_path: PathBuf) -> ConversionSummary {
+    ConversionSummary {
+        rows_written: 0,
+        columns: 0,
+        schema: Arc::new(Schema::empty()),
+        output_path,
+        bytes_written: 0,
+        skipped: true,
+        ragged_rows: 0,
+        invalid_utf8_rows: 0,
+        detected_delimiter: None,
+        warnings: vec![],
+    }
+}
+
+/// A column of the schema document written by [`write_schema_file`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaColumn {
+    /// The name of the column.
+    pub name: String,
+
+    /// The type of the column, using the names of [`parse_data_type`] such as `int64` or `utf8`.
+    #[serde(rename = "type")]
+    pub data_type: String,
+
+    /// Whether the column may contain nulls.
+    pub nullable: bool,
+}
+
+/// The schema document written next to a Parquet file by [`write_schema_file`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaDocument {
+    /// The columns of the Parquet file, in order.
+    pub columns: Vec<SchemaColumn>,
+}
+
+impl From<&Schema> for SchemaDocument {
+    fn from(schema: &Schema) -> Self {
+        Self {
+            columns: schema
+                .fields()
+                .iter()
+                .map(|field| SchemaColumn {
+                    name: field.name().clone(),
+                    data_type: data_type_name(field.data_type()),
+                    nullable: field.is_nullable(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Writes a JSON document describing the schema next to a Parquet file and returns its path.
+///
+/// The document of `data.parquet` is written to `data.parquet.schema.json`, so files differing only
+/// by their extension get their own document. It lists the name, type and nullability of every
+/// column:
+///
+/// ```json
+/// {
+///   "columns": [
+///     { "name": "id", "type": "int64", "nullable": true }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `parquet_file` - The path of the Parquet file the schema belongs to.
+/// * `schema` - The schema of the Parquet file.
+///
+/// # Returns
+///
+/// Returns the path of the schema document, otherwise returns an `Err` with a `Box<dyn std::error::Error>`.
+pub fn write_schema_file(
+    parquet_file: &Path,
+    schema: &Schema,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut schema_file = parquet_file.as_os_str().to_os_string();
+    schema_file.push(".schema.json");
+    let schema_file = PathBuf::from(schema_file);
+    let mut writer = std::io::BufWriter::new(File::create(&schema_file)?);
+    serde_json::to_writer_pretty(&mut writer, &SchemaDocument::from(schema))?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(schema_file)
+}
+
+/// Computes the SHA-256 hash of a file, such as a Parquet file written by a conversion.
+///
+/// The file is read in chunks, so it is never held in memory as a whole.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// Returns the hash as a lowercase hexadecimal string, otherwise returns an `Err` with a
+/// `std::io::Error` if the file cannot be read.
+///
+/// # Example
+///
+/// ```
+/// use cc2p::sha256_file;
+/// use std::path::Path;
+///
+/// let hash = sha256_file(Path::new("testdata/sample.csv")).unwrap();
+/// assert_eq!(hash.len(), 64);
+/// ```
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hash = sha256::Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let length = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(length) => length,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hash.update(&buffer[..length]);
+    }
+
+    Ok(hash
+        .finish()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Writes the SHA-256 hash of every file to a checksum manifest and returns the number of files.
+///
+/// Every line holds a hash and a path, separated by two spaces, in the format of `sha256sum`, so
+/// `sha256sum -c` verifies the files from the directory the paths are relative to:
+///
+/// ```text
+/// 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae  out/data.parquet
+/// ```
+///
+/// # Arguments
+///
+/// * `manifest` - The path of the checksum manifest, which is replaced once it is complete.
+/// * `files` - The files to hash, in the order of the manifest.
+///
+/// # Returns
+///
+/// Returns the number of files, otherwise returns an `Err` with a `Box<dyn std::error::Error>` if a
+/// file cannot be read or the manifest cannot be written.
+pub fn write_checksums(
+    manifest: &Path,
+    files: &[PathBuf],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut lines = String::new();
+    for file in files {
+        let hash =
+            sha256_file(file).map_err(|e| format!("cannot hash {}: {}", file.display(), e))?;
+        lines.push_str(&format!("{}  {}\n", hash, file.display()));
+    }
+    write_file_atomically(manifest, |mut writer| {
+        writer.write_all(lines.as_bytes())?;
+        Ok(())
+    })?;
+
+    Ok(files.len())
+}
+
+/// Reads a list of columns, such as the selection of [`ConvertOptions::columns`], from a text file.
+///
+/// The columns are separated by line breaks or commas. Blank lines and lines starting with `#`
+/// are skipped, and the names are trimmed.
+///
+/// # Arguments
+///
+/// * `path` - The path of the text file.
+///
+/// # Returns
+///
+/// Returns the columns in the order of the file, otherwise returns an `Err` with a
+/// `Box<dyn std::error::Error>` if the file cannot be read or lists no columns.
+pub fn read_column_list(path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read column list {}: {}", path.display(), e))?;
+    let columns: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    if columns.is_empty() {
+        return Err(format!("column list {} has no columns", path.display()).into());
+    }
+
+    Ok(columns)
+}
+
+/// The options of the files matching a pattern of a [`Manifest`].
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    /// The file name, or a glob pattern such as `eu_*.csv`, of the files the options apply to.
+    pub pattern: glob::Pattern,
+
+    /// The delimiter of the files, see [`ConvertOptions::delimiter`].
+    pub delimiter: Option<Delimiter>,
+
+    /// Whether the files have a header, see [`ConvertOptions::has_header`].
+    pub has_header: Option<bool>,
+
+    /// The character encoding of the files, see [`ConvertOptions::encoding`].
+    pub encoding: Option<&'static Encoding>,
+}
+
+impl ManifestEntry {
+    /// Returns whether the pattern of the entry matches the path or the file name of the file.
+    pub fn matches(&self, file: &Path) -> bool {
+        self.pattern.matches_path(file)
+            || file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.pattern.matches(name))
+    }
+
+    /// Applies the options set by the entry, keeping the other options unchanged.
+    pub fn apply(&self, mut options: ConvertOptions) -> ConvertOptions {
+        if let Some(delimiter) = &self.delimiter {
+            options = options.delimiter(delimiter.clone());
+        }
+        if let Some(has_header) = self.has_header {
+            options = options.has_header(has_header);
+        }
+        if let Some(encoding) = self.encoding {
+            options = options.encoding(encoding);
+        }
+
+        options
+    }
+}
+
+/// Options of individual files overriding the options of a run, read by [`read_manifest`].
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    /// The entries in the order of the manifest.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Returns the options of the file, `options` with the first entry matching the file applied.
+    ///
+    /// A file without a matching entry is converted with `options` unchanged.
+    pub fn options_for(&self, file: &Path, options: &ConvertOptions) -> ConvertOptions {
+        match self.entries.iter().find(|entry| entry.matches(file)) {
+            Some(entry) => entry.apply(options.clone()),
+            None => options.clone(),
+        }
+    }
+}
+
+/// A row of a CSV manifest or an object of a JSON manifest.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestRecord {
+    file: String,
+    delimiter: Option<String>,
+    header: Option<bool>,
+    encoding: Option<String>,
+}
+
+impl ManifestRecord {
+    fn into_entry(self) -> Result<ManifestEntry, String> {
+        let non_empty = |value: Option<String>| value.filter(|value| !value.is_empty());
+        let pattern = glob::Pattern::new(&self.file)
+            .map_err(|e| format!("invalid pattern '{}': {}", self.file, e))?;
+
+        Ok(ManifestEntry {
+            pattern,
+            delimiter: non_empty(self.delimiter)
+                .map(|delimiter| parse_delimiter(&delimiter))
+                .transpose()?,
+            has_header: self.header,
+            encoding: non_empty(self.encoding)
+                .map(|encoding| parse_encoding(&encoding))
+                .transpose()?,
+        })
+    }
+}
+
+/// Reads the options of individual files from a manifest.
+///
+/// A manifest with a `.json` extension is an array of objects, any other manifest is a CSV file
+/// with a header. Every entry has a `file`, the file name or a glob pattern matching the path or
+/// the name of the files, and optionally a `delimiter` as accepted by [`parse_delimiter`], a
+/// boolean `header` and an `encoding` as accepted by [`parse_encoding`]:
+///
+/// ```text
+/// file,delimiter,header,encoding
+/// eu_*.csv,;,,latin1
+/// raw.txt,|,false,
+/// ```
+///
+/// Empty or missing values keep the options of the run.
+///
+/// # Arguments
+///
+/// * `path` - The path of the manifest.
+///
+/// # Returns
+///
+/// Returns the manifest, otherwise returns an `Err` with a `Box<dyn std::error::Error>` if the
+/// manifest cannot be read or has an invalid entry.
+pub fn read_manifest(path: &Path) -> Result<Manifest, Box<dyn std::error::Error>> {
+    let invalid = |e: &dyn std::fmt::Display| format!("invalid manifest {}: {}", path.display(), e);
+    let file =
+        File::open(path).map_err(|e| format!("cannot read manifest {}: {}", path.display(), e))?;
+
+    let records: Vec<ManifestRecord> = if is_json_file(path) {
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| invalid(&e))?
+    } else {
+        csv::Reader::from_reader(file)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|e| invalid(&e))?
+    };
+    let entries = records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            record
+                .into_entry()
+                .map_err(|e| invalid(&format!("entry {}: {}", index + 1, e)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Manifest { entries })
+}
+
+/// Returns whether the file has a `.json` extension.
+fn is_json_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Reads a schema from a JSON document in the format written by [`write_schema_file`].
+///
+/// The `null` type of columns without sampled values is accepted besides the types of
+/// [`parse_data_type`].
+///
+/// # Arguments
+///
+/// * `path` - The path of the JSON document.
+///
+/// # Returns
+///
+/// Returns the schema, otherwise returns an `Err` with a `Box<dyn std::error::Error>` if the
+/// document cannot be read, has an unknown type or duplicate column names.
+pub fn read_schema_file(path: &Path) -> Result<Schema, Box<dyn std::error::Error>> {
+    let document: SchemaDocument = serde_json::from_reader(BufReader::new(File::open(path)?))
+        .map_err(|e| format!("invalid schema file {}: {}", path.display(), e))?;
+
+    let mut fields: Vec<Field> = Vec::with_capacity(document.columns.len());
+    for column in &document.columns {
+        if fields.iter().any(|field| field.name() == &column.name) {
+            return Err(format!("duplicate column '{}' in schema file", column.name).into());
+        }
+        let data_type = if column.data_type.eq_ignore_ascii_case("null") {
+            DataType::Null
+        } else {
+            parse_data_type(&column.data_type)
+                .map_err(|e| format!("column '{}' in schema file: {}", column.name, e))?
+        };
+        fields.push(Field::new(&column.name, data_type, column.nullable));
+    }
+
+    Ok(Schema::new(fields))
+}
+
+/// Returns the name of a data type as accepted by [`parse_data_type`].
+///
+/// Types which cannot be parsed, such as the `null` type of columns without sampled values, use
+/// the lowercase Arrow name.
+fn data_type_name(data_type: &DataType) -> String {
+    let name = match data_type {
+        DataType::Utf8 => "utf8",
+        DataType::Int8 => "int8",
+        DataType::Int16 => "int16",
+        DataType::Int32 => "int32",
+        DataType::Int64 => "int64",
+        DataType::UInt8 => "uint8",
+        DataType::UInt16 => "uint16",
+        DataType::UInt32 => "uint32",
+        DataType::UInt64 => "uint64",
+        DataType::Float32 => "float32",
+        DataType::Float64 => "float64",
+        DataType::Boolean => "bool",
+        DataType::Date32 => "date",
+        DataType::Timestamp(TimeUnit::Microsecond, None) => "timestamp",
+        DataType::Decimal128(precision, scale) => return format!("decimal({precision},{scale})"),
+        other => return other.to_string().to_lowercase(),
+    };
+
+    name.to_string()
+}
+
+/// Splits the path of an entry of a ZIP archive, such as `data.zip/2024/sales.csv`, into the path
+/// of the archive and the name of the entry. Returns `None` for other paths.
+fn split_zip_path(file_path: &Path) -> Option<(&Path, String)> {
+    let archive = file_path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| is_zip_file(ancestor) && ancestor.is_file())?;
+    let name = file_path
+        .strip_prefix(archive)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some((archive, name))
+}
+
+/// Opens a CSV file, or the data of an entry of a ZIP archive, see [`find_zip_entries`].
+fn open_source(file_path: &Path) -> std::io::Result<Box<dyn Read>> {
+    match split_zip_path(file_path) {
+        Some((archive, name)) => zip::open_entry(archive, &name),
+        None => Ok(Box::new(File::open(file_path)?)),
+    }
+}
+
+/// Returns the size of a CSV file, or the uncompressed size of an entry of a ZIP archive.
+fn source_size(file_path: &Path) -> std::io::Result<u64> {
+    match split_zip_path(file_path) {
+        Some((archive, name)) => Ok(zip::find_entry(archive, &name)?.size),
+        None => Ok(fs::metadata(file_path)?.len()),
+    }
+}
+
+/// Opens a CSV file, decompressing it if it is gzip compressed and reporting the bytes read to the
+/// progress callback if one is set.
+fn open_csv_file(file_path: &Path, options: &ConvertOptions) -> std::io::Result<Box<dyn Read>> {
+    let file: Box<dyn Read> = match &options.progress {
+        Some(progress) => Box::new(ProgressReader {
+            inner: open_source(file_path)?,
+            position: 0,
+            progress: progress.clone(),
+        }),
+        None => open_source(file_path)?,
+    };
+
+    if is_gzip_input(file_path, options) {
+        Ok(Box::new(MultiGzDecoder::new(BufReader::with_capacity(
+            options.read_buffer_size,
+            file,
+        ))))
+    } else {
+        Ok(file)
+    }
+}
+
+/// Returns whether the CSV file is gzip compressed, from its extension unless the options set the compression.
+fn is_gzip_input(file_path: &Path, options: &ConvertOptions) -> bool {
+    match options.input_compression {
+        InputCompression::Auto => is_gzip_file(file_path),
+        InputCompression::Gzip => true,
+        InputCompression::None => false,
+    }
+}
+
+/// Wraps the reader in a gzip decoder if the data is gzip compressed.
+fn decompress_reader<'a, R: Read + 'a>(
+    reader: R,
+    options: &ConvertOptions,
+) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::with_capacity(options.read_buffer_size, reader);
+
+    let gzip = match options.input_compression {
+        InputCompression::Auto => reader.fill_buf()?.starts_with(&GZIP_MAGIC),
+        InputCompression::Gzip => true,
+        InputCompression::None => false,
+    };
+    if gzip {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The first bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Returns whether the file has a `.gz` extension.
+fn is_gzip_file(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Returns whether the file is a tab-separated file, optionally gzip compressed, judging by its extension.
+fn is_tsv_file(file_path: &Path) -> bool {
+    has_data_extension(file_path, "tsv")
+}
+
+/// Returns whether the file has the given extension, before a `.gz` extension if it is gzip compressed.
+fn has_data_extension(file_path: &Path, extension: &str) -> bool {
+    let file_path = if is_gzip_file(file_path) {
+        Path::new(file_path.file_stem().unwrap_or_default())
+    } else {
+        file_path
+    };
+
+    file_path.extension().is_some_and(|ext| ext == extension)
+}
+
+/// Returns the Parquet file path of a CSV file, with the given extension.
+///
+/// A `.csv` or `.tsv` extension is replaced by `.parquet`, together with a `.gz` extension after it.
+/// Any other file name is kept whole and `.parquet` is appended, so `dump.dat` is written to
+/// `dump.dat.parquet` and an input never ends up as its own output.
+fn parquet_file_path(file_path: &Path, extension: &str) -> PathBuf {
+    let file_path = if is_gzip_file(file_path) {
+        file_path.with_extension("")
+    } else {
+        file_path.to_path_buf()
+    };
+
+    let csv_data = file_path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("csv") || extension.eq_ignore_ascii_case("tsv")
+    });
+    if csv_data {
+        file_path.with_extension(extension)
+    } else {
+        let mut target_file = file_path.into_os_string();
+        target_file.push(".");
+        target_file.push(extension);
+        PathBuf::from(target_file)
+    }
+}
+
+/// Parses a delimiter, a single character or a string such as `||`.
+///
+/// The escape `\t` stands for a tab, so it can be passed on the command line without quoting
+/// issues, and `auto` detects the delimiter, see [`Delimiter::Auto`]. A delimiter of several
+/// characters must not contain line breaks.
+///
+/// # Arguments
+///
+/// * `value` - The delimiter.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::{parse_delimiter, Delimiter};
+///
+/// assert_eq!(parse_delimiter(";").unwrap(), Delimiter::Char(';'));
+/// assert_eq!(parse_delimiter("\\t").unwrap(), Delimiter::Char('\t'));
+/// assert_eq!(parse_delimiter("||").unwrap(), Delimiter::Str("||".to_string()));
+/// assert_eq!(parse_delimiter("auto").unwrap(), Delimiter::Auto);
+/// assert!(parse_delimiter("").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the delimiter, or an `Err` describing why the value is invalid.
+pub fn parse_delimiter(value: &str) -> Result<Delimiter, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(Delimiter::Auto);
+    }
+
+    let delimiter = Delimiter::from(value.replace("\\t", "\t").as_str());
+    match &delimiter {
+        Delimiter::Str(s) if s.is_empty() => Err("the delimiter must not be empty".to_string()),
+        Delimiter::Str(s) if s.contains(['\n', '\r']) => {
+            Err(format!("delimiter {:?} must not contain line breaks", s))
+        }
+        _ => Ok(delimiter),
+    }
+}
+
+/// Checks that the delimiter, the quote character and the comment character do not collide.
+///
+/// A character used for two of them, or a line break used for any of them, would silently split or
+/// join fields instead of failing. The quote character is `None` if quoting is disabled. A detected
+/// delimiter is checked once it is known.
+///
+/// # Arguments
+///
+/// * `delimiter` - The delimiter.
+/// * `quote` - The quote character, or `None` if quoting is disabled.
+/// * `comment` - The comment character, or `None` if comment lines are not skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::{validate_special_characters, Delimiter};
+///
+/// assert!(validate_special_characters(&Delimiter::Char(';'), Some('"'), Some('#')).is_ok());
+/// assert_eq!(
+///     validate_special_characters(&Delimiter::Char('#'), Some('"'), Some('#')).unwrap_err(),
+///     "the delimiter and the comment character are both '#'"
+/// );
+/// ```
+pub fn validate_special_characters(
+    delimiter: &Delimiter,
+    quote: Option<char>,
+    comment: Option<char>,
+) -> Result<(), String> {
+    let line_break = |character: Option<char>| matches!(character, Some('\n' | '\r'));
+    if matches!(delimiter, Delimiter::Char('\n' | '\r')) {
+        return Err("the delimiter must not be a line break".to_string());
+    }
+    if line_break(quote) {
+        return Err("the quote character must not be a line break".to_string());
+    }
+    if line_break(comment) {
+        return Err("the comment character must not be a line break".to_string());
+    }
+    if let Some(quote) = quote.filter(|&quote| Some(quote) == comment) {
+        return Err(format!(
+            "the quote character and the comment character are both {:?}",
+            quote
+        ));
+    }
+
+    match delimiter {
+        Delimiter::Char(delimiter) if Some(*delimiter) == quote => Err(format!(
+            "the delimiter and the quote character are both {:?}",
+            delimiter
+        )),
+        Delimiter::Char(delimiter) if Some(*delimiter) == comment => Err(format!(
+            "the delimiter and the comment character are both {:?}",
+            delimiter
+        )),
+        Delimiter::Str(delimiter) if quote.is_some_and(|quote| delimiter.contains(quote)) => {
+            Err(format!(
+                "the delimiter {:?} contains the quote character {:?}",
+                delimiter,
+                quote.unwrap()
+            ))
+        }
+        // a line starting with an empty field would be skipped as a comment
+        Delimiter::Str(delimiter)
+            if comment.is_some_and(|comment| delimiter.starts_with(comment)) =>
+        {
+            Err(format!(
+                "the delimiter {:?} starts with the comment character {:?}",
+                delimiter,
+                comment.unwrap()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parses a comma-separated list of null tokens, such as `NA,NULL,N/A`.
+///
+/// Tokens are trimmed and empty entries are ignored.
+///
+/// # Arguments
+///
+/// * `value` - The comma-separated list of tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_null_values;
+///
+/// assert_eq!(parse_null_values("NA, NULL,,\\N"), vec!["NA", "NULL", "\\N"]);
+/// ```
+pub fn parse_null_values(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// The format of the numbers in the CSV data, see [`ConvertOptions::number_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// The character between the groups of three digits, such as `,` in `1,234.56`, or `None`.
+    pub thousands_separator: Option<char>,
+    /// The character before the fractional digits, such as `.` in `1,234.56`.
+    pub decimal_point: char,
+}
+
+/// Parses a number format from the way it writes the number 1234.56, such as `1,234.56`,
+/// `1.234,56`, `1 234,56` or `1234,56` without a thousands separator.
+///
+/// # Arguments
+///
+/// * `value` - The number 1234.56 in the format.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::{parse_number_format, NumberFormat};
+///
+/// let format = parse_number_format("1.234,56").unwrap();
+/// assert_eq!(format, NumberFormat { thousands_separator: Some('.'), decimal_point: ',' });
+/// assert_eq!(parse_number_format("1234,56").unwrap().thousands_separator, None);
+/// assert!(parse_number_format("1,234,56").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the [`NumberFormat`], or an error if the value is not the number 1234.56.
+pub fn parse_number_format(value: &str) -> Result<NumberFormat, String> {
+    let invalid = || {
+        format!(
+            "invalid number format '{}', expected the number 1234.56 written in the format, such as 1,234.56 or 1.234,56",
+            value
+        )
+    };
+    let chars: Vec<char> = value.chars().collect();
+    let (thousands_separator, decimal_point) = match chars[..] {
+        ['1', separator, '2', '3', '4', decimal_point, '5', '6'] => {
+            (Some(separator), decimal_point)
+        }
+        ['1', '2', '3', '4', decimal_point, '5', '6'] => (None, decimal_point),
+        _ => return Err(invalid()),
+    };
+
+    let special = |c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | 'e' | 'E');
+    if special(decimal_point)
+        || thousands_separator
+            .is_some_and(|separator| special(separator) || separator == decimal_point)
+    {
+        return Err(invalid());
+    }
+
+    Ok(NumberFormat {
+        thousands_separator,
+        decimal_point,
+    })
+}
+
+/// A preset of the delimiter and the number format of the CSV files of a region, see
+/// [`ConvertOptions::locale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// European files, with `;` as the delimiter, `,` as the decimal point and `.` as the
+    /// thousands separator, such as `1.234,56`.
+    Eu,
+}
+
+impl Locale {
+    /// Returns the delimiter of the files of the locale.
+    pub fn delimiter(self) -> Delimiter {
+        match self {
+            Locale::Eu => Delimiter::Char(';'),
+        }
+    }
+
+    /// Returns the format of the numbers of the files of the locale.
+    pub fn number_format(self) -> NumberFormat {
+        match self {
+            Locale::Eu => NumberFormat {
+                thousands_separator: Some('.'),
+                decimal_point: ',',
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "eu" => Ok(Locale::Eu),
+            _ => Err(format!("unknown locale '{}', expected one of: eu", value)),
+        }
+    }
+}
+
+/// Reads all CSV records from the reader using the inferred schema and writes them as Parquet to `writer`.
+fn write_parquet<R: Read, W: Write + Send>(
+    reader: R,
+    reopen: Reopen,
+    source: Option<&Path>,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    if options.partition_by.is_some() {
+        return Err("partitioned output requires an output file instead of a writer".into());
+    }
+
+    let mut csv = CsvSource::new(reader, options)?;
+
+    let mut warnings = vec![];
+    let schema_ref = conversion_schema(&mut csv, reopen, options, &mut warnings)?;
+    if let Some(on_schema) = &options.on_schema {
+        (on_schema.0)(&schema_ref);
+    }
+
+    let props = writer_properties(options, &schema_ref, &mut warnings);
+
+    let columns = schema_ref.fields().len();
+    let (rows_written, writer) = if options.parallelism > 1 && !options.reproducible {
+        write_row_groups_parallel(
+            &mut csv,
+            &schema_ref,
+            props,
+            source,
+            options,
+            CountingWriter::new(writer),
+        )?
+    } else {
+        let mut parquet_writer = parquet::arrow::ArrowWriter::try_new(
+            CountingWriter::new(writer),
+            schema_ref.clone(),
+            Some(props),
+        )?;
+
+        let mut rows_written = 0;
+        while let Some(batch) = csv.next_batch(&schema_ref)? {
+            let row_groups = parquet_writer.flushed_row_groups().len();
+            parquet_writer.write(&batch)?;
+            if options.flush_every.is_some()
+                && parquet_writer.flushed_row_groups().len() > row_groups
+            {
+                parquet_writer.inner_mut().flush()?;
+            }
+            rows_written += batch.num_rows() as u64;
+        }
+        if options.metadata {
+            for kv in conversion_metadata(&csv, source, rows_written, options) {
+                parquet_writer.append_key_value_metadata(kv);
+            }
+        }
+
+        (rows_written, parquet_writer.into_inner()?)
+    };
+
+    Ok(ConversionSummary {
+        rows_written,
+        columns,
+        schema: schema_ref,
+        output_path: PathBuf::new(),
+        bytes_written: writer.bytes_written,
+        skipped: false,
+        ragged_rows: csv.ragged_rows(),
+        invalid_utf8_rows: csv.invalid_utf8_rows(),
+        detected_delimiter: csv.detected_delimiter(),
+        warnings,
+    })
+}
+
+/// Writes the rows of each distinct value of the partition column to its own Parquet file.
+///
+/// The file of a partition is `target_file` with a `column=value` directory inserted before the
+/// file name. The returned summary points at the parent directory of the partitions and is only
+/// marked as skipped if every partition was skipped because its file already exists.
+fn write_partitioned<R: Read>(
+    reader: R,
+    reopen: Reopen,
+    source: Option<&Path>,
+    partition_column: &str,
+    target_file: &Path,
+    options: &ConvertOptions,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    let mut csv = CsvSource::new(reader, options)?;
+
+    let mut warnings = vec![];
+    let schema = conversion_schema(&mut csv, reopen, options, &mut warnings)?;
+    if let Some(on_schema) = &options.on_schema {
+        (on_schema.0)(&schema);
+    }
+    let (index, _) = schema
+        .column_with_name(partition_column)
+        .ok_or_else(|| format!("partition column '{}' does not exist", partition_column))?;
+    let output_schema = if options.keep_partition_column {
+        schema.clone()
+    } else if schema.fields().len() == 1 {
+        return Err(format!(
+            "partition column '{}' is the only column, keep it to write any data",
+            partition_column
+        )
+        .into());
+    } else {
+        let columns: Vec<usize> = (0..schema.fields().len()).filter(|&i| i != index).collect();
+        Arc::new(schema.project(&columns)?)
+    };
+
+    let props = writer_properties(options, &output_schema, &mut warnings);
+
+    let output_dir = target_file.parent().unwrap_or(Path::new(""));
+    let file_name = target_file.file_name().ok_or("missing output file name")?;
+    let directory_name = escape_partition_path(partition_column);
+
+    // the partition files are written to temporary files, renamed once every partition is complete
+    let mut partition_files: Vec<(PathBuf, PathBuf)> = vec![];
+    let mut renamed_files: Vec<PathBuf> = vec![];
+    let result = (|| -> Result<(u64, u64, bool), Box<dyn std::error::Error>> {
+        // a partition whose file already exists and is skipped has no writer
+        type PartitionWriter = parquet::arrow::ArrowWriter<CountingWriter<File>>;
+        let mut writers: HashMap<Option<String>, Option<PartitionWriter>> = HashMap::new();
+        let mut partition_rows: HashMap<Option<String>, u64> = HashMap::new();
+        let mut rows_written = 0;
+        while let Some(rows) = csv.next_record_batches(options.batch_size)?.pop() {
+            let builder = csv.builder();
+            let mut partitions: BTreeMap<Option<String>, Vec<csv::StringRecord>> = BTreeMap::new();
+            for row in rows {
+                let value = row
+                    .get(index)
+                    .filter(|value| !builder.is_null(value))
+                    .map(str::to_string);
+                partitions.entry(value).or_default().push(row);
+            }
+
+            for (value, rows) in partitions {
+                if !writers.contains_key(&value) {
+                    if writers.len() == options.max_partitions {
+                        return Err(format!(
+                            "partition column '{}' has more than {} distinct values",
+                            partition_column, options.max_partitions
+                        )
+                        .into());
+                    }
+
+                    let value_name = match &value {
+                        Some(value) => escape_partition_path(value),
+                        None => HIVE_DEFAULT_PARTITION.to_string(),
+                    };
+                    let partition_file = output_dir
+                        .join(format!("{}={}", directory_name, value_name))
+                        .join(file_name);
+                    let writer = if prepare_target(&partition_file, options)? {
+                        fs::create_dir_all(partition_file.parent().unwrap())?;
+                        let temporary_file = temporary_file_path(&partition_file);
+                        let file = File::create(&temporary_file)?;
+                        partition_files.push((temporary_file, partition_file));
+                        Some(parquet::arrow::ArrowWriter::try_new(
+                            CountingWriter::new(file),
+                            output_schema.clone(),
+                            Some(props.clone()),
+                        )?)
+                    } else {
+                        None
+                    };
+                    writers.insert(value.clone(), writer);
+                }
+
+                if let Some(Some(writer)) = writers.get_mut(&value) {
+                    let mut batch = builder.build(&schema, &rows)?;
+                    if !options.keep_partition_column {
+                        batch.remove_column(index);
+                    }
+                    let row_groups = writer.flushed_row_groups().len();
+                    writer.write(&batch)?;
+                    if options.flush_every.is_some()
+                        && writer.flushed_row_groups().len() > row_groups
+                    {
+                        writer.inner_mut().flush()?;
+                    }
+                    rows_written += batch.num_rows() as u64;
+                    *partition_rows.entry(value).or_default() += batch.num_rows() as u64;
+                }
+            }
+        }
+
+        let skipped = !writers.is_empty() && writers.values().all(Option::is_none);
+        let mut bytes_written = 0;
+        for (value, writer) in writers {
+            let Some(mut writer) = writer else {
+                continue;
+            };
+            if options.metadata {
+                let rows = partition_rows.get(&value).copied().unwrap_or(0);
+                for kv in conversion_metadata(&csv, source, rows, options) {
+                    writer.append_key_value_metadata(kv);
+                }
+            }
+            bytes_written += writer.into_inner()?.bytes_written;
+        }
+        options.check_cancelled()?;
+        for (temporary_file, partition_file) in &partition_files {
+            fs::rename(temporary_file, partition_file)?;
+            renamed_files.push(partition_file.clone());
+            if options.emit_schema {
+                renamed_files.push(write_schema_file(partition_file, &output_schema)?);
+            }
+        }
+
+        Ok((rows_written, bytes_written, skipped))
+    })();
+    let (rows_written, bytes_written, skipped) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            // a partly renamed output would look like a complete dataset with missing partitions
+            for renamed_file in &renamed_files {
+                let _ = fs::remove_file(renamed_file);
+            }
+            for (temporary_file, partition_file) in &partition_files {
+                let _ = fs::remove_file(temporary_file);
+                // only removed if no other file is left in it
+                let _ = fs::remove_dir(partition_file.parent().unwrap());
+            }
+            return Err(error);
+        }
+    };
+
+    Ok(ConversionSummary {
+        rows_written,
+        columns: output_schema.fields().len(),
+        schema: output_schema,
+        output_path: if output_dir.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            output_dir.to_path_buf()
+        },
+        bytes_written,
+        skipped,
+        ragged_rows: csv.ragged_rows(),
+        invalid_utf8_rows: csv.invalid_utf8_rows(),
+        detected_delimiter: csv.detected_delimiter(),
+        warnings,
+    })
+}
+
+/// Escapes the characters of a partition directory name which are special in paths or to Hive as `%XX`.
+fn escape_partition_path(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_control() || "\"#%'*/:=?\\{[]^".contains(c) {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+/// Writes the CSV records as Parquet, encoding up to `parallelism` row groups at the same time.
+///
+/// The records are still read sequentially, so quoted fields spanning several lines are split
+/// correctly, while converting and encoding the values of each row group runs on its own thread.
+/// The row groups are appended to the file in their original order.
+fn write_row_groups_parallel<R: Read, W: Write + Send>(
+    csv: &mut CsvSource<R>,
+    schema: &SchemaRef,
+    props: WriterProperties,
+    source: Option<&Path>,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<(u64, W), Box<dyn std::error::Error>> {
+    let parquet_schema = arrow_to_parquet_schema(schema)?;
+    let props = Arc::new(props);
+    let mut file_writer =
+        SerializedFileWriter::new(writer, parquet_schema.root_schema_ptr(), props.clone())?;
+
+    let mut rows_written = 0;
+    loop {
+        let mut row_groups = vec![];
+        for _ in 0..options.parallelism {
+            let batches = csv.next_record_batches(options.max_row_group_size())?;
+            if batches.is_empty() {
+                break;
+            }
+            row_groups.push(batches);
+        }
+        if row_groups.is_empty() {
+            break;
+        }
+
+        let builder = csv.builder();
+        let encoded = std::thread::scope(|scope| {
+            let handles: Vec<_> = row_groups
+                .iter()
+                .map(|batches| {
+                    scope.spawn(|| {
+                        encode_row_group(builder, schema, &parquet_schema, &props, batches)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("row group encoder panicked"))
+                .collect::<Result<Vec<_>, ParquetError>>()
+        })?;
+
+        for (chunks, batches) in encoded.into_iter().zip(&row_groups) {
+            let mut row_group = file_writer.next_row_group()?;
+            for chunk in chunks {
+                chunk.append_to_row_group(&mut row_group)?;
+            }
+            row_group.close()?;
+            if options.flush_every.is_some() {
+                file_writer.inner_mut().flush()?;
+            }
+            rows_written += batches.iter().map(|rows| rows.len() as u64).sum::<u64>();
+        }
+    }
+    if options.metadata {
+        for kv in conversion_metadata(csv, source, rows_written, options) {
+            file_writer.append_key_value_metadata(kv);
+        }
+    }
+
+    Ok((rows_written, file_writer.into_inner()?))
+}
+
+/// Converts the batches of records of one row group and encodes them into column chunks.
+fn encode_row_group(
+    builder: &BatchBuilder,
+    schema: &SchemaRef,
+    parquet_schema: &SchemaDescriptor,
+    props: &WriterPropertiesPtr,
+    batches: &[Vec<csv::StringRecord>],
+) -> Result<Vec<ArrowColumnChunk>, ParquetError> {
+    let mut writers = get_column_writers(parquet_schema, props, schema)?;
+    for rows in batches {
+        let batch = builder.build(schema, rows)?;
+        let mut leaf_writers = writers.iter_mut();
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            for leaf in compute_leaves(field, column)? {
+                leaf_writers
+                    .next()
+                    .expect("a column writer for every leaf column")
+                    .write(&leaf)?;
+            }
+        }
+    }
+
+    writers.into_iter().map(|writer| writer.close()).collect()
+}
+
+/// Opens the CSV data again for a pass over the whole data, `None` if the data can only be read once.
+type Reopen<'a> = Option<&'a dyn Fn() -> std::io::Result<Box<dyn Read + 'a>>>;
+
+/// Infers the schema of the CSV data, deduplicates the column names and applies the column types.
+///
+/// A declared schema is used as is instead, apart from the column types. The random and full
+/// sample strategies read the data again with `reopen`.
+fn parquet_schema<R: Read>(
+    csv: &mut CsvSource<R>,
+    reopen: Reopen,
+    options: &ConvertOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Arc<Schema>, Box<dyn std::error::Error>> {
+    warnings.extend(csv.take_warnings());
+    let schema = match &options.schema {
+        Some(schema) => Arc::new(csv.declared_schema(schema)?),
+        None => {
+            let schema = match (options.sample_strategy, reopen) {
+                _ if options.all_strings => csv.string_schema(),
+                (SampleStrategy::Head, _) => csv.infer_schema(),
+                (strategy, Some(reopen)) => csv.infer_schema_from(
+                    CsvSource::new(reopen()?, options)?,
+                    strategy,
+                    options.sampling as usize,
+                )?,
+                (_, None) => {
+                    return Err(
+                        "the random and full sample strategies read the data twice, which is only supported for files and data in memory"
+                            .into(),
+                    )
+                }
+            };
+            let deduplicated = remove_deduplicate_columns(schema.clone());
+            for (field, renamed) in schema.fields().iter().zip(deduplicated.fields()) {
+                if !field.name().is_empty() && field.name() != renamed.name() {
+                    warnings.push(format!(
+                        "duplicate column '{}' is renamed to '{}'",
+                        field.name(),
+                        renamed.name()
+                    ));
+                }
+            }
+            deduplicated
+        }
+    };
+
+    if options.decimal_columns.is_empty() {
+        return Ok(apply_column_types(schema, &options.column_types, warnings));
+    }
+
+    let arguments = format!("{},{}", options.decimal_precision, options.decimal_scale);
+    let decimal_type = parse_decimal_type(&arguments).ok_or_else(|| {
+        format!("invalid decimal precision and scale {arguments}, expected a precision of 1 to 38")
+    })?;
+    let mut column_types: HashMap<String, DataType> = options
+        .decimal_columns
+        .iter()
+        .map(|name| (name.clone(), decimal_type.clone()))
+        .collect();
+    column_types.extend(options.column_types.clone());
+
+    Ok(apply_column_types(schema, &column_types, warnings))
+}
+
+/// Infers the schema like [`parquet_schema`] and gives the columns without a sampled value the
+/// [`ConvertOptions::null_column_type`].
+fn conversion_schema<R: Read>(
+    csv: &mut CsvSource<R>,
+    reopen: Reopen,
+    options: &ConvertOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Arc<Schema>, Box<dyn std::error::Error>> {
+    let schema = parquet_schema(csv, reopen, options, warnings)?;
+    if options.schema.is_some() {
+        return Ok(schema);
+    }
+
+    Ok(resolve_null_columns(schema, options))
+}
+
+/// Replaces the type of the null columns of the schema with [`ConvertOptions::null_column_type`].
+fn resolve_null_columns(schema: Arc<Schema>, options: &ConvertOptions) -> Arc<Schema> {
+    let has_null_columns = schema
+        .fields()
+        .iter()
+        .any(|field| field.data_type() == &DataType::Null);
+    if !has_null_columns || options.null_column_type == DataType::Null {
+        return schema;
+    }
+
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            DataType::Null => field
+                .as_ref()
+                .clone()
+                .with_data_type(options.null_column_type.clone()),
+            _ => field.as_ref().clone(),
+        })
+        .collect();
+
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Builds the Parquet writer properties of the options for the given schema.
+///
+/// Columns of the dictionary settings which are not in the schema are reported as warnings.
+fn writer_properties(
+    options: &ConvertOptions,
+    schema: &Schema,
+    warnings: &mut Vec<String>,
+) -> WriterProperties {
+    let mut builder = WriterProperties::builder()
+        .set_compression(options.compression)
+        .set_max_row_group_size(options.max_row_group_size())
+        .set_dictionary_enabled(options.dictionary)
+        .set_data_page_size_limit(options.page_size)
+        .set_writer_version(options.parquet_version)
+        .set_statistics_enabled(options.statistics)
+        .set_created_by("cc2p".to_string());
+
+    let mut dictionary_columns: Vec<(&String, &bool)> = options.dictionary_columns.iter().collect();
+    dictionary_columns.sort();
+    let names = field_names(schema);
+    for (name, enabled) in dictionary_columns {
+        if !names.contains(name.as_str()) {
+            warnings.push(format!(
+                "column '{}' in dictionary columns does not exist",
+                name
+            ));
+            continue;
+        }
+        builder = builder.set_column_dictionary_enabled(ColumnPath::from(name.as_str()), *enabled);
+    }
+
+    builder.build()
+}
+
+/// Describes the conversion of the data of `csv`, read from `source`, as Parquet key-value metadata.
+///
+/// See [`ConvertOptions::metadata`] for the keys.
+fn conversion_metadata<R: Read>(
+    csv: &CsvSource<R>,
+    source: Option<&Path>,
+    rows: u64,
+    options: &ConvertOptions,
+) -> Vec<KeyValue> {
+    let mut metadata = vec![];
+    if let Some(source) = source {
+        metadata.push(("cc2p:source", source.display().to_string()));
+    }
+    if !options.reproducible {
+        metadata.push(("cc2p:converted_at", chrono::Utc::now().to_rfc3339()));
+    }
+    metadata.push(("cc2p:version", env!("CARGO_PKG_VERSION").to_string()));
+    metadata.push(("cc2p:delimiter", csv.delimiter().to_string()));
+    metadata.push(("cc2p:rows", rows.to_string()));
+
+    metadata
+        .into_iter()
+        .map(|(key, value)| KeyValue::new(key.to_string(), value))
+        .collect()
+}
+
+/// Replaces the types of the named columns in the schema, adding a warning for each unknown name.
+fn apply_column_types(
+    schema: Arc<Schema>,
+    column_types: &HashMap<String, DataType>,
+    warnings: &mut Vec<String>,
+) -> Arc<Schema> {
+    if column_types.is_empty() {
+        return schema;
+    }
+
+    let names = field_names(&schema);
+    let mut unknown: Vec<&String> = column_types
+        .keys()
+        .filter(|name| !names.contains(name.as_str()))
+        .collect();
+    unknown.sort();
+    for name in unknown {
+        warnings.push(format!("column '{}' in column types does not exist", name));
+    }
+
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match column_types.get(field.name()) {
+            Some(data_type) => field.as_ref().clone().with_data_type(data_type.clone()),
+            None => field.as_ref().clone(),
+        })
+        .collect();
+
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Returns the names of the fields, to look up many names in schemas with thousands of fields.
+fn field_names(schema: &Schema) -> HashSet<&str> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect()
+}
+
+/// A writer which counts the bytes passed to the wrapped writer.
+struct CountingWriter<W> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Parses a Parquet format version, `1.0` or `2.0`, into a Parquet `WriterVersion`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_parquet_version;
+/// use parquet::file::properties::WriterVersion;
+///
+/// assert_eq!(parse_parquet_version("2.0").unwrap(), WriterVersion::PARQUET_2_0);
+/// assert_eq!(parse_parquet_version("1").unwrap(), WriterVersion::PARQUET_1_0);
+/// assert!(parse_parquet_version("3.0").is_err());
+/// ```
+pub fn parse_parquet_version(value: &str) -> Result<WriterVersion, String> {
+    match value.trim() {
+        "1" | "1.0" => Ok(WriterVersion::PARQUET_1_0),
+        "2" | "2.0" => Ok(WriterVersion::PARQUET_2_0),
+        _ => Err(format!(
+            "unknown Parquet version '{}', expected 1.0 or 2.0",
+            value.trim()
+        )),
+    }
+}
+
+/// Parses a column statistics level, `none`, `chunk` or `page`, into a Parquet `EnabledStatistics`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_statistics;
+/// use parquet::file::properties::EnabledStatistics;
+///
+/// assert_eq!(parse_statistics("None").unwrap(), EnabledStatistics::None);
+/// assert_eq!(parse_statistics("chunk").unwrap(), EnabledStatistics::Chunk);
+/// assert!(parse_statistics("all").is_err());
+/// ```
+pub fn parse_statistics(value: &str) -> Result<EnabledStatistics, String> {
+    match value.trim().to_lowercase().as_str() {
+        "none" => Ok(EnabledStatistics::None),
+        "chunk" => Ok(EnabledStatistics::Chunk),
+        "page" => Ok(EnabledStatistics::Page),
+        _ => Err(format!(
+            "unknown statistics level '{}', expected one of: none, chunk, page",
+            value.trim()
+        )),
+    }
+}
+
+/// Parses a compression codec name into a Parquet `Compression` value.
+///
+/// Supported codecs are `none`, `snappy`, `gzip`, `zstd`, `lz4` and `brotli` (case-insensitive).
+/// `zstd` and `brotli` accept an optional level suffix, for example `zstd:9` or `brotli:5`.
+///
+/// # Arguments
+///
+/// * `value` - The codec name, optionally followed by `:<level>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_compression;
+/// use parquet::basic::Compression;
+///
+/// assert_eq!(parse_compression("snappy").unwrap(), Compression::SNAPPY);
+/// assert!(parse_compression("zstd:9").is_ok());
+/// assert!(parse_compression("rar").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the matching `Compression`, or an `Err` describing why the value is invalid.
+pub fn parse_compression(value: &str) -> Result<Compression, String> {
+    let value = value.trim().to_lowercase();
+    let (codec, level) = match value.split_once(':') {
+        Some((codec, level)) => (codec, Some(level)),
+        None => (value.as_str(), None),
+    };
+
+    let compression = match (codec, level) {
+        ("none" | "uncompressed", None) => Compression::UNCOMPRESSED,
+        ("snappy", None) => Compression::SNAPPY,
+        ("gzip", None) => Compression::GZIP(Default::default()),
+        ("lz4", None) => Compression::LZ4_RAW,
+        ("zstd", None) => Compression::ZSTD(Default::default()),
+        ("brotli", None) => Compression::BROTLI(Default::default()),
+        ("zstd", Some(level)) => {
+            let level = level
+                .parse::<i32>()
+                .map_err(|_| format!("invalid zstd level '{}'", level))?;
+            Compression::ZSTD(ZstdLevel::try_new(level).map_err(|e| e.to_string())?)
+        }
+        ("brotli", Some(level)) => {
+            let level = level
+                .parse::<u32>()
+                .map_err(|_| format!("invalid brotli level '{}'", level))?;
+            Compression::BROTLI(BrotliLevel::try_new(level).map_err(|e| e.to_string())?)
+        }
+        ("none" | "uncompressed" | "snappy" | "gzip" | "lz4", Some(_)) => {
+            return Err(format!("compression '{}' does not support a level", codec));
+        }
+        _ => {
+            return Err(format!(
+                "unknown compression '{}', expected one of: none, snappy, gzip, zstd, lz4, brotli",
+                codec
+            ));
+        }
+    };
+
+    Ok(compression)
+}
+
+/// Parses an encoding label such as `utf-8`, `latin1` or `windows-1252` into an `Encoding`.
+///
+/// Labels follow the WHATWG Encoding Standard, so `latin1` and `iso-8859-1` select `windows-1252`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_encoding;
+///
+/// assert_eq!(parse_encoding("Latin1").unwrap().name(), "windows-1252");
+/// assert!(parse_encoding("klingon").is_err());
+/// ```
+pub fn parse_encoding(value: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(value.trim().as_bytes())
+        .ok_or_else(|| format!("unknown encoding '{}'", value.trim()))
+}
+
+/// Parses the type of the columns without sampled values, a data type name of [`parse_data_type`]
+/// or `null` to keep such columns null, see [`ConvertOptions::null_column_type`].
+///
+/// # Examples
+///
+/// ```rust
+/// use arrow_schema::DataType;
+/// use cc2p::parse_null_column_type;
+///
+/// assert_eq!(parse_null_column_type("int64").unwrap(), DataType::Int64);
+/// assert_eq!(parse_null_column_type("Null").unwrap(), DataType::Null);
+/// assert!(parse_null_column_type("nothing").is_err());
+/// ```
+pub fn parse_null_column_type(value: &str) -> Result<DataType, String> {
+    if value.trim().eq_ignore_ascii_case("null") {
+        return Ok(DataType::Null);
+    }
+
+    parse_data_type(value)
+}
+
+/// Parses a data type name such as `utf8`, `int64`, `float64` or `bool` into an Arrow `DataType`.
+///
+/// Supported names are `utf8` (or `string`), `int8`, `int16`, `int32`, `int64`, `uint8`, `uint16`,
+/// `uint32`, `uint64`, `float32`, `float64` (or `double`), `bool` (or `boolean`), `date` (or `date32`),
+/// `timestamp` and `decimal(precision,scale)` (case-insensitive).
+///
+/// # Examples
+///
+/// ```rust
+/// use arrow_schema::DataType;
+/// use cc2p::parse_data_type;
+///
+/// assert_eq!(parse_data_type("Float64").unwrap(), DataType::Float64);
+/// assert_eq!(parse_data_type("decimal(18,2)").unwrap(), DataType::Decimal128(18, 2));
+/// assert!(parse_data_type("money").is_err());
+/// ```
+pub fn parse_data_type(value: &str) -> Result<DataType, String> {
+    let name = value.trim().to_lowercase();
+    if let Some(arguments) = name
+        .strip_prefix("decimal(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return parse_decimal_type(arguments)
+            .ok_or_else(|| format!("invalid decimal type '{}', expected decimal(precision,scale) with a precision of 1 to 38", value.trim()));
+    }
+
+    let data_type = match name.as_str() {
+        "decimal" => DataType::Decimal128(DEFAULT_DECIMAL_PRECISION, DEFAULT_DECIMAL_SCALE),
+        "utf8" | "string" => DataType::Utf8,
+        "int8" => DataType::Int8,
+        "int16" => DataType::Int16,
+        "int32" => DataType::Int32,
+        "int64" => DataType::Int64,
+        "uint8" => DataType::UInt8,
+        "uint16" => DataType::UInt16,
+        "uint32" => DataType::UInt32,
+        "uint64" => DataType::UInt64,
+        "float32" => DataType::Float32,
+        "float64" | "double" => DataType::Float64,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" | "date32" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        _ => return Err(format!("unknown data type '{}'", value.trim())),
+    };
+
+    Ok(data_type)
+}
+
+/// Parses the `precision,scale` or `precision` arguments of a decimal type.
+fn parse_decimal_type(arguments: &str) -> Option<DataType> {
+    let (precision, scale) = match arguments.split_once(',') {
+        Some((precision, scale)) => (precision, scale.trim().parse().ok()?),
+        None => (arguments, 0),
+    };
+    let precision: u8 = precision.trim().parse().ok()?;
+    if !(1..=DECIMAL128_MAX_PRECISION).contains(&precision) || scale > precision as i8 || scale < 0
+    {
+        return None;
+    }
+
+    Some(DataType::Decimal128(precision, scale))
+}
+
+/// Parses a comma-separated list of column type overrides, such as `zip=utf8,amount=float64`.
+///
+/// # Arguments
+///
+/// * `value` - The list of `name=type` pairs, see [`parse_data_type`] for the supported types.
+///
+/// # Examples
+///
+/// ```rust
+/// use arrow_schema::DataType;
+/// use cc2p::parse_column_types;
+///
+/// let column_types = parse_column_types("zip=utf8, active=bool").unwrap();
+/// assert_eq!(column_types["zip"], DataType::Utf8);
+/// assert_eq!(column_types["active"], DataType::Boolean);
+/// ```
+///
+/// # Returns
+///
+/// Returns the types keyed by column name, or an `Err` describing the invalid entry.
+pub fn parse_column_types(value: &str) -> Result<HashMap<String, DataType>, String> {
+    let mut column_types = HashMap::new();
+    for entry in split_outside_parentheses(value).filter(|entry| !entry.trim().is_empty()) {
+        let Some((name, data_type)) = entry.split_once('=') else {
+            return Err(format!(
+                "invalid column type '{}', expected name=type",
+                entry.trim()
+            ));
+        };
+        column_types.insert(name.trim().to_string(), parse_data_type(data_type)?);
+    }
+
+    Ok(column_types)
+}
+
+/// Splits the value on the commas outside parentheses, such as the one of `decimal(18,2)`.
+fn split_outside_parentheses(value: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    value.split(move |c| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        c == ',' && depth == 0
+    })
+}
+
+/// Parses a comma-separated list of column renames, such as `cust_id=customer_id,amt=amount`.
+///
+/// # Arguments
+///
+/// * `value` - The list of `old=new` pairs.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_renames;
+///
+/// let renames = parse_renames("cust_id=customer_id, amt=amount").unwrap();
+/// assert_eq!(renames["amt"], "amount");
+/// assert!(parse_renames("amt=").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the new names keyed by the old names, or an `Err` describing the invalid entry.
+pub fn parse_renames(value: &str) -> Result<HashMap<String, String>, String> {
+    let mut renames = HashMap::new();
+    for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+        let Some((old, new)) = entry
+            .split_once('=')
+            .filter(|(old, new)| !old.trim().is_empty() && !new.trim().is_empty())
+        else {
+            return Err(format!(
+                "invalid rename '{}', expected old=new",
+                entry.trim()
+            ));
+        };
+        renames.insert(old.trim().to_string(), new.trim().to_string());
+    }
+
+    Ok(renames)
+}
+
+/// Parses a comma-separated list of per-column dictionary settings, such as `country=on,id=off`.
+///
+/// # Arguments
+///
+/// * `value` - The list of `name=setting` pairs, where the setting is `on` or `off`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_dictionary_columns;
+///
+/// let dictionary_columns = parse_dictionary_columns("country=on, id=off").unwrap();
+/// assert!(dictionary_columns["country"]);
+/// assert!(!dictionary_columns["id"]);
+/// assert!(parse_dictionary_columns("id=maybe").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the settings keyed by column name, or an `Err` describing the invalid entry.
+pub fn parse_dictionary_columns(value: &str) -> Result<HashMap<String, bool>, String> {
+    let mut dictionary_columns = HashMap::new();
+    for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+        let enabled = match entry.split_once('=') {
+            Some((name, setting)) if !name.trim().is_empty() => {
+                match setting.trim().to_lowercase().as_str() {
+                    "on" => Some((name, true)),
+                    "off" => Some((name, false)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let Some((name, enabled)) = enabled else {
+            return Err(format!(
+                "invalid dictionary setting '{}', expected name=on or name=off",
+                entry.trim()
+            ));
+        };
+        dictionary_columns.insert(name.trim().to_string(), enabled);
+    }
+
+    Ok(dictionary_columns)
+}
+
+/// Parses the extension of the Parquet files, such as `pq` or `.pq`.
+///
+/// # Arguments
+///
+/// * `value` - The extension, with or without the leading dot.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::parse_output_extension;
+///
+/// assert_eq!(parse_output_extension(".pq").unwrap(), "pq");
+/// assert_eq!(parse_output_extension("parquet.lz4").unwrap(), "parquet.lz4");
+/// assert!(parse_output_extension("out/pq").is_err());
+/// assert!(parse_output_extension("csv").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the extension without the leading dot, or an `Err` if it is empty, holds a path
+/// separator or is an extension of CSV data, which would overwrite the input.
+pub fn parse_output_extension(value: &str) -> Result<String, String> {
+    let extension = value.strip_prefix('.').unwrap_or(value);
+    if extension.is_empty() || extension.ends_with('.') {
+        return Err(format!("invalid output extension '{}'", value));
+    }
+    if extension.contains(['/', '\\']) {
+        return Err(format!(
+            "invalid output extension '{}', it must not hold a path separator",
+            value
+        ));
+    }
+    let lowercase = extension.to_lowercase();
+    if ["csv", "tsv", "gz"].contains(&lowercase.as_str()) {
+        return Err(format!(
+            "invalid output extension '{}', it is an extension of CSV files",
+            value
+        ));
+    }
+
+    Ok(extension.to_string())
+}
+
+/// Returns the path of the Parquet file written for the given CSV file.
+///
+/// The `.csv` or `.tsv` extension, or both extensions of a gzip compressed `.csv.gz` file, are
+/// replaced by the given extension. A file with another extension or none keeps its whole name, so
+/// `dump.dat` is written to `dump.dat.parquet`.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the CSV file.
+/// * `base_dir` - The directory the CSV file was searched from. If set, the path of the CSV file
+///   relative to it is preserved under `output_dir`, otherwise only the file name is kept.
+/// * `output_dir` - The directory for the Parquet file. If `None`, the file is placed next to the CSV file.
+/// * `extension` - The extension of the Parquet file without the leading dot, usually
+///   [`DEFAULT_OUTPUT_EXTENSION`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use cc2p::{target_file_path, DEFAULT_OUTPUT_EXTENSION};
+///
+/// let file = Path::new("testdata/nested/sample_nested.csv");
+/// assert_eq!(target_file_path(file, None, None, DEFAULT_OUTPUT_EXTENSION), PathBuf::from("testdata/nested/sample_nested.parquet"));
+///
+/// let output_dir = Path::new("out");
+/// assert_eq!(target_file_path(file, None, Some(output_dir), DEFAULT_OUTPUT_EXTENSION), PathBuf::from("out/sample_nested.parquet"));
+///
+/// let base_dir = Path::new("testdata");
+/// assert_eq!(target_file_path(file, Some(base_dir), Some(output_dir), DEFAULT_OUTPUT_EXTENSION), PathBuf::from("out/nested/sample_nested.parquet"));
+///
+/// assert_eq!(target_file_path(Path::new("data/jan.csv.gz"), None, None, "pq"), PathBuf::from("data/jan.pq"));
+/// ```
+pub fn target_file_path(
+    file_path: &Path,
+    base_dir: Option<&Path>,
+    output_dir: Option<&Path>,
+    extension: &str,
+) -> PathBuf {
+    let target_file = parquet_file_path(file_path, extension);
+
+    let Some(output_dir) = output_dir else {
+        return target_file;
+    };
+
+    match base_dir.and_then(|base_dir| target_file.strip_prefix(base_dir).ok()) {
+        Some(relative) => output_dir.join(relative),
+        None => match target_file.file_name() {
+            Some(file_name) => output_dir.join(file_name),
+            None => target_file,
+        },
+    }
+}
+
+/// Builds the name of a Parquet file from a template and the path of the CSV file.
+///
+/// The placeholders `{stem}` (the file name without extension), `{ext}` (the extension, `csv` or
+/// `csv.gz`) and `{parent}` (the name of the parent directory) are replaced. The given extension
+/// is added if the name does not end with it.
+///
+/// # Arguments
+///
+/// * `template` - The template of the file name.
+/// * `file_path` - The path of the CSV file.
+/// * `extension` - The extension of the Parquet file without the leading dot, usually
+///   [`DEFAULT_OUTPUT_EXTENSION`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use cc2p::{output_file_name, DEFAULT_OUTPUT_EXTENSION};
+///
+/// let file = Path::new("reports/2024_data.csv");
+/// assert_eq!(output_file_name("{stem}_converted", file, DEFAULT_OUTPUT_EXTENSION).unwrap(), "2024_data_converted.parquet");
+/// assert_eq!(output_file_name("{parent}-{stem}.parquet", file, DEFAULT_OUTPUT_EXTENSION).unwrap(), "reports-2024_data.parquet");
+/// assert_eq!(output_file_name("{stem}", file, "pq").unwrap(), "2024_data.pq");
+/// assert!(output_file_name("{name}", file, DEFAULT_OUTPUT_EXTENSION).is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the file name, or an `Err` describing the invalid template.
+pub fn output_file_name(
+    template: &str,
+    file_path: &Path,
+    extension: &str,
+) -> Result<String, String> {
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lowercase = file_name.to_lowercase();
+    let ext_len = if lowercase.ends_with(".csv.gz") {
+        ".csv.gz".len()
+    } else if lowercase.ends_with(".csv") {
+        ".csv".len()
+    } else {
+        file_path.extension().map_or(0, |ext| ext.len() + 1)
+    };
+    let (stem, ext) = file_name.split_at(file_name.len() - ext_len);
+    let ext = ext.trim_start_matches('.');
+    let parent = file_path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        name.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            return Err(format!(
+                "unclosed placeholder in output name template '{}'",
+                template
+            ));
+        };
+        match &rest[start + 1..end] {
+            "stem" => name.push_str(stem),
+            "ext" => name.push_str(ext),
+            "parent" => name.push_str(&parent),
+            placeholder => {
+                return Err(format!(
+                    "unknown placeholder '{{{}}}' in output name template, expected one of: {{stem}}, {{ext}}, {{parent}}",
+                    placeholder
+                ));
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    name.push_str(rest);
+
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(format!(
+            "output name template '{}' must build a file name, got '{}'",
+            template, name
+        ));
+    }
+    if !name.ends_with(&format!(".{}", extension)) {
+        name.push('.');
+        name.push_str(extension);
+    }
+
+    Ok(name)
+}
+
+/// Deletes a file if it exists.
+///
+/// # Arguments
+///
+/// * `filename` - The name of the file to delete.
+///
+/// # Errors
+///
+/// Returns `Err` if there is an error accessing the file or deleting it.
+///
+pub fn delete_if_exist(filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if fs::metadata(filename).is_ok() {
+        fs::remove_file(filename)?;
+    }
+
+    Ok(())
+}
+
+struct Empty {}
+
+/// Removes duplicate columns from a given Arrow schema, and returns a new schema with deduplicated columns.
+///
+/// # Arguments
+///
+/// * `sc` - The input Arrow schema.
+///
+/// # Returns
+///
+/// Returns an `Arc` containing the deduplicated schema.
+pub fn remove_deduplicate_columns(sc: arrow_schema::Schema) -> Arc<arrow_schema::Schema> {
+    let mut index = 1;
+    let mut deduplicated_fields = Vec::new();
+    let mut names = HashMap::new();
+    for field in sc.fields() {
+        let field_name = field.name().as_str();
+        let field_name = clean_column_name(field_name);
+
+        if let std::collections::hash_map::Entry::Vacant(e) = names.entry(field_name.clone()) {
+            e.insert(Empty {});
+
+            if field.name().is_empty() {
+                let name = format!("column_{}", index);
+                index += 1;
+                let new_field =
+                    <arrow_schema::Field as Clone>::clone(&(*field).clone()).with_name(name);
+                deduplicated_fields.push(Arc::new(new_field));
+            } else {
+                deduplicated_fields.push(field.clone());
+            }
+        } else {
+            let name = format!("{}_{}", field_name, index);
+            index += 1;
+            let new_field =
+                <arrow_schema::Field as Clone>::clone(&(*field).clone()).with_name(name);
+            deduplicated_fields.push(Arc::new(new_field));
+        }
+    }
+
+    let list_fields: Vec<_> = deduplicated_fields.into_iter().collect();
+
+    let deduplicated_schema = arrow_schema::Schema::new_with_metadata(list_fields, sc.metadata);
+
+    Arc::new(deduplicated_schema)
+}
+
+/// Searches for files matching the given pattern.
+///
+/// The pattern may hold several comma-separated patterns, such as `data/*.csv,data/*.tsv`, and is
+/// matched case-insensitively, see [`find_files_with`]. An existing directory, such as
+/// `/data/incoming`, matches the CSV files directly inside it.
+///
+/// # Arguments
+///
+/// * `pattern` - A string slice representing the search pattern.
+///
+/// # Returns
+///
+/// Returns the paths of the matching files, or an `Err` describing the first invalid pattern,
+/// see [`expand_patterns`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use cc2p::find_files;
+///
+/// let pattern = "testdata/sample*.csv";
+/// let files = find_files(pattern).unwrap();
+///
+/// for file in files {
+///     println!("{:?}", file);
+/// }
+/// assert!(find_files("testdata/a[").is_err());
+/// ```
+pub fn find_files(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    find_files_with(&[pattern.to_string()], true, false)
+}
+
+/// Searches for files matching any of the given patterns.
+///
+/// Every pattern may hold several comma-separated patterns. A pattern for CSV files also matches
+/// their gzip compressed versions. The path of an existing directory matches the CSV files
+/// directly inside it, like `dir/*.csv`; use [`find_files_recursive_with`] to search its
+/// subdirectories too. A file matched by several patterns is returned once, in the order of the
+/// first pattern matching it.
+///
+/// Unless `any_extension` is set, only CSV and TSV files, optionally gzip compressed, and ZIP
+/// archives are returned, so `data/*` skips other files. Parquet files, such as the output of an
+/// earlier conversion, are never returned.
+///
+/// # Arguments
+///
+/// * `patterns` - The search patterns, such as `data/*.csv` and `data/*.txt`.
+/// * `ignore_case` - Match the patterns case-insensitively, so `*.csv` also matches `DATA.CSV`.
+/// * `any_extension` - Return every matching file instead of only CSV files.
+///
+/// # Returns
+///
+/// Returns the paths of the matching files, or an `Err` describing the first invalid pattern,
+/// see [`expand_patterns`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::find_files_with;
+///
+/// let find = |pattern: &str, ignore_case| {
+///     find_files_with(&[String::from(pattern)], ignore_case, false).unwrap()
+/// };
+/// assert_eq!(find("testdata/sample.csv,testdata/*.csv", false).len(), 5);
+/// assert_eq!(find("testdata", false).len(), 5);
+/// assert!(find("testdata/*.CSV", false).is_empty());
+/// assert_eq!(find("testdata/*.CSV", true).len(), 5);
+/// assert_eq!(find("testdata/*", true).len(), 6);
+/// ```
+pub fn find_files_with(
+    patterns: &[String],
+    ignore_case: bool,
+    any_extension: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let options = MatchOptions {
+        case_sensitive: !ignore_case,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+
+    // an existing directory stands for the CSV files directly inside it
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|pattern| {
+            if Path::new(pattern).is_dir() {
+                Ok(vec![pattern.clone()])
+            } else {
+                expand_patterns(pattern)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .map(|pattern| {
+            if Path::new(&pattern).is_dir() {
+                let directory = escape_pattern(&pattern);
+                Path::new(&directory).join("*.csv").display().to_string()
+            } else {
+                pattern
+            }
+        })
+        .collect();
+
+    let mut files = vec![];
+    let mut seen = HashSet::new();
+    for pattern in split_patterns(&patterns)? {
+        let entries = glob_with(&pattern, options)
+            .map_err(|e| format!("invalid pattern '{}': {}", pattern, e.msg))?;
+        for entry in entries {
+            match entry {
+                Ok(p) => {
+                    if p.is_file() && is_search_result(&p, any_extension) && seen.insert(p.clone())
+                    {
+                        files.push(p);
+                    }
+                }
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns whether a file found by a search is returned: a data file, unless `any_extension` is
+/// set, and never a Parquet file.
+fn is_search_result(file_path: &Path, any_extension: bool) -> bool {
+    let name = file_path.to_string_lossy().to_lowercase();
+    if name.ends_with(&format!(".{}", DEFAULT_OUTPUT_EXTENSION)) {
+        return false;
+    }
+
+    any_extension
+        || [".csv", ".tsv", ".csv.gz", ".tsv.gz", ".zip"]
+            .iter()
+            .any(|extension| name.ends_with(extension))
+}
+
+/// Returns whether the file is a ZIP archive, judging by its `.zip` extension.
+pub fn is_zip_file(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Lists the entries of a ZIP archive whose file names match any of the given patterns.
+///
+/// The entries are returned as paths below the archive, such as `data.zip/2024/sales.csv`, which
+/// [`convert_to_parquet`], [`inspect_csv`] and the other functions taking a CSV file read from the
+/// archive. The patterns are matched against the file names of the entries like in
+/// [`find_files_with`], and directories and other entries are ignored. Stored and deflated
+/// entries are supported, ZIP64 and encrypted archives are not.
+///
+/// # Arguments
+///
+/// * `archive` - The path of the ZIP archive.
+/// * `patterns` - The file name patterns, such as `*.csv`.
+/// * `ignore_case` - Match the patterns case-insensitively, so `*.csv` also matches `DATA.CSV`.
+///
+/// # Returns
+///
+/// Returns the paths of the matching entries in the order of the archive, otherwise returns an
+/// `Err` with a `Box<dyn std::error::Error>` if the archive cannot be read or an entry name would
+/// escape the archive, such as `../sales.csv`.
+pub fn find_zip_entries(
+    archive: &Path,
+    patterns: &[String],
+    ignore_case: bool,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let options = MatchOptions {
+        case_sensitive: !ignore_case,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+    let patterns = split_patterns(patterns)?
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let entries = zip::read_entries(&mut File::open(archive)?)
+        .map_err(|e| format!("cannot read {}: {}", archive.display(), e))?;
+    let mut files = vec![];
+    for entry in entries.iter().filter(|entry| !entry.is_dir()) {
+        let name = Path::new(&entry.name);
+        if !name
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            return Err(format!(
+                "the entry {} of {} is outside the archive",
+                entry.name,
+                archive.display()
+            )
+            .into());
+        }
+        let file_name = name.file_name().and_then(|name| name.to_str());
+        if file_name.is_some_and(|file_name| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches_with(file_name, options))
+        }) {
+            files.push(archive.join(name));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Splits comma-separated patterns and expands their braces, adding a `.gz` pattern after every
+/// pattern for CSV files.
+fn split_patterns(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut split = vec![];
+    for pattern in patterns.iter().map(|pattern| expand_patterns(pattern)) {
+        for pattern in pattern? {
+            // a pattern for CSV files also matches their gzip compressed versions
+            let gzip =
+                (pattern.to_lowercase().ends_with(".csv")).then(|| format!("{}.gz", pattern));
+            split.push(pattern);
+            split.extend(gzip);
+        }
+    }
+
+    Ok(split)
+}
+
+/// Splits comma-separated patterns and expands the alternatives in braces, checking that every
+/// pattern is valid.
+///
+/// The commas within braces separate alternatives rather than patterns, so `{2023,2024}/*.csv`
+/// expands to `2023/*.csv` and `2024/*.csv`. Braces may be nested, and braces within a character
+/// class such as `[{]` are matched literally. Besides braces, the patterns support the syntax of
+/// the `glob` crate: `?` matches any character, `*` any sequence of characters, `**` any number
+/// of directories when it forms a whole path component, such as `data/**/*.csv`, and `[...]` one
+/// of the characters in the brackets.
+///
+/// # Arguments
+///
+/// * `value` - The comma-separated patterns, such as `data/*.csv,{2023,2024}/*.tsv`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::expand_patterns;
+///
+/// assert_eq!(
+///     expand_patterns("{2023,2024}/*.csv, *.tsv").unwrap(),
+///     vec!["2023/*.csv", "2024/*.csv", "*.tsv"]
+/// );
+/// assert!(expand_patterns("{2023,2024/*.csv").is_err());
+/// assert!(expand_patterns("data/a**/*.csv").is_err());
+/// ```
+///
+/// # Returns
+///
+/// Returns the patterns without braces, or an `Err` describing the first invalid pattern.
+pub fn expand_patterns(value: &str) -> Result<Vec<String>, String> {
+    let mut expanded = vec![];
+    for pattern in split_outside_braces(value) {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            continue;
+        }
+        for alternative in expand_braces(pattern)
+            .map_err(|reason| format!("invalid pattern '{}': {}", pattern, reason))?
+        {
+            glob::Pattern::new(&alternative)
+                .map_err(|e| format!("invalid pattern '{}': {}", pattern, e.msg))?;
+            expanded.push(alternative);
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Splits the value on the commas outside braces and character classes, such as the one of `{a,b}`.
+fn split_outside_braces(value: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    let mut class = false;
+    value.split(move |c| {
+        match c {
+            '[' => class = true,
+            ']' => class = false,
+            '{' if !class => depth += 1,
+            '}' if !class => depth -= 1,
+            _ => {}
+        }
+        c == ',' && depth == 0 && !class
+    })
+}
+
+/// Expands the first alternatives in braces of the pattern, and those of the expanded patterns in turn.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, String> {
+    let mut depth = 0;
+    let mut class = false;
+    let mut start = None;
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '[' => class = true,
+            ']' => class = false,
+            '{' if !class => {
+                if depth == 0 {
+                    start = Some(i);
+                }
+                depth += 1;
+            }
+            '}' if !class => {
+                let Some(open) = start.filter(|_| depth > 0) else {
+                    return Err(String::from("'}' without an opening brace"));
+                };
+                depth -= 1;
+                if depth == 0 {
+                    let (prefix, suffix) = (&pattern[..open], &pattern[i + 1..]);
+                    let mut expanded = vec![];
+                    for alternative in split_outside_braces(&pattern[open + 1..i]) {
+                        expanded.extend(expand_braces(&format!(
+                            "{}{}{}",
+                            prefix, alternative, suffix
+                        ))?);
+                    }
+                    return Ok(expanded);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match start {
+        Some(_) => Err(String::from("'{' without a closing brace")),
+        None => Ok(vec![pattern.to_string()]),
+    }
+}
+
+/// Searches for files matching the given pattern which were modified after the given time.
+///
+/// Works like [`find_files`], and additionally keeps only the files whose modification time is
+/// newer than `modified_after`, see [`filter_modified_after`]. Files whose modification time
+/// cannot be read are left out.
+///
+/// # Arguments
+///
+/// * `pattern` - A string slice representing the search pattern.
+/// * `modified_after` - Only files modified after this time are returned. If `None`, no file is filtered out.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use cc2p::find_files_filtered;
+///
+/// let files = find_files_filtered("testdata/*.csv", Some(SystemTime::UNIX_EPOCH)).unwrap();
+/// assert_eq!(files.len(), 5);
+///
+/// let future = SystemTime::now() + Duration::from_secs(3600);
+/// assert!(find_files_filtered("testdata/*.csv", Some(future)).unwrap().is_empty());
+/// ```
+///
+/// # Returns
+///
+/// Returns the paths of the matching files, or an `Err` describing the first invalid pattern.
+pub fn find_files_filtered(
+    pattern: &str,
+    modified_after: Option<SystemTime>,
+) -> Result<Vec<PathBuf>, String> {
+    let files = find_files(pattern)?;
+
+    Ok(match modified_after {
+        Some(modified_after) => filter_modified_after(files, modified_after).0,
+        None => files,
+    })
+}
+
+/// Keeps only the files whose modification time is newer than `modified_after`.
+///
+/// # Arguments
+///
+/// * `files` - The paths of the files to filter.
+/// * `modified_after` - Only files modified after this time are kept.
+///
+/// # Returns
+///
+/// Returns the kept files, and the skipped files whose modification time cannot be read with the
+/// error reading it, both in the order of `files`.
+pub fn filter_modified_after(
+    files: Vec<PathBuf>,
+    modified_after: SystemTime,
+) -> (Vec<PathBuf>, Vec<(PathBuf, std::io::Error)>) {
+    let mut kept = vec![];
+    let mut skipped = vec![];
+    for file in files {
+        match fs::metadata(&file).and_then(|m| m.modified()) {
+            Ok(modified) if modified > modified_after => kept.push(file),
+            Ok(_) => {}
+            Err(e) => skipped.push((file, e)),
+        }
+    }
+
+    (kept, skipped)
+}
+
+/// Searches for files matching the given pattern, walking subdirectories of the pattern's base directory.
+///
+/// The leading part of the pattern without wildcards is the directory to walk (see [`search_root`]);
+/// the rest of the pattern is matched against the path of each file relative to that directory.
+/// An existing directory is walked for every `.csv` and `.csv.gz` file. Symbolic links to directories are not
+/// followed, to avoid cycles.
+///
+/// # Arguments
+///
+/// * `pattern` - A string slice representing the search pattern.
+/// * `max_depth` - The maximum number of directory levels to descend. `1` only searches the base
+///   directory itself. If `None`, there is no limit.
+///
+/// # Returns
+///
+/// Returns the paths of the matching files, or an `Err` describing the first invalid pattern,
+/// see [`expand_patterns`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::find_files_recursive;
+///
+/// let files = find_files_recursive("testdata/*.csv", Some(2)).unwrap();
+///
+/// for file in files {
+///     println!("{:?}", file);
+/// }
+/// ```
+pub fn find_files_recursive(
+    pattern: &str,
+    max_depth: Option<usize>,
+) -> Result<Vec<PathBuf>, String> {
+    find_files_recursive_with(pattern, max_depth, true, false)
+}
+
+/// Searches for files matching the given pattern like [`find_files_recursive`], optionally
+/// matching the pattern case-sensitively or returning files of any extension.
+///
+/// Only CSV files are returned unless `any_extension` is set, and Parquet files never are, see
+/// [`find_files_with`].
+///
+/// # Arguments
+///
+/// * `pattern` - A string slice representing the search pattern.
+/// * `max_depth` - The maximum number of directory levels to descend, see [`find_files_recursive`].
+/// * `ignore_case` - Match the pattern case-insensitively, so `*.csv` also matches `DATA.CSV`.
+/// * `any_extension` - Return every matching file instead of only CSV files.
+///
+/// # Returns
+///
+/// Returns the paths of the matching files, or an `Err` describing the first invalid pattern.
+pub fn find_files_recursive_with(
+    pattern: &str,
+    max_depth: Option<usize>,
+    ignore_case: bool,
+    any_extension: bool,
+) -> Result<Vec<PathBuf>, String> {
+    // an existing directory is walked even if its name holds wildcards
+    let expanded = if Path::new(pattern).is_dir() {
+        vec![pattern.to_string()]
+    } else {
+        expand_patterns(pattern)?
+    };
+    if !expanded.is_empty() && expanded != [pattern] {
+        let mut seen = HashSet::new();
+        let mut files = vec![];
+        for pattern in &expanded {
+            let found = find_files_recursive_with(pattern, max_depth, ignore_case, any_extension)?;
+            files.extend(found.into_iter().filter(|file| seen.insert(file.clone())));
+        }
+        return Ok(files);
+    }
+
+    let (root, rest) = split_search_root(pattern);
+    let file_pattern = match rest {
+        Some(rest) => rest.to_string_lossy().to_string(),
+        None => String::from("*.csv"),
+    };
+    let invalid = |e: glob::PatternError| format!("invalid pattern '{}': {}", pattern, e.msg);
+    let matcher = glob::Pattern::new(&file_pattern).map_err(invalid)?;
+    let gzip_matcher = glob::Pattern::new(&format!("{}.gz", file_pattern)).map_err(invalid)?;
+    let options = MatchOptions {
+        case_sensitive: !ignore_case,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+
+    let mut walker = WalkDir::new(&root).follow_links(false).sort_by_file_name();
+    if let Some(max_depth) = max_depth {
+        walker = walker.max_depth(max_depth);
+    }
+
+    let mut files = vec![];
+    for entry in walker {
+        match entry {
+            Ok(entry) => {
+                let p = entry.path();
+                if !p.is_file() {
+                    continue;
+                }
+                let relative = p.strip_prefix(&root).unwrap_or(p);
+                if (matcher.matches_path_with(relative, options)
+                    || gzip_matcher.matches_path_with(relative, options))
+                    && is_search_result(p, any_extension)
+                {
+                    files.push(p.to_path_buf());
+                }
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns the directory a search pattern starts from: its leading components without wildcards.
+///
+/// Characters escaped with [`escape_pattern`] are no wildcards, so the root of `a[[]b/*.csv` is
+/// the directory `a[b`.
+///
+/// # Arguments
+///
+/// * `pattern` - A string slice representing the search pattern.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use cc2p::search_root;
+///
+/// assert_eq!(search_root("data/2024/*.csv"), PathBuf::from("data/2024"));
+/// assert_eq!(search_root("*.csv"), PathBuf::from("."));
+/// assert_eq!(search_root("testdata"), PathBuf::from("testdata"));
+/// assert_eq!(search_root("data/a[[]b/*.csv"), PathBuf::from("data/a[b"));
+/// ```
+pub fn search_root(pattern: &str) -> PathBuf {
+    split_search_root(pattern).0
+}
+
+/// Splits a search pattern into the directory it starts from, see [`search_root`], and the
+/// pattern of the paths relative to it, if any.
+fn split_search_root(pattern: &str) -> (PathBuf, Option<PathBuf>) {
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        return (path.to_path_buf(), None);
+    }
+
+    let mut root = PathBuf::new();
+    let mut rest = PathBuf::new();
+    let mut components = path.components().peekable();
+    while let Some(component) = components.next() {
+        let literal = unescape_component(&component.as_os_str().to_string_lossy());
+        match literal {
+            // the last component is a file name or a file pattern, never part of the root
+            Some(literal) if components.peek().is_some() && rest.as_os_str().is_empty() => {
+                root.push(literal)
+            }
+            _ => rest.push(component),
+        }
+    }
+
+    let root = if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root
+    };
+    (root, (!rest.as_os_str().is_empty()).then_some(rest))
+}
+
+/// Returns a path component without its escaped characters, such as `a[b` for `a[[]b`, or `None`
+/// if it holds wildcards.
+fn unescape_component(component: &str) -> Option<String> {
+    let mut literal = String::new();
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' | '{' | '}' => return None,
+            // a class of a single character matches just that character
+            '[' => {
+                let escaped = chars.next()?;
+                if escaped == '!' || chars.next()? != ']' {
+                    return None;
+                }
+                literal.push(escaped);
+            }
+            c => literal.push(c),
+        }
+    }
+
+    Some(literal)
+}
+
+/// Escapes the characters of a path which are special in search patterns, so the pattern matches
+/// the path literally.
+///
+/// Unlike `glob::Pattern::escape`, braces and commas are escaped as well, as they separate
+/// alternatives and patterns. A directory named `a[b` can thus be searched with
+/// `escape_pattern("a[b") + "/*.csv"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::{escape_pattern, expand_patterns};
+///
+/// assert_eq!(escape_pattern("data/a[b"), "data/a[[]b");
+/// assert_eq!(escape_pattern("x{y},z"), "x[{]y[}][,]z");
+/// assert!(expand_patterns(&escape_pattern("x{y")).is_ok());
+/// ```
+pub fn escape_pattern(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            '*' | '?' | '[' | ']' | '{' | '}' | ',' => {
+                escaped.push('[');
+                escaped.push(c);
+                escaped.push(']');
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Cleans a given string by removing any characters that are not alphanumeric or whitespace.
+///
+/// # Arguments
+///
+/// * `column_name` - The string to be cleaned.
+///
+/// # Examples
+///
+/// ```rust
+/// use cc2p::*;
+///
+/// let name = clean_column_name("John!Doe");
+/// assert_eq!(name, "JohnDoe");
+///
+/// let name = clean_column_name("Welcome, User 123!");
+/// assert_eq!(name, "Welcome User 123");
+/// ```
+///
+/// # Returns
+///
+/// A `String` containing the cleaned string, with all non-alphanumeric characters removed.
+pub fn clean_column_name(column_name: &str) -> String {
+    // compiled once, as the name of every column of every file is cleaned
+    static PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let cleaned = PATTERN
+        .get_or_init(|| regex::Regex::new(r"[^a-zA-Z0-9_\-\s]").unwrap())
+        .replace_all(column_name, "");
+
+    cleaned.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::time::Duration;
+
+    #[test]
+    fn test_convert_to_parquet() {
+        let mut source_file = std::env::current_dir().unwrap();
+        source_file.push("testdata");
+        source_file.push("sample_empty_header.csv");
+
+        let result = convert_to_parquet(&source_file, &ConvertOptions::new().sampling(10));
+
+        // Check that the function completed successfully
+        assert!(result.is_ok());
+
+        let parquet_file = PathBuf::from("testdata/sample_empty_header.parquet");
+        // Verify the parquet file was created
+        assert!(parquet_file.exists());
+
+        // Optionally, clean up the parquet file
+        fs::remove_file(parquet_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_summary() {
+        let source_file = PathBuf::from("testdata/sample.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_summary.parquet");
+
+        let summary =
+            convert_to_parquet(&source_file, &ConvertOptions::new().output(&output_file)).unwrap();
+
+        assert_eq!(summary.rows_written, 5);
+        assert_eq!(summary.columns, 3);
+        assert_eq!(summary.output_path, output_file);
+        assert_eq!(
+            summary.bytes_written,
+            fs::metadata(&output_file).unwrap().len()
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_if_exists() {
+        let source_file = PathBuf::from("testdata/sample.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_if_exists.parquet");
+        fs::write(&output_file, "existing").unwrap();
+
+        let options = ConvertOptions::new().output(&output_file);
+
+        let summary = convert_to_parquet(&source_file, &options.clone().if_exists(IfExists::Skip));
+        assert!(summary.unwrap().skipped);
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "existing");
+
+        let result = convert_to_parquet(&source_file, &options.clone().if_exists(IfExists::Error));
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "existing");
+
+        let summary = convert_to_parquet(&source_file, &options.if_exists(IfExists::Overwrite));
+        assert!(!summary.unwrap().skipped);
+        assert!(fs::read(&output_file).unwrap().starts_with(b"PAR1"));
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_failure_keeps_output() {
+        let source_file = std::env::temp_dir().join("cc2p_test_convert_failure.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_failure.parquet");
+        fs::write(&source_file, "id\n1\n2\nthree\n").unwrap();
+        fs::write(&output_file, "existing").unwrap();
+
+        let options = ConvertOptions::new().output(&output_file).sampling(2);
+        let result = convert_to_parquet(&source_file, &options);
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "existing");
+        assert!(!temporary_file_path(&output_file).exists());
+
+        fs::remove_file(&output_file).unwrap();
+        assert!(convert_to_parquet(&source_file, &options).is_err());
+        assert!(!output_file.exists());
+        assert!(!temporary_file_path(&output_file).exists());
+
+        fs::remove_file(source_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_delimiter() {
+        let mut source_file = std::env::current_dir().unwrap();
+        source_file.push("testdata");
+        source_file.push("sample_delimiter.csv");
+
+        let result = convert_to_parquet(
+            &source_file,
+            &ConvertOptions::new().delimiter(';').sampling(10),
+        );
+
+        // Check that the function completed successfully
+        assert!(result.is_ok());
+
+        // Verify the parquet file was created
+        let parquet_file = PathBuf::from("testdata/sample_delimiter.parquet");
+        assert!(parquet_file.exists());
+
+        // Optionally, clean up the parquet file
+        fs::remove_file(parquet_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_mixed_line_endings() {
+        let source_file = PathBuf::from("testdata/sample_mixed_endings.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_mixed_line_endings.parquet");
+
+        for delimiter in [
+            Delimiter::Char(','),
+            Delimiter::Str(String::from(",")),
+            Delimiter::Auto,
+        ] {
+            let options = ConvertOptions::new()
+                .delimiter(delimiter)
+                .output(&output_file);
+            let summary = convert_to_parquet(&source_file, &options).unwrap();
+            assert_eq!(summary.rows_written, 5);
+
+            let batches = read_parquet(&output_file);
+            let note = batches[0]
+                .column(2)
+                .as_any()
+                .downcast_ref::<arrow_array::StringArray>()
+                .unwrap();
+            let values: Vec<&str> = note.iter().map(|value| value.unwrap()).collect();
+            // a line break within quotes is part of the value
+            assert_eq!(values, ["plain", "quoted", "crlf", "multi\r\nline", "last"]);
+        }
+
+        // the lines skipped before the data and the directive may end with a lone \r as well
+        let data = "sep=;\rexported by a tool\rid;note\r1;a\r\n2;b\n";
+        let options = ConvertOptions::new()
+            .delimiter(Delimiter::Auto)
+            .skip_rows(1);
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        assert_eq!(summary.detected_delimiter, Some(';'));
+        assert_eq!(summary.schema.field(1).name(), "note");
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_parquet_no_header() {
+        let mut source_file = std::env::current_dir().unwrap();
+        source_file.push("testdata");
+        source_file.push("sample_no_header.csv");
+
+        let result = convert_to_parquet(
+            &source_file,
+            &ConvertOptions::new().has_header(false).sampling(10),
+        );
+
+        // Check that the function completed successfully
+        assert!(result.is_ok());
+
+        // Verify the parquet file was created
+        let parquet_file = PathBuf::from("testdata/sample_no_header.parquet");
+        assert!(parquet_file.exists());
+
+        // Optionally, clean up the parquet file
+        fs::remove_file(parquet_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_bytes_to_parquet() {
+        let data = "id,code\n1,10\n2,20\n3,A7\n";
+        let options = ConvertOptions::new()
+            .sampling(2)
+            .sample_strategy(SampleStrategy::Full);
+
+        // the whole data is sampled, unlike for a reader which can only be read once
+        let mut parquet = std::io::Cursor::new(Vec::new());
+        let summary = convert_bytes_to_parquet(data.as_bytes(), &mut parquet, &options).unwrap();
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.schema.field(1).data_type(), &DataType::Utf8);
+        assert!(
+            convert_reader_to_writer(std::io::Cursor::new(data), std::io::sink(), &options)
+                .is_err()
+        );
+
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_bytes_to_parquet.parquet");
+        fs::write(&output_file, parquet.into_inner()).unwrap();
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].num_rows(), 3);
+        assert_eq!(batches[0].schema(), summary.schema);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_reader_to_writer() {
+        let data = "name,age\nJohn,25\nJane,28\nBob,22\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_reader.parquet");
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().sampling(2),
+        );
+
+        // Check that the function completed successfully, including rows after the sample
+        let summary = result.unwrap();
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.columns, 2);
+        assert_eq!(
+            summary.bytes_written,
+            fs::metadata(&output_file).unwrap().len()
+        );
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_null_values() {
+        let data = "id,name\n1,John\nNA,NULL\n3,\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_null_values.parquet");
+        let options = ConvertOptions::new().null_values(parse_null_values("NA,NULL"));
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        );
+        assert!(result.is_ok());
+
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).data_type(), &arrow_schema::DataType::Int64);
+        assert_eq!(batches[0].column(0).null_count(), 1);
+        assert_eq!(batches[0].column(1).null_count(), 2);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_quoted_fields() {
+        let data = "name,note\n\"Smith, John\",\"said \"\"hi\"\"\"\nDoe,\"a\nb\"\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_quoted_fields.parquet");
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        assert_eq!(summary.columns, 2);
+
+        let batches = read_parquet(&output_file);
+        let name = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        let note = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(name.value(0), "Smith, John");
+        assert_eq!(note.value(0), "said \"hi\"");
+        assert_eq!(note.value(1), "a\nb");
+
+        // with a different quote character, single quotes enclose the field instead
+        convert_reader_to_writer(
+            "name,note\n'Doe, Jane',x\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().quote('\''),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let name = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(name.value(0), "Doe, Jane");
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_read_buffer_size() {
+        let data = "name,note\n\"Smith, John\",\"said \"\"hi\"\"\"\nDoe,\"a\nb\"\n";
+        let options = ConvertOptions::new().metadata(false);
+
+        // the buffers only change how the data is read, never the result
+        let mut expected = vec![];
+        convert_reader_to_writer(data.as_bytes(), &mut expected, &options).unwrap();
+        let mut buffer = vec![];
+        convert_reader_to_writer(data.as_bytes(), &mut buffer, &options.read_buffer_size(1))
+            .unwrap();
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_convert_no_quoting() {
+        let data = "name,note\n\"John\",5\"\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_no_quoting.parquet");
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().quoting(false),
+        )
+        .unwrap();
+        assert_eq!(summary.columns, 2);
+
+        let batches = read_parquet(&output_file);
+        let name = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(name.value(0), "\"John\"");
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_row_group_size() {
+        let source_file = PathBuf::from("testdata/sample.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_row_group_size.parquet");
+
+        let result = convert_to_parquet(
+            &source_file,
+            &ConvertOptions::new().row_group_size(2).output(&output_file),
+        );
+        assert!(result.is_ok());
+
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+        assert_eq!(reader.metadata().row_group(0).num_rows(), 2);
+
+        // a row group holds at least one row
+        let summary = convert_reader_to_writer(
+            "id\n1\n2\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().row_group_size(0),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_flush_every() {
+        let data: String = std::iter::once("id,name\n".to_string())
+            .chain((0..25).map(|i| format!("{},name {}\n", i, i)))
+            .collect();
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_flush_every.parquet");
+
+        for (row_group_size, flush_every, parallelism, expected) in [
+            (10, None, 1, vec![10, 10, 5]),
+            (10, Some(4), 1, vec![4, 4, 4, 4, 4, 4, 1]),
+            (4, Some(10), 1, vec![4, 4, 4, 4, 4, 4, 1]),
+            (10, Some(8), 3, vec![8, 8, 8, 1]),
+        ] {
+            let options = ConvertOptions::new()
+                .row_group_size(row_group_size)
+                .flush_every(flush_every)
+                .parallelism(parallelism);
+            convert_reader_to_writer(
+                data.as_bytes(),
+                File::create(&output_file).unwrap(),
+                &options,
+            )
+            .unwrap();
+
+            let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+            let row_groups: Vec<i64> = reader
+                .metadata()
+                .row_groups()
+                .iter()
+                .map(|row_group| row_group.num_rows())
+                .collect();
+            assert_eq!(row_groups, expected, "flush every {:?}", flush_every);
+        }
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_batch_size_independent_of_row_groups() {
+        let data: String = std::iter::once("id,name\n".to_string())
+            .chain((0..25).map(|i| format!("{},name {}\n", i, i)))
+            .collect();
+        let output_file =
+            std::env::temp_dir().join("cc2p_test_convert_batch_size_independent.parquet");
+
+        for batch_size in [3, 10, 100] {
+            let options = ConvertOptions::new()
+                .batch_size(batch_size)
+                .row_group_size(10);
+            convert_reader_to_writer(
+                data.as_bytes(),
+                File::create(&output_file).unwrap(),
+                &options,
+            )
+            .unwrap();
+
+            let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+            let row_groups: Vec<i64> = reader
+                .metadata()
+                .row_groups()
+                .iter()
+                .map(|row_group| row_group.num_rows())
+                .collect();
+            assert_eq!(row_groups, vec![10, 10, 5], "batch size {}", batch_size);
+        }
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_preview_parquet() {
+        let data = "name,note\nJosé,\"line 1\nline 2\"\nAl,\n";
+        let mut parquet = vec![];
+        convert_reader_to_writer(data.as_bytes(), &mut parquet, &ConvertOptions::new()).unwrap();
+
+        let table = preview_parquet(bytes::Bytes::from(parquet.clone()), 10).unwrap();
+        assert_eq!(
+            table,
+            "+------+----------------+\n\
+             | name | note           |\n\
+             +------+----------------+\n\
+             | José | line 1\\nline 2 |\n\
+             | Al   |                |\n\
+             +------+----------------+\n"
+        );
+
+        let table = preview_parquet(bytes::Bytes::from(parquet), 1).unwrap();
+        assert_eq!(table.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_convert_wide_file() {
+        let columns = 5000;
+        let names: Vec<String> = (0..columns).map(|i| format!("c{}", i)).collect();
+        let mut data = names.join(",");
+        data.push('\n');
+        for row in 0..20 {
+            let values: Vec<String> = (0..columns)
+                .map(|i| match i % 3 {
+                    0 => format!("v{}", row),
+                    1 => (i + row).to_string(),
+                    _ => format!("{}.5", row),
+                })
+                .collect();
+            data.push_str(&values.join(","));
+            data.push('\n');
+        }
+        // every column is selected and renamed, which looks up every name
+        let selected: Vec<String> = names.iter().rev().cloned().collect();
+        let renames: HashMap<String, String> = names
+            .iter()
+            .map(|name| (name.clone(), format!("{}_renamed", name)))
+            .collect();
+        let options = ConvertOptions::new()
+            .columns(selected)
+            .renames(renames)
+            .dictionary_columns(HashMap::from([("c1_renamed".to_string(), false)]));
+
+        let started = std::time::Instant::now();
+        let mut parquet = vec![];
+        let summary = convert_reader_to_writer(data.as_bytes(), &mut parquet, &options).unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(30));
+        assert_eq!(summary.columns, columns);
+        assert_eq!(summary.rows_written, 20);
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+
+        let reader = SerializedFileReader::new(bytes::Bytes::from(parquet)).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr();
+        assert_eq!(schema.num_columns(), columns);
+        assert_eq!(schema.column(0).name(), "c4999_renamed");
+    }
+
+    #[test]
+    fn test_convert_comment_lines() {
+        let data = "# exported 2024-01-15\n# source: crm\nname,age\nJohn,25\n# page 2\nJane,28\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_comment_lines.parquet");
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().comment(Some('#')),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        assert_eq!(summary.columns, 2);
+
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].schema().field(0).name(), "name");
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Int64);
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().comment(Some(',')),
+        );
+        assert!(result.is_err());
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_validate_special_characters() {
+        let quote = Some('"');
+        assert!(validate_special_characters(&Delimiter::Char(','), quote, Some('#')).is_ok());
+        assert!(validate_special_characters(&Delimiter::Char('"'), None, None).is_ok());
+        assert_eq!(
+            validate_special_characters(&Delimiter::Char('"'), quote, None).unwrap_err(),
+            "the delimiter and the quote character are both '\"'"
+        );
+        assert_eq!(
+            validate_special_characters(&Delimiter::Char(','), Some('#'), Some('#')).unwrap_err(),
+            "the quote character and the comment character are both '#'"
+        );
+        assert_eq!(
+            validate_special_characters(&Delimiter::Str("#|".into()), quote, Some('#'))
+                .unwrap_err(),
+            "the delimiter \"#|\" starts with the comment character '#'"
+        );
+        assert_eq!(
+            validate_special_characters(&Delimiter::Char(','), Some('\n'), None).unwrap_err(),
+            "the quote character must not be a line break"
+        );
+
+        // a delimiter declared in the data is checked as well
+        let error = convert_reader_to_writer(
+            "sep=;\na;b\n1;2\n".as_bytes(),
+            Vec::new(),
+            &ConvertOptions::new().quote(';'),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the delimiter and the quote character are both ';'"
+        );
+    }
+
+    #[test]
+    fn test_convert_header_row() {
+        let data = "Export\n\"Sales report,\nfirst quarter\"\nname,amount\nJohn,25\nJane,28\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_header_row.parquet");
+
+        // the quoted title spanning two lines is one row
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().header_row(2),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].schema().field(0).name(), "name");
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Int64);
+
+        // counted after the skipped lines
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().skip_rows(1).header_row(1),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().header_row(1).has_header(false),
+        );
+        assert!(result.is_err());
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().header_row(10),
+        );
+        assert!(result.is_err());
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let file = PathBuf::from("testdata/sample.csv");
+        let options = ConvertOptions::new()
+            .column_types(HashMap::from([("age".to_string(), DataType::Int32)]));
+
+        let schema = infer_schema(&file, &options).unwrap();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["name", "age", "job"]);
+        assert_eq!(schema.field(1).data_type(), &DataType::Int32);
+
+        // the schema is the one of the converted file
+        let mut parquet = vec![];
+        convert_to_writer(&file, &options, &mut parquet).unwrap();
+        let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            bytes::Bytes::from(parquet),
+        )
+        .unwrap();
+        assert_eq!(reader.schema().fields(), schema.fields());
+
+        assert!(infer_schema(Path::new("testdata/missing.csv"), &options).is_err());
+    }
+
+    #[test]
+    fn test_convert_skip_rows() {
+        let data =
+            "Sales report\ngenerated 2024-01-15\nname,amount\nJohn,25\nJane,28\nTotal,53.5\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_skip_rows.parquet");
+        let options = ConvertOptions::new().skip_rows(2).skip_footer(1);
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().sampling(5),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+
+        // the footer is not sampled, so the amount column stays an integer column
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].schema().field(0).name(), "name");
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Int64);
+
+        // without a header, the first line after the skipped lines is data
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().skip_rows(3).has_header(false),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+
+        // without sampled rows, the data rows are only read while converting
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().sampling(0),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+
+        let result = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.skip_footer(3),
+        );
+        assert!(result.is_err());
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_limit() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_limit");
+        fs::create_dir_all(&dir).unwrap();
+        let source_file = dir.join("data.csv");
+        fs::write(
+            &source_file,
+            "name,age\nJohn,25\nJane,28\nJim,unknown\nJo,22\n",
+        )
+        .unwrap();
+
+        // rows after the limit are not sampled, even when the whole file would be
+        let options = ConvertOptions::new()
+            .limit(Some(2))
+            .sample_strategy(SampleStrategy::Full);
+        let summary = convert_to_parquet(&source_file, &options).unwrap();
+        assert_eq!(summary.rows_written, 2);
+
+        let batches = read_parquet(&summary.output_path);
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Int64);
+
+        let summary = convert_to_parquet(&source_file, &options.limit(Some(10))).unwrap();
+        assert_eq!(summary.rows_written, 4);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_on_empty() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_on_empty");
+        fs::create_dir_all(&dir).unwrap();
+        let source_file = dir.join("data.csv");
+        let target_file = dir.join("data.parquet");
+        fs::write(&source_file, "name,age\n").unwrap();
+
+        let summary = convert_to_parquet(&source_file, &ConvertOptions::new()).unwrap();
+        assert_eq!(summary.rows_written, 0);
+        assert_eq!(summary.columns, 2);
+        assert!(read_parquet(&target_file).is_empty());
+        fs::remove_file(&target_file).unwrap();
+
+        let options = ConvertOptions::new().on_empty("skip".parse().unwrap());
+        let summary = convert_to_parquet(&source_file, &options).unwrap();
+        assert!(summary.skipped);
+        assert_eq!(summary.warnings, vec!["the file has no data rows"]);
+        assert!(!target_file.exists());
+
+        fs::write(&source_file, "").unwrap();
+        let options = options.on_empty(OnEmpty::Error);
+        let err = convert_to_parquet(&source_file, &options).unwrap_err();
+        assert_eq!(err.to_string(), "the file has no data rows");
+        assert!(!target_file.exists());
+        assert!("ignore".parse::<OnEmpty>().is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_gzip_input() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_gzip_input");
+        fs::create_dir_all(&dir).unwrap();
+        let source_file = dir.join("data.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&source_file).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"name,age\nJohn,25\nJane,28\n").unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(
+            find_files(dir.join("*.csv").to_str().unwrap()).unwrap(),
+            vec![source_file.clone()]
+        );
+
+        let summary = convert_to_parquet(&source_file, &ConvertOptions::new()).unwrap();
+        assert_eq!(summary.rows_written, 2);
+        assert_eq!(summary.output_path, dir.join("data.parquet"));
+
+        let options = ConvertOptions::new().output_extension("pq");
+        let summary = convert_to_parquet(&source_file, &options).unwrap();
+        assert_eq!(summary.output_path, dir.join("data.pq"));
+
+        // the gzip stream is detected from its magic bytes when reading from a reader
+        let mut buffer = Vec::new();
+        let summary = convert_reader_to_writer(
+            File::open(&source_file).unwrap(),
+            &mut buffer,
+            &ConvertOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(summary.columns, 2);
+
+        let result = convert_to_parquet(
+            &source_file,
+            &ConvertOptions::new().input_compression(InputCompression::None),
+        );
+        assert!(result.is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_byte_order_mark() {
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_byte_order_mark.parquet");
+        let utf8 = b"\xef\xbb\xbfid,name\n1,J\xc3\xbcrgen\n".to_vec();
+        let mut utf16le = vec![0xff, 0xfe];
+        let mut utf16be = vec![0xfe, 0xff];
+        for unit in "id,name\n1,J\u{fc}rgen\n".encode_utf16() {
+            utf16le.extend(unit.to_le_bytes());
+            utf16be.extend(unit.to_be_bytes());
+        }
+
+        for data in [utf8, utf16le, utf16be] {
+            convert_reader_to_writer(
+                data.as_slice(),
+                File::create(&output_file).unwrap(),
+                &ConvertOptions::new(),
+            )
+            .unwrap();
+
+            let batches = read_parquet(&output_file);
+            assert_eq!(batches[0].schema().field(0).name(), "id");
+            assert_eq!(batches[0].schema().field(0).data_type(), &DataType::Int64);
+            let name = batches[0]
+                .column(1)
+                .as_any()
+                .downcast_ref::<arrow_array::StringArray>()
+                .unwrap();
+            assert_eq!(name.value(0), "J\u{fc}rgen");
+        }
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_encoding() {
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_encoding.parquet");
+        let data = b"name,city\nJ\xfcrgen,K\xf6ln\n";
+
+        let options = ConvertOptions::new().encoding(parse_encoding("latin1").unwrap());
+        convert_reader_to_writer(
+            data.as_slice(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let city = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(city.value(0), "K\u{f6}ln");
+
+        let options = ConvertOptions::new().encoding(parse_encoding("utf-8").unwrap());
+        let result = convert_reader_to_writer(
+            data.as_slice(),
+            File::create(&output_file).unwrap(),
+            &options,
+        );
+        assert!(result.unwrap_err().to_string().contains("invalid UTF-8"));
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_date_format() {
+        let data = "day,time,mixed\n15/01/2024,15/01/2024 10:30,15/01/2024\n16/01/2024,,n/a\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_date_format.parquet");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().date_format(Some("%d/%m/%Y".to_string())),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Date32);
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+        let day = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Date32Array>()
+            .unwrap();
+        assert_eq!(day.value(0), 19737);
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().date_format(Some("%d/%m/%Y %H:%M".to_string())),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        assert_eq!(
+            batches[0].schema().field(1).data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+        let time = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(time.value(0), 1_705_314_600_000_000);
+        assert_eq!(batches[0].column(1).null_count(), 1);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_parallelism() {
+        let mut data = String::from("id,name,note\n");
+        for i in 0..5000 {
+            data.push_str(&format!("{},name {},\"line one\nline, two\"\n", i, i));
+        }
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_parallelism.parquet");
+
+        let options = ConvertOptions::new().row_group_size(1500).parallelism(3);
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 5000);
+        assert_eq!(
+            summary.bytes_written,
+            fs::metadata(&output_file).unwrap().len()
+        );
+
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 4);
+
+        let batches = read_parquet(&output_file);
+        let ids: Vec<i64> = batches
+            .iter()
+            .flat_map(|batch| {
+                let ids = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<arrow_array::Int64Array>()
+                    .unwrap();
+                ids.values().to_vec()
+            })
+            .collect();
+        assert_eq!(ids, (0..5000).collect::<Vec<i64>>());
+        let note = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(note.value(0), "line one\nline, two");
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_inspect_csv() {
+        let source_file = std::env::temp_dir().join("cc2p_test_inspect_csv.csv");
+        let mut data = String::from("id,name\n");
+        for i in 0..1000 {
+            data.push_str(&format!("{:04},name{:04}\n", i, i));
+        }
+        fs::write(&source_file, &data).unwrap();
+
+        let inspection = inspect_csv(&source_file, &ConvertOptions::new().sampling(10)).unwrap();
+        assert_eq!(inspection.sampled_rows, 10);
+        assert_eq!(inspection.schema.field(0).data_type(), &DataType::Int64);
+        let estimated_rows = inspection.estimated_rows.unwrap();
+        assert!((950..=1050).contains(&estimated_rows), "{}", estimated_rows);
+        assert!(!source_file.with_extension("parquet").exists());
+
+        let inspection =
+            inspect_reader(data.as_bytes(), &ConvertOptions::new().sampling(10)).unwrap();
+        assert_eq!(inspection.estimated_rows, None);
+
+        let inspection =
+            inspect_reader(data.as_bytes(), &ConvertOptions::new().sampling(2000)).unwrap();
+        assert_eq!(inspection.estimated_rows, Some(1000));
+
+        fs::remove_file(source_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_trim() {
+        let data = " id , name \n  42 , John \n NA ,Jane\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_trim.parquet");
+        let options = ConvertOptions::new().null_values(parse_null_values("NA"));
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().trim(Trim::Both),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).name(), "id");
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(batches[0].column(0).null_count(), 1);
+        let name = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(name.value(0), "John");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.trim(Trim::Headers),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].schema().field(1).name(), "name");
+        assert_eq!(batches[0].schema().field(0).data_type(), &DataType::Utf8);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_metadata() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_metadata");
+        fs::create_dir_all(&dir).unwrap();
+        let source_file = dir.join("data.csv");
+        fs::write(&source_file, "id;name\n1;John\n2;Jane\n").unwrap();
+        let options = ConvertOptions::new().delimiter(';');
+
+        let summary = convert_to_parquet(&source_file, &options).unwrap();
+        let reader = SerializedFileReader::new(File::open(&summary.output_path).unwrap()).unwrap();
+        let metadata: HashMap<String, Option<String>> = reader
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .map(|kv| (kv.key.clone(), kv.value.clone()))
+            .collect();
+        let value = |key: &str| metadata[key].as_deref().unwrap();
+        assert_eq!(value("cc2p:source"), source_file.display().to_string());
+        assert_eq!(value("cc2p:version"), env!("CARGO_PKG_VERSION"));
+        assert_eq!(value("cc2p:delimiter"), ";");
+        assert_eq!(value("cc2p:rows"), "2");
+        assert!(metadata.contains_key("cc2p:converted_at"));
+
+        // without metadata the output only depends on the data
+        let options = options.metadata(false);
+        let mut first = vec![];
+        convert_to_writer(&source_file, &options, &mut first).unwrap();
+        let mut second = vec![];
+        convert_to_writer(&source_file, &options, &mut second).unwrap();
+        assert_eq!(first, second);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_reproducible() {
+        let source_file = std::env::temp_dir().join("cc2p_test_convert_reproducible.csv");
+        let data: String = (0..5000).map(|i| format!("{},{}\n", i, i % 7)).collect();
+        fs::write(&source_file, format!("id,group\n{}", data)).unwrap();
+        let options = ConvertOptions::new()
+            .row_group_size(1000)
+            .parallelism(4)
+            .reproducible(true);
+
+        let mut first = vec![];
+        convert_to_writer(&source_file, &options, &mut first).unwrap();
+        let mut second = vec![];
+        convert_to_writer(&source_file, &options.parallelism(1), &mut second).unwrap();
+        assert_eq!(first, second);
+
+        fs::remove_file(source_file).unwrap();
+    }
+
+    #[test]
+    fn test_merge_to_parquet() {
+        let dir = std::env::temp_dir().join("cc2p_test_merge_to_parquet");
+        fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = ["day1.csv", "day2.csv", "other.csv", "day3.csv"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        fs::write(&files[0], "id,note\n1,\n2,\n").unwrap();
+        fs::write(&files[1], "id,note\n3,late\n").unwrap();
+        fs::write(&files[2], "id,name\n4,Jane\n").unwrap();
+        fs::write(&files[3], "id,note\nx,\n").unwrap();
+        let output_file = dir.join("all.parquet");
+        let options = ConvertOptions::new().output(&output_file);
+
+        let merge = merge_to_parquet(&files, &options, false).unwrap();
+        assert_eq!(merge.summary.rows_written, 3);
+        assert_eq!(
+            merge.merged,
+            vec![(files[0].clone(), 2), (files[1].clone(), 1)]
+        );
+        assert_eq!(merge.excluded.len(), 2);
+        assert_eq!(merge.excluded[0].0, files[2]);
+        assert_eq!(merge.excluded[1].1, "column 'id' is Utf8 instead of Int64");
+
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Utf8);
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+
+        let err = merge_to_parquet(&files, &options, true).unwrap_err();
+        assert!(err.to_string().contains("other.csv cannot be merged"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_row_group_per_file() {
+        let dir = std::env::temp_dir().join("cc2p_test_merge_row_group_per_file");
+        fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = ["day1.csv", "empty.csv", "day2.csv"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        fs::write(&files[0], "id\n1\n2\n3\n").unwrap();
+        fs::write(&files[1], "id\n").unwrap();
+        fs::write(&files[2], "id\n4\n5\n").unwrap();
+        let output_file = dir.join("all.parquet");
+        let options = ConvertOptions::new()
+            .output(&output_file)
+            .row_group_size(2)
+            .row_group_per_file(true);
+
+        merge_to_parquet(&files, &options, false).unwrap();
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(metadata.row_group(0).num_rows(), 3);
+        let row_group_files = metadata
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == ROW_GROUP_FILES_KEY)
+            .and_then(|kv| kv.value.clone())
+            .unwrap();
+        let row_group_files: Vec<String> = serde_json::from_str(&row_group_files).unwrap();
+        assert_eq!(
+            row_group_files,
+            vec![
+                files[0].display().to_string(),
+                files[2].display().to_string()
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_append_to_dataset() {
+        let dir = std::env::temp_dir().join("cc2p_test_append_to_dataset");
+        let _ = fs::remove_dir_all(&dir);
+        let dataset_dir = dir.join("dataset");
+        fs::create_dir_all(&dataset_dir).unwrap();
+        let files: Vec<PathBuf> = ["day1.csv", "day2.csv", "bad.csv"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        fs::write(&files[0], "id,amount\n1,2.5\n2,3.5\n").unwrap();
+        // integers fit the float column of the dataset
+        fs::write(&files[1], "id,amount\n3,4\n").unwrap();
+        fs::write(&files[2], "id,amount\nx,1.5\n").unwrap();
+        fs::write(dataset_dir.join("README.txt"), "not a part").unwrap();
+
+        let dataset = read_dataset(&dataset_dir).unwrap();
+        assert_eq!(dataset.last_part, 0);
+        assert_eq!(dataset.schema, None);
+
+        convert_to_parquet(
+            &files[0],
+            &ConvertOptions::new().output(dataset_dir.join(part_file_name(1))),
+        )
+        .unwrap();
+        let dataset = read_dataset(&dataset_dir).unwrap();
+        assert_eq!(dataset.last_part, 1);
+        let schema = dataset.schema.unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+
+        let part_file = dataset_dir.join(part_file_name(2));
+        let summary =
+            append_to_dataset(&files[1], &part_file, &schema, &ConvertOptions::new()).unwrap();
+        assert_eq!(summary.schema, schema);
+        let batches = read_parquet(&part_file);
+        assert_eq!(batches[0].schema().field(1).data_type(), &DataType::Float64);
+
+        let error = append_to_dataset(
+            &files[2],
+            &dataset_dir.join(part_file_name(3)),
+            &schema,
+            &ConvertOptions::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the schema does not match the dataset: column 'id' is Utf8 instead of Int64"
+        );
+        // an existing part is never overwritten
+        assert!(append_to_dataset(&files[1], &part_file, &schema, &ConvertOptions::new()).is_err());
+        assert_eq!(read_dataset(&dataset_dir).unwrap().last_part, 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_partition_by() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_partition_by");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.csv");
+        fs::write(&file_path, "id,region\n1,US\n2,EU\n3,US\n4,\n5,a/b\n").unwrap();
+        let options = ConvertOptions::new()
+            .output(dir.join("data.parquet"))
+            .partition_by(Some("region".to_string()));
+
+        let summary = convert_to_parquet(&file_path, &options).unwrap();
+        assert_eq!(summary.rows_written, 5);
+        assert_eq!(summary.columns, 1);
+        assert_eq!(summary.output_path, dir);
+
+        let batches = read_parquet(&dir.join("region=US").join("data.parquet"));
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].num_columns(), 1);
+        assert!(dir
+            .join(format!("region={}", HIVE_DEFAULT_PARTITION))
+            .join("data.parquet")
+            .is_file());
+        assert!(dir.join("region=a%2Fb").join("data.parquet").is_file());
+
+        convert_to_parquet(&file_path, &options.clone().keep_partition_column(true)).unwrap();
+        let batches = read_parquet(&dir.join("region=EU").join("data.parquet"));
+        assert_eq!(batches[0].num_columns(), 2);
+
+        let err = convert_to_parquet(&file_path, &options.max_partitions(2)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "partition column 'region' has more than 2 distinct values"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_partition_by_rename_failure() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_partition_by_rename_failure");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.csv");
+        fs::write(&file_path, "id,region\n1,US\n2,EU\n").unwrap();
+        // the EU partition is renamed first, the US partition cannot replace a directory
+        fs::create_dir_all(dir.join("region=US").join("data.parquet")).unwrap();
+        let options = ConvertOptions::new()
+            .output(dir.join("data.parquet"))
+            .partition_by(Some("region".to_string()))
+            .emit_schema(true);
+
+        assert!(convert_to_parquet(&file_path, &options).is_err());
+        assert!(!dir.join("region=EU").exists());
+        assert_eq!(fs::read_dir(dir.join("region=US")).unwrap().count(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_columns() {
+        let data = "a,b,c\n1,x,2.5\n2,y,3.5\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_columns.parquet");
+        let options = ConvertOptions::new();
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options
+                .clone()
+                .columns(vec!["c".to_string(), "a".to_string()]),
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.fields().len(), 2);
+        assert_eq!(schema.field(0).name(), "c");
+        assert_eq!(schema.field(0).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(1).name(), "a");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().exclude_columns(vec!["b".to_string()]),
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.field(0).name(), "a");
+        assert_eq!(schema.field(1).name(), "c");
+
+        // without a header, columns are selected by index
+        convert_reader_to_writer(
+            "1,x,2.5\n2,y,3.5\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options
+                .clone()
+                .has_header(false)
+                .columns(vec!["2".to_string(), "0".to_string()]),
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.field(0).name(), "column_2");
+        assert_eq!(schema.field(0).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(1).name(), "column_0");
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &options.clone().columns(vec!["d".to_string()]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 'd' does not exist, available columns: a, b, c"
+        );
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &options.exclude_columns(vec!["3".to_string()]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column index 3 is out of range, the file has 3 columns"
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_write_checksums() {
+        let dir = std::env::temp_dir().join("cc2p_test_write_checksums");
+        fs::create_dir_all(&dir).unwrap();
+        let vectors = [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ];
+        let mut files = vec![];
+        for (i, (data, hash)) in vectors.iter().enumerate() {
+            let file = dir.join(format!("{}.bin", i));
+            fs::write(&file, data).unwrap();
+            assert_eq!(sha256_file(&file).unwrap(), *hash);
+            files.push(file);
+        }
+        // a million bytes span many blocks and chunks of the reader
+        let file = dir.join("million.bin");
+        fs::write(&file, "a".repeat(1_000_000)).unwrap();
+        assert_eq!(
+            sha256_file(&file).unwrap(),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+
+        let manifest = dir.join("SHA256SUMS");
+        assert_eq!(write_checksums(&manifest, &files).unwrap(), 3);
+        let lines: Vec<String> = fs::read_to_string(&manifest)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            lines[1],
+            format!("{}  {}", vectors[1].1, files[1].display())
+        );
+        assert!(write_checksums(&manifest, &[dir.join("missing.bin")]).is_err());
+        assert_eq!(fs::read_to_string(&manifest).unwrap().lines().count(), 3);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_column_list() {
+        let list_file = std::env::temp_dir().join("cc2p_test_read_column_list.txt");
+        fs::write(
+            &list_file,
+            "# selected columns\nid\n\n  amount , city\r\n#name\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_column_list(&list_file).unwrap(),
+            vec!["id", "amount", "city"]
+        );
+
+        fs::write(&list_file, "# nothing selected\n\n").unwrap();
+        assert!(read_column_list(&list_file).is_err());
+
+        fs::remove_file(&list_file).unwrap();
+        assert!(read_column_list(&list_file).is_err());
+    }
+
+    #[test]
+    fn test_read_manifest() {
+        let manifest_file = std::env::temp_dir().join("cc2p_test_read_manifest.csv");
+        fs::write(
+            &manifest_file,
+            "file,delimiter,header,encoding\neu_*.csv,;,,latin1\ndata/raw.txt,\\t,false,\n",
+        )
+        .unwrap();
+        let manifest = read_manifest(&manifest_file).unwrap();
+        let options = ConvertOptions::new().delimiter('|');
+
+        let eu = manifest.options_for(Path::new("in/eu_2024.csv"), &options);
+        assert_eq!(eu.delimiter, Some(Delimiter::Char(';')));
+        assert!(eu.has_header);
+        assert_eq!(eu.encoding.unwrap().name(), "windows-1252");
+        let raw = manifest.options_for(Path::new("data/raw.txt"), &options);
+        assert_eq!(raw.delimiter, Some(Delimiter::Char('\t')));
+        assert!(!raw.has_header);
+        assert!(raw.encoding.is_none());
+        let other = manifest.options_for(Path::new("us_2024.csv"), &options);
+        assert_eq!(other.delimiter, Some(Delimiter::Char('|')));
+
+        let json_file = std::env::temp_dir().join("cc2p_test_read_manifest.json");
+        fs::write(&json_file, r#"[{"file": "a.csv", "header": false}]"#).unwrap();
+        let manifest = read_manifest(&json_file).unwrap();
+        assert!(
+            !manifest
+                .options_for(Path::new("a.csv"), &options)
+                .has_header
+        );
+
+        fs::write(&json_file, r#"[{"file": "a.csv", "delimter": ";"}]"#).unwrap();
+        assert!(read_manifest(&json_file).is_err());
+        fs::write(&manifest_file, "file,encoding\na.csv,klingon\n").unwrap();
+        let err = read_manifest(&manifest_file).unwrap_err().to_string();
+        assert!(
+            err.contains("entry 1: unknown encoding 'klingon'"),
+            "{}",
+            err
+        );
+
+        fs::remove_file(&manifest_file).unwrap();
+        fs::remove_file(&json_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_renames() {
+        let data = "a,b,c\n1,x,2\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_renames.parquet");
+        let options = ConvertOptions::new()
+            .columns(vec!["c".to_string(), "a".to_string()])
+            .renames(parse_renames("a=id,c=count").unwrap());
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.field(0).name(), "count");
+        assert_eq!(schema.field(1).name(), "id");
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &ConvertOptions::new().renames(parse_renames("a=b").unwrap()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "renaming results in the duplicate column name 'b'"
+        );
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &options.renames(parse_renames("b=name").unwrap()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column 'b' does not exist, available columns: c, a"
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_header_names() {
+        let data = "1,x\n2,y\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_header_names.parquet");
+        let options = ConvertOptions::new().has_header(false);
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema().field(0).name(), "column_0");
+        assert_eq!(batches[0].schema().field(1).name(), "column_1");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.clone().column_prefix("c"),
+        )
+        .unwrap();
+        assert_eq!(read_parquet(&output_file)[0].schema().field(1).name(), "c1");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options
+                .clone()
+                .header_names(vec!["id".to_string(), "name".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(
+            read_parquet(&output_file)[0].schema().field(1).name(),
+            "name"
+        );
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &options.header_names(vec!["id".to_string()]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1 header names given, but the file has 2 columns"
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_null_column_type() {
+        // the note column is empty in the sample of two rows, but has a value further down
+        let data = "id,note,count\n1,,\n2,,\n3,late,\n";
+        let convert = |options: ConvertOptions| {
+            let mut parquet = vec![];
+            convert_reader_to_writer(data.as_bytes(), &mut parquet, &options.sampling(2))
+                .map(|summary| summary.schema)
+        };
+
+        let schema = convert(ConvertOptions::new()).unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+
+        let options = ConvertOptions::new()
+            .null_column_type(DataType::Int64)
+            .column_types(HashMap::from([("note".to_string(), DataType::Utf8)]));
+        let schema = convert(options).unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+
+        // the late value does not parse as the type
+        assert!(convert(ConvertOptions::new().null_column_type(DataType::Int64)).is_err());
+
+        let schema = convert(ConvertOptions::new().null_column_type(DataType::Null)).unwrap();
+        assert_eq!(schema.field(2).data_type(), &DataType::Null);
+
+        // merged columns take the type of the files with values
+        let dir = std::env::temp_dir().join("cc2p_test_convert_null_column_type");
+        fs::create_dir_all(&dir).unwrap();
+        let files = vec![dir.join("a.csv"), dir.join("b.csv")];
+        fs::write(&files[0], "id,count,note\n1,,\n").unwrap();
+        fs::write(&files[1], "id,count,note\n2,5,\n").unwrap();
+        let output = dir.join("merged.parquet");
+        let merge = merge_to_parquet(&files, &ConvertOptions::new().output(&output), true).unwrap();
+        assert_eq!(merge.summary.schema.field(1).data_type(), &DataType::Int64);
+        assert_eq!(merge.summary.schema.field(2).data_type(), &DataType::Utf8);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_emit_schema() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_emit_schema");
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.csv");
+        fs::write(&file_path, "id,name,note\n1,John,\n").unwrap();
+
+        let options = ConvertOptions::new()
+            .emit_schema(true)
+            .null_column_type(DataType::Null);
+        let summary = convert_to_parquet(&file_path, &options).unwrap();
+        assert_eq!(summary.schema.fields().len(), 3);
+
+        let document: SchemaDocument =
+            serde_json::from_reader(File::open(dir.join("data.parquet.schema.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            document.columns[0],
+            SchemaColumn {
+                name: "id".to_string(),
+                data_type: "int64".to_string(),
+                nullable: true,
+            }
+        );
+        assert_eq!(document.columns[1].data_type, "utf8");
+        assert_eq!(document.columns[2].data_type, "null");
+
+        let schema_file = write_schema_file(&dir.join("data.v1.pq"), &summary.schema).unwrap();
+        assert_eq!(schema_file, dir.join("data.v1.pq.schema.json"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_declared_schema() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_declared_schema");
+        fs::create_dir_all(&dir).unwrap();
+        let schema_file = dir.join("data.parquet.schema.json");
+        fs::write(
+            &schema_file,
+            r#"{"columns": [
+                {"name": "zip", "type": "utf8", "nullable": true},
+                {"name": "amount", "type": "float64", "nullable": true}
+            ]}"#,
+        )
+        .unwrap();
+        let schema = Arc::new(read_schema_file(&schema_file).unwrap());
+        let output_file = dir.join("data.parquet");
+        let options = ConvertOptions::new().schema(Some(schema)).sampling(1);
+
+        convert_reader_to_writer(
+            "code,value\n01234,1\n02345,2\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.field(0).name(), "zip");
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+
+        let err = convert_reader_to_writer(
+            "code,value\n01234,1\n02345,x\n".as_bytes(),
+            std::io::sink(),
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parser error: Error while parsing value x for column 'amount' at line 3"
+        );
+
+        let err =
+            convert_reader_to_writer("a\n1\n".as_bytes(), std::io::sink(), &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the schema declares 2 columns, but the file has 1 columns"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_all_strings() {
+        let data = "id,zip,flag\n1,00501,true\n2,,false\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_all_strings.parquet");
+
+        // the random and full strategies are not an error for a reader, as nothing is sampled
+        let options = ConvertOptions::new()
+            .all_strings(true)
+            .sample_strategy(SampleStrategy::Full);
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert!(summary
+            .schema
+            .fields()
+            .iter()
+            .all(|field| field.data_type() == &DataType::Utf8));
+        let batch = &read_parquet(&output_file)[0];
+        let zip = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(zip.value(0), "00501");
+        assert_eq!(batch.column(1).null_count(), 1);
+
+        let options = options.column_types(parse_column_types("id=int64").unwrap());
+        let summary = convert_reader_to_writer(data.as_bytes(), std::io::sink(), &options).unwrap();
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(summary.schema.field(2).data_type(), &DataType::Utf8);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_lenient_newlines() {
+        let data = "id,text,n\n1,broken\nline,3\n2,two\nline\nbreaks,4\n3,ok,5\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_lenient_newlines.parquet");
+
+        let err =
+            convert_reader_to_writer(data.as_bytes(), std::io::sink(), &ConvertOptions::new())
+                .unwrap_err();
+        assert_eq!(err.to_string(), "line 2 has 2 fields, expected 3");
+
+        let options = ConvertOptions::new().lenient_newlines(true);
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.ragged_rows, 0);
+        let batch = &read_parquet(&output_file)[0];
+        let text = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(text.value(0), "broken\nline");
+        assert_eq!(text.value(1), "two\nline\nbreaks");
+        assert_eq!(
+            batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<arrow_array::Int64Array>()
+                .unwrap()
+                .value(2),
+            5
+        );
+
+        // a fragment is not joined with a row which would have too many fields
+        let data = "id,text,n\n1,short\n2,ok,4\n";
+        let err = convert_reader_to_writer(data.as_bytes(), std::io::sink(), &options).unwrap_err();
+        assert_eq!(err.to_string(), "line 2 has 2 fields, expected 3");
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options.on_ragged(OnRagged::Skip),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 1);
+        assert_eq!(summary.ragged_rows, 1);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_on_ragged() {
+        let data = "a,b,c\n1,2,3\n4,5\n6,7,8,9\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_on_ragged.parquet");
+
+        let err =
+            convert_reader_to_writer(data.as_bytes(), std::io::sink(), &ConvertOptions::new())
+                .unwrap_err();
+        assert_eq!(err.to_string(), "line 3 has 2 fields, expected 3");
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().on_ragged(OnRagged::Skip),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 1);
+        assert_eq!(summary.ragged_rows, 2);
+
+        let err = convert_reader_to_writer(
+            data.as_bytes(),
+            std::io::sink(),
+            &ConvertOptions::new().on_ragged(OnRagged::Pad),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "line 4 has 4 fields, expected 3");
+
+        let summary = convert_reader_to_writer(
+            "a,b,c\n1,2,3\n4,5\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().on_ragged(OnRagged::Pad),
+        )
+        .unwrap();
+        assert_eq!(summary.ragged_rows, 1);
+        assert_eq!(read_parquet(&output_file)[0].column(2).null_count(), 1);
+
+        let summary = convert_reader_to_writer(
+            "a,b,c\n1,2,3\n6,7,8,9\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().on_ragged(OnRagged::Truncate),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 2);
+        assert_eq!(read_parquet(&output_file)[0].num_columns(), 3);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_on_invalid_utf8() {
+        let data = b"id,name\n1,Jos\xe9\n2,Ana\n3,\xff\xfe\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_on_invalid_utf8.parquet");
+
+        let err = convert_reader_to_writer(&data[..], std::io::sink(), &ConvertOptions::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid utf-8"), "{}", err);
+
+        let summary = convert_reader_to_writer(
+            &data[..],
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().on_invalid_utf8(OnInvalidUtf8::Replace),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.invalid_utf8_rows, 2);
+        let batches = read_parquet(&output_file);
+        let name = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(name.value(0), "Jos\u{fffd}");
+        assert_eq!(name.value(1), "Ana");
+
+        let summary = convert_reader_to_writer(
+            &data[..],
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new()
+                .on_invalid_utf8(OnInvalidUtf8::SkipRow)
+                .encoding(encoding_rs::UTF_8),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 1);
+        assert_eq!(summary.invalid_utf8_rows, 2);
+        assert_eq!(read_parquet(&output_file)[0].num_rows(), 1);
+
+        // the header is replaced rather than skipped
+        let summary = convert_reader_to_writer(
+            &b"id,n\xe4me\n1,Ana\n"[..],
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().on_invalid_utf8(OnInvalidUtf8::SkipRow),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 1);
+        assert_eq!(summary.invalid_utf8_rows, 0);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_multi_char_delimiter() {
+        let data = "id||name||note\n1||\"a||b\"||x|y\n2||\"say \"\"hi\"\"\"|||\n";
+        let output_file =
+            std::env::temp_dir().join("cc2p_test_convert_multi_char_delimiter.parquet");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().delimiter("||"),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].num_columns(), 3);
+        let column = |i: usize| {
+            batches[0]
+                .column(i)
+                .as_any()
+                .downcast_ref::<arrow_array::StringArray>()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(column(1).value(0), "a||b");
+        assert_eq!(column(1).value(1), "say \"hi\"");
+        assert_eq!(column(2).value(0), "x|y");
+        assert_eq!(column(2).value(1), "|");
+
+        // the delimiter is also found across the chunks of the input
+        let mut split = String::new();
+        delimiter::DelimiterReader::new(
+            BufReader::with_capacity(1, "a::b:c:::d".as_bytes()),
+            Some("::"),
+            Some(b'"'),
+        )
+        .read_to_string(&mut split)
+        .unwrap();
+        assert_eq!(split, "a\x1fb:c\x1f:d");
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_tsv_file() {
+        let dir = std::env::temp_dir().join("cc2p_test_convert_tsv_file");
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.tsv");
+        fs::write(&file_path, "id\tname,full\n1\tDoe, John\n").unwrap();
+
+        let summary = convert_to_parquet(&file_path, &ConvertOptions::new()).unwrap();
+        assert_eq!(summary.columns, 2);
+        assert_eq!(summary.output_path, dir.join("data.parquet"));
+
+        let summary =
+            convert_to_parquet(&file_path, &ConvertOptions::new().delimiter(',')).unwrap();
+        assert_eq!(summary.columns, 2);
+        let schema = read_parquet(&summary.output_path)[0].schema();
+        assert_eq!(schema.field(0).name(), "id\tname");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        let options = ConvertOptions::new().delimiter(Delimiter::Auto);
+
+        let inspection = inspect_reader("a;b;c\n1;\"x;y\";2,5\n".as_bytes(), &options).unwrap();
+        assert_eq!(inspection.detected_delimiter, Some(';'));
+        assert_eq!(inspection.schema.fields().len(), 3);
+        assert!(inspection.warnings.is_empty());
+
+        let inspection = inspect_reader("a\tb\n1\t2\n".as_bytes(), &options).unwrap();
+        assert_eq!(inspection.detected_delimiter, Some('\t'));
+
+        // both candidates are consistent, so the comma is used
+        let inspection = inspect_reader("a,b|c\n1,2|3\n".as_bytes(), &options).unwrap();
+        assert_eq!(inspection.detected_delimiter, None);
+        assert_eq!(inspection.schema.fields().len(), 2);
+        assert_eq!(
+            inspection.warnings,
+            vec!["the delimiter could not be detected, using ','"]
+        );
+    }
+
+    #[test]
+    fn test_convert_parse_bools() {
+        let data = "active,flag,count\nyes,Y,1\n1,n,0\nno,N,2\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_parse_bools.parquet");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().parse_bools(true),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Boolean);
+        assert_eq!(schema.field(1).data_type(), &DataType::Boolean);
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+        let active = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::BooleanArray>()
+            .unwrap();
+        assert!(active.value(0) && active.value(1) && !active.value(2));
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new()
+                .parse_bools(true)
+                .bool_values(vec!["y".to_string()], vec!["n".to_string()]),
+        )
+        .unwrap();
+        let schema = read_parquet(&output_file)[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &DataType::Boolean);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_decimal_columns() {
+        let data = "amount,price\n12.34,1.5\n-0.5,2.125\n,3\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_decimal_columns.parquet");
+
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new()
+                .column_types(parse_column_types("amount=decimal(10,2)").unwrap())
+                .decimal_columns(vec!["price".to_string()], 5, 3),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Decimal128(10, 2));
+        assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(5, 3));
+        let amount = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Decimal128Array>()
+            .unwrap();
+        assert_eq!(amount.value(0), 1234);
+        assert_eq!(amount.value(1), -50);
+        assert!(arrow_array::Array::is_null(amount, 2));
+        let price = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::Decimal128Array>()
+            .unwrap();
+        assert_eq!(price.value(1), 2125);
+
+        let error = convert_reader_to_writer(
+            "amount\n123.45\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().decimal_columns(vec!["amount".to_string()], 4, 2),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("123.45"), "{error}");
+
+        // digits beyond the scale fail instead of being rounded, trailing zeros fit
+        let options =
+            ConvertOptions::new().column_types(parse_column_types("x=decimal(10,2)").unwrap());
+        let error = convert_reader_to_writer(
+            "x\n1.5\n1.239\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Parser error: value 1.239 for column 'x' at line 3 has more fractional digits than the scale 2"
+        );
+        convert_reader_to_writer(
+            "x\n1.230\n-3\n".as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_inspect_sample_strategy() {
+        let source_file = std::env::temp_dir().join("cc2p_test_inspect_sample_strategy.csv");
+        let mut data = String::from("id,amount\n");
+        for i in 0..1000 {
+            // the amounts turn into floats after the head of the file
+            if i < 500 {
+                data.push_str(&format!("{i},{i}\n"));
+            } else {
+                data.push_str(&format!("{i},{i}.5\n"));
+            }
+        }
+        fs::write(&source_file, data).unwrap();
+
+        let options = ConvertOptions::new();
+        let head = inspect_csv(&source_file, &options).unwrap();
+        assert_eq!(head.schema.field(1).data_type(), &DataType::Int64);
+        assert_eq!(head.sampled_rows, 100);
+
+        let options = options.sample_strategy(SampleStrategy::Full);
+        let full = inspect_csv(&source_file, &options).unwrap();
+        assert_eq!(full.schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(full.sampled_rows, 1000);
+        assert_eq!(full.estimated_rows, Some(1000));
+
+        let options = options.sample_strategy(SampleStrategy::Random).sampling(20);
+        let random = inspect_csv(&source_file, &options).unwrap();
+        assert_eq!(random.schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(random.sampled_rows, 20);
+
+        // the data of a reader can only be read once
+        let result = inspect_reader(fs::read(&source_file).unwrap().as_slice(), &options);
+        assert!(result.is_err());
+
+        fs::remove_file(source_file).unwrap();
+    }
+
+    #[test]
+    fn test_inspect_strict_leading_zeros() {
+        let data = "code,amount,count\n007,0.5,0\n123,-01.5,10\n";
+        let types = |options: &ConvertOptions| -> Vec<DataType> {
+            let inspection = inspect_reader(data.as_bytes(), options).unwrap();
+            inspection
+                .schema
+                .fields()
+                .iter()
+                .map(|f| f.data_type().clone())
+                .collect()
+        };
+
+        assert_eq!(
+            types(&ConvertOptions::new()),
+            [DataType::Int64, DataType::Float64, DataType::Int64]
+        );
+        let options = ConvertOptions::new().strict_leading_zeros(true);
+        assert_eq!(
+            types(&options),
+            [DataType::Utf8, DataType::Utf8, DataType::Int64]
+        );
+        // a declared column type still wins
+        let options = options.column_types(parse_column_types("code=int64").unwrap());
+        assert_eq!(types(&options)[0], DataType::Int64);
+    }
+
+    #[test]
+    fn test_convert_int_sizing() {
+        let source_file = std::env::temp_dir().join("cc2p_test_convert_int_sizing.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_int_sizing.parquet");
+        let mut data = String::from("small,signed,large\n");
+        for i in 0..300 {
+            // the small column overflows uint8 after the sample
+            let small = if i < 250 { i % 200 } else { 1000 };
+            data.push_str(&format!("{},{},{}\n", small, -(i % 100), i * 100_000));
+        }
+        fs::write(&source_file, data).unwrap();
+
+        let options = ConvertOptions::new().output(&output_file);
+        let inspection = inspect_csv(&source_file, &options).unwrap();
+        assert_eq!(inspection.schema.field(0).data_type(), &DataType::Int64);
+
+        let options = options.int_sizing(IntSizing::Minimal);
+        let inspection = inspect_csv(&source_file, &options).unwrap();
+        let types: Vec<&DataType> = inspection
+            .schema
+            .fields()
+            .iter()
+            .map(|f| f.data_type())
+            .collect();
+        assert_eq!(
+            types,
+            [&DataType::UInt8, &DataType::Int8, &DataType::UInt32]
+        );
+
+        let error = convert_to_parquet(&source_file, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "value 1000 for column 'small' at line 252 does not fit in uint8"
+        );
+        assert!(!output_file.exists());
+
+        let summary = convert_to_parquet(&source_file, &options.promote_on_overflow(true)).unwrap();
+        assert_eq!(summary.rows_written, 300);
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(summary.schema.field(1).data_type(), &DataType::Int8);
+        assert_eq!(summary.warnings.len(), 1);
+
+        assert_eq!(
+            common_integer_type(&DataType::UInt8, &DataType::Int8),
+            Some(DataType::Int16)
+        );
+        assert_eq!(common_integer_type(&DataType::UInt8, &DataType::Utf8), None);
+
+        fs::remove_file(source_file).unwrap();
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_sep_directive() {
+        let data = "\u{feff}sep=;\r\nname;amount\r\nalice;15\r\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_sep_directive.parquet");
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(summary.rows_written, 1);
+        assert_eq!(summary.detected_delimiter, Some(';'));
+        let names: Vec<&String> = summary.schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, ["name", "amount"]);
+
+        // an explicit delimiter wins, but the directive is still not part of the data
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().delimiter(','),
+        )
+        .unwrap();
+        assert_eq!(summary.detected_delimiter, None);
+        let names: Vec<&String> = summary.schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, ["name;amount"]);
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_on_duplicate_header() {
+        let data = "id,name,id\n1,alice,a\n2,bob,b\n";
+        let output_file =
+            std::env::temp_dir().join("cc2p_test_convert_on_duplicate_header.parquet");
+        let convert = |policy| {
+            convert_reader_to_writer(
+                data.as_bytes(),
+                File::create(&output_file).unwrap(),
+                &ConvertOptions::new().on_duplicate_header(policy),
+            )
+        };
+
+        let summary = convert(OnDuplicateHeader::Rename).unwrap();
+        let names: Vec<&String> = summary.schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, ["id", "name", "id_1"]);
+        assert_eq!(
+            summary.warnings,
+            ["duplicate column 'id' is renamed to 'id_1'"]
+        );
+
+        let summary = convert(OnDuplicateHeader::First).unwrap();
+        let names: Vec<&String> = summary.schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, ["id", "name"]);
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(
+            summary.warnings,
+            ["duplicate column 'id' in column 3 is dropped"]
+        );
+        let batches = read_parquet(&output_file);
+        assert_eq!(batches[0].num_columns(), 2);
+
+        let error = convert(OnDuplicateHeader::Error).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "duplicate column name 'id' in columns 1 and 3"
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_normalize_headers() {
+        let data = " First Name ,customerID,HTTPStatus code,Zip\nalice,1,200,10115\n";
+        let names = |normalizations: Vec<NormalizeHeader>| {
+            let summary = convert_reader_to_writer(
+                data.as_bytes(),
+                Vec::new(),
+                &ConvertOptions::new().normalize_headers(normalizations),
+            )
+            .unwrap();
+            summary
+                .schema
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(vec![NormalizeHeader::Snake]),
+            ["first_name", "customer_id", "http_status_code", "zip"]
+        );
+        assert_eq!(
+            names(vec![NormalizeHeader::Lower, NormalizeHeader::Trim]),
+            ["first name", "customerid", "httpstatus code", "zip"]
+        );
+
+        // names which become equal follow the duplicate header policy
+        let error = convert_reader_to_writer(
+            "First Name,first_name\nalice,bob\n".as_bytes(),
+            Vec::new(),
+            &ConvertOptions::new()
+                .normalize_headers(vec![NormalizeHeader::Snake])
+                .on_duplicate_header(OnDuplicateHeader::Error),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "duplicate column name 'first_name' in columns 1 and 2"
+        );
+    }
+
+    #[test]
+    fn test_convert_number_format() {
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_number_format.parquet");
+        let convert = |data: &str, options: ConvertOptions| {
+            convert_reader_to_writer(
+                data.as_bytes(),
+                File::create(&output_file).unwrap(),
+                &options,
+            )
+        };
+
+        let data = "amount;count;code\n1.234,56;1.000.000;1,2,3\n-7,5;12;4\n1,2E3;0;5\n";
+        let options = ConvertOptions::new()
+            .delimiter(';')
+            .number_format(Some(parse_number_format("1.234,56").unwrap()));
+        let summary = convert(data, options).unwrap();
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Float64);
+        assert_eq!(summary.schema.field(1).data_type(), &DataType::Int64);
+        // the groups of a thousands separator have three digits
+        assert_eq!(summary.schema.field(2).data_type(), &DataType::Utf8);
+
+        let batches = read_parquet(&output_file);
+        let amount = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Float64Array>()
+            .unwrap();
+        assert_eq!(amount.values(), &[1234.56, -7.5, 1200.0]);
+        let count = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(count.value(0), 1_000_000);
+
+        // a quoted number may contain the delimiter
+        let options =
+            ConvertOptions::new().number_format(Some(parse_number_format("1,234.56").unwrap()));
+        let summary = convert("amount\n\"1,234.5\"\n", options.clone()).unwrap();
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Float64);
+        let error = convert("amount\n1.5\n", options.quoting(false)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the number format uses the delimiter ',', which requires quoting"
+        );
+
+        // a number of another format is no number of the column
+        let data = "amount\n1.500\n1.5\n12,5\n";
+        let options = ConvertOptions::new()
+            .delimiter(';')
+            .number_format(Some(parse_number_format("1.234,56").unwrap()));
+        let summary = convert(data, options.clone()).unwrap();
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Utf8);
+        let batches = read_parquet(&output_file);
+        let amount = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(amount.value(1), "1.5");
+        let options = options.column_types(parse_column_types("amount=float64").unwrap());
+        let error = convert(data, options).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("value 1.5 for column 'amount' at line 3 does not match the number format"));
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_locale() {
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_locale.parquet");
+        let data = "amount;count\n1.234,56;1.000\n-7,5;12\n";
+
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().locale(Locale::Eu),
+        )
+        .unwrap();
+        assert_eq!(summary.columns, 2);
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Float64);
+        assert_eq!(summary.schema.field(1).data_type(), &DataType::Int64);
+        let batches = read_parquet(&output_file);
+        let amount = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Float64Array>()
+            .unwrap();
+        assert_eq!(amount.values(), &[1234.56, -7.5]);
+
+        // a setting given after the preset overrides it
+        let data = "amount|count\n1234,56|1000\n";
+        let options = ConvertOptions::new()
+            .locale(Locale::Eu)
+            .delimiter('|')
+            .number_format(Some(parse_number_format("1234,56").unwrap()));
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(summary.columns, 2);
+        assert_eq!(summary.schema.field(0).data_type(), &DataType::Float64);
+
+        fs::remove_file(output_file).unwrap();
+    }
 
-    let list_fields: Vec<_> = deduplicated_fields.into_iter().collect();
+    #[test]
+    fn test_convert_empty_as() {
+        let data = "name,count,missing\n,1,\nbob,,\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_empty_as.parquet");
 
-    let deduplicated_schema = arrow_schema::Schema::new_with_metadata(list_fields, sc.metadata);
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new(),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        assert!(batches[0].column(0).is_null(0));
+        // the column without values is a string column of nulls
+        assert_eq!(batches[0].schema().field(2).data_type(), &DataType::Utf8);
+        assert_eq!(batches[0].column(2).null_count(), 2);
 
-    Arc::new(deduplicated_schema)
-}
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().empty_as(EmptyAs::String),
+        )
+        .unwrap();
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+        let name = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert!(batches[0].column(0).is_valid(0));
+        assert_eq!(name.value(0), "");
+        assert!(batches[0].column(1).is_null(1));
+        assert_eq!(batches[0].column(2).null_count(), 0);
 
-/// Searches for files matching the given pattern.
-///
-/// # Arguments
-///
-/// * `pattern` - A string slice representing the search pattern.
-///
-/// # Returns
-///
-/// A vector of `PathBuf` representing the paths of the matching files.
-///
-/// # Panics
-///
-/// This function will panic if it fails to read the file search pattern.
-///
-/// # Examples
-///
-/// ```rust
-/// use std::path::PathBuf;
-/// use cc2p::find_files;
-///
-/// let pattern = "testdata/sample*.csv";
-/// let files = find_files(pattern);
-///
-/// for file in files {
-///     println!("{:?}", file);
-/// }
-/// ```
-pub fn find_files(pattern: &str) -> Vec<PathBuf> {
-    let mut files = vec![];
-    let options = MatchOptions {
-        case_sensitive: false,
-        require_literal_separator: false,
-        require_literal_leading_dot: false,
-    };
+        fs::remove_file(output_file).unwrap();
+    }
 
-    for entry in glob_with(pattern, options).expect("failed to read file search pattern") {
-        match entry {
-            Ok(p) => {
-                if p.is_file() {
-                    if let Some(ext) = p.extension() {
-                        if ext == "csv" {
-                            files.push(p);
-                        }
-                    }
+    #[test]
+    fn test_convert_cancel_flag() {
+        let cancel_flag = Arc::new(AtomicBool::new(true));
+        let error = convert_reader_to_writer(
+            "a,b\n1,2\n".as_bytes(),
+            Vec::new(),
+            &ConvertOptions::new().cancel_flag(cancel_flag),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "the conversion was cancelled");
+    }
+
+    #[test]
+    fn test_convert_progress() {
+        let file_path = PathBuf::from("testdata/sample.csv");
+        let output_file = std::env::temp_dir().join("cc2p_test_progress.parquet");
+        let position = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let reported = Arc::clone(&position);
+        convert_to_parquet(
+            &file_path,
+            &ConvertOptions::new()
+                .output(&output_file)
+                .progress(move |bytes| reported.store(bytes, Ordering::SeqCst)),
+        )
+        .unwrap();
+        assert_eq!(
+            position.load(Ordering::SeqCst),
+            fs::metadata(&file_path).unwrap().len()
+        );
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_convert_on_schema() {
+        let schemas = Arc::new(std::sync::Mutex::new(vec![]));
+        let reported = Arc::clone(&schemas);
+        // the schema is reported before the conversion fails
+        let result = convert_reader_to_writer(
+            "id,name\n1,alice\nx,bob\n".as_bytes(),
+            Vec::new(),
+            &ConvertOptions::new()
+                .sampling(1)
+                .on_schema(move |schema| reported.lock().unwrap().push(schema.clone())),
+        );
+        assert!(result.is_err());
+
+        let schemas = schemas.lock().unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0].field(0).data_type(), &DataType::Int64);
+        assert_eq!(schemas[0].field(1).data_type(), &DataType::Utf8);
+    }
+
+    /// Generates CSV rows on the fly, counting the rows read so far.
+    struct RowGenerator {
+        rows: u64,
+        rows_read: Arc<std::sync::atomic::AtomicU64>,
+        line: Vec<u8>,
+        position: usize,
+    }
+
+    impl Read for RowGenerator {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.position == self.line.len() {
+                let row = self.rows_read.load(Ordering::SeqCst);
+                if row == self.rows {
+                    return Ok(0);
                 }
+                self.line = format!("{},name {},{}.5\n", row, row % 100, row).into_bytes();
+                self.position = 0;
+                self.rows_read.fetch_add(1, Ordering::SeqCst);
             }
-            Err(e) => eprintln!("{:?}", e),
+            let length = buf.len().min(self.line.len() - self.position);
+            buf[..length].copy_from_slice(&self.line[self.position..self.position + length]);
+            self.position += length;
+            Ok(length)
         }
     }
 
-    files
-}
+    /// Records the number of rows read when the first row group is written.
+    struct FirstRowGroupWriter {
+        rows_read: Arc<std::sync::atomic::AtomicU64>,
+        rows_read_at_first_row_group: Arc<std::sync::Mutex<Option<u64>>>,
+        bytes_written: usize,
+    }
 
-/// Cleans a given string by removing any characters that are not alphanumeric or whitespace.
-///
-/// # Arguments
-///
-/// * `column_name` - The string to be cleaned.
-///
-/// # Examples
-///
-/// ```rust
-/// use cc2p::*;
-///
-/// let name = clean_column_name("John!Doe");
-/// assert_eq!(name, "JohnDoe");
-///
-/// let name = clean_column_name("Welcome, User 123!");
-/// assert_eq!(name, "Welcome User 123");
-/// ```
-///
-/// # Returns
-///
-/// A `String` containing the cleaned string, with all non-alphanumeric characters removed.
-pub fn clean_column_name(column_name: &str) -> String {
-    let cleaned = regex::Regex::new(r"[^a-zA-Z0-9_\-\s]")
-        .unwrap()
-        .replace_all(column_name, "");
+    impl Write for FirstRowGroupWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.bytes_written += buf.len();
+            // the first four bytes are the magic number written on creation
+            if self.bytes_written > 4 {
+                let mut first = self.rows_read_at_first_row_group.lock().unwrap();
+                first.get_or_insert(self.rows_read.load(Ordering::SeqCst));
+            }
+            Ok(buf.len())
+        }
 
-    cleaned.to_string()
-}
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use arrow_schema::Field;
+    #[test]
+    fn test_convert_streaming() {
+        let rows = 200_000;
+        let rows_read = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let first_row_group = Arc::new(std::sync::Mutex::new(None));
+        let mut reader = RowGenerator {
+            rows,
+            rows_read: rows_read.clone(),
+            line: b"id,name,amount\n".to_vec(),
+            position: 0,
+        };
+        let options = ConvertOptions::new().batch_size(500).row_group_size(10_000);
+        let writer = FirstRowGroupWriter {
+            rows_read: rows_read.clone(),
+            rows_read_at_first_row_group: first_row_group.clone(),
+            bytes_written: 0,
+        };
+
+        let summary = convert_reader_to_writer(&mut reader, writer, &options).unwrap();
+        assert_eq!(summary.rows_written, rows);
+        // the first row group is written long before the end of the data is read
+        let rows_read_at_first_row_group = first_row_group.lock().unwrap().unwrap();
+        assert!(rows_read_at_first_row_group < 20_000);
+    }
+
+    /// Returns the peak resident set size of the process in KiB, from `/proc/self/status`.
+    fn peak_resident_kib() -> u64 {
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        let line = status
+            .lines()
+            .find(|line| line.starts_with("VmHWM:"))
+            .unwrap();
+        line.split_whitespace().nth(1).unwrap().parse().unwrap()
+    }
 
+    /// Converts about 2 GiB of generated CSV data and checks that the peak memory stays bounded.
+    ///
+    /// It is too slow for the test suite and measures the memory of the whole process, so it only
+    /// runs on Linux when asked for: `cargo test --release -- --ignored test_convert_large_input_memory`.
     #[test]
-    fn test_convert_to_parquet() {
-        let mut source_file = std::env::current_dir().unwrap();
-        source_file.push("testdata");
-        source_file.push("sample_empty_header.csv");
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn test_convert_large_input_memory() {
+        let rows = 80_000_000;
+        let rows_read = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let mut reader = RowGenerator {
+            rows,
+            rows_read: rows_read.clone(),
+            line: b"id,name,amount\n".to_vec(),
+            position: 0,
+        };
+        // resets the peak resident set size to the current one
+        std::fs::write("/proc/self/clear_refs", "5").unwrap();
+        let before = peak_resident_kib();
 
-        let result = convert_to_parquet(&source_file, ',', true, 10);
+        let summary =
+            convert_reader_to_writer(&mut reader, std::io::sink(), &ConvertOptions::new()).unwrap();
+        assert_eq!(summary.rows_written, rows);
+        let growth_mib = (peak_resident_kib() - before) / 1024;
+        assert!(growth_mib < 256, "peak memory grew by {} MiB", growth_mib);
+    }
 
-        // Check that the function completed successfully
-        assert!(result.is_ok());
+    #[test]
+    fn test_convert_dictionary() {
+        let data: String = std::iter::once("country,city\n".to_string())
+            .chain((0..100).map(|i| format!("US,city {}\n", i % 3)))
+            .collect();
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_dictionary.parquet");
+        let has_dictionary = |options: &ConvertOptions| {
+            let summary = convert_reader_to_writer(
+                data.as_bytes(),
+                File::create(&output_file).unwrap(),
+                options,
+            )
+            .unwrap();
+            let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+            let row_group = reader.metadata().row_group(0).clone();
+            let columns: Vec<bool> = row_group
+                .columns()
+                .iter()
+                .map(|column| column.dictionary_page_offset().is_some())
+                .collect();
+            (columns, summary.warnings)
+        };
 
-        let parquet_file = PathBuf::from("testdata/sample_empty_header.parquet");
-        // Verify the parquet file was created
-        assert!(parquet_file.exists());
+        assert_eq!(has_dictionary(&ConvertOptions::new()).0, vec![true, true]);
+        assert_eq!(
+            has_dictionary(&ConvertOptions::new().dictionary(false)).0,
+            vec![false, false]
+        );
+        let (columns, warnings) = has_dictionary(
+            &ConvertOptions::new()
+                .dictionary(false)
+                .dictionary_columns(parse_dictionary_columns("city=on,missing=off").unwrap()),
+        );
+        assert_eq!(columns, vec![false, true]);
+        assert_eq!(
+            warnings,
+            vec!["column 'missing' in dictionary columns does not exist"]
+        );
 
-        // Optionally, clean up the parquet file
-        fs::remove_file(parquet_file).unwrap();
+        fs::remove_file(output_file).unwrap();
     }
 
     #[test]
-    fn test_convert_to_parquet_delimiter() {
-        let mut source_file = std::env::current_dir().unwrap();
-        source_file.push("testdata");
-        source_file.push("sample_delimiter.csv");
+    fn test_convert_page_size_and_version() {
+        let data: String = std::iter::once("id,name\n".to_string())
+            .chain((0..5000).map(|i| format!("{},name {}\n", i, i)))
+            .collect();
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_page_size.parquet");
 
-        let result = convert_to_parquet(&source_file, ';', true, 10);
+        convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new()
+                .page_size(1024)
+                .parquet_version(WriterVersion::PARQUET_2_0),
+        )
+        .unwrap();
+        let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().version(), 2);
+        let mut pages = reader
+            .get_row_group(0)
+            .unwrap()
+            .get_column_page_reader(0)
+            .unwrap();
+        let mut data_pages = 0;
+        while let Some(page) = pages.get_next_page().unwrap() {
+            if page.page_type() == parquet::basic::PageType::DATA_PAGE_V2 {
+                data_pages += 1;
+            }
+        }
+        assert!(data_pages > 1);
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 5000);
 
-        // Check that the function completed successfully
-        assert!(result.is_ok());
+        fs::remove_file(output_file).unwrap();
+    }
 
-        // Verify the parquet file was created
-        let parquet_file = PathBuf::from("testdata/sample_delimiter.parquet");
-        assert!(parquet_file.exists());
+    #[test]
+    fn test_convert_statistics() {
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_statistics.parquet");
+        let has_statistics = |statistics: EnabledStatistics| {
+            convert_reader_to_writer(
+                "id,name\n1,a\n2,b\n".as_bytes(),
+                File::create(&output_file).unwrap(),
+                &ConvertOptions::new().statistics(statistics),
+            )
+            .unwrap();
+            let reader = SerializedFileReader::new(File::open(&output_file).unwrap()).unwrap();
+            let column = reader.metadata().row_group(0).column(0).clone();
+            column.statistics().is_some()
+        };
 
-        // Optionally, clean up the parquet file
-        fs::remove_file(parquet_file).unwrap();
+        assert!(has_statistics(EnabledStatistics::Page));
+        assert!(has_statistics(EnabledStatistics::Chunk));
+        assert!(!has_statistics(EnabledStatistics::None));
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    fn read_parquet(path: &Path) -> Vec<arrow_array::RecordBatch> {
+        let file = File::open(path).unwrap();
+        parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect()
     }
 
     #[test]
-    fn test_convert_to_parquet_no_header() {
-        let mut source_file = std::env::current_dir().unwrap();
-        source_file.push("testdata");
-        source_file.push("sample_no_header.csv");
+    fn test_convert_column_types() {
+        let data = "zip,amount\n01234,10\n98765,20\n";
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_column_types.parquet");
+        let column_types = parse_column_types("zip=utf8,amount=float64,missing=bool").unwrap();
 
-        let result = convert_to_parquet(&source_file, ',', false, 10);
+        let summary = convert_reader_to_writer(
+            data.as_bytes(),
+            File::create(&output_file).unwrap(),
+            &ConvertOptions::new().column_types(column_types),
+        )
+        .unwrap();
+        assert_eq!(summary.warnings.len(), 1);
 
-        // Check that the function completed successfully
-        assert!(result.is_ok());
+        let batches = read_parquet(&output_file);
+        let schema = batches[0].schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        let zip = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(zip.value(0), "01234");
 
-        // Verify the parquet file was created
-        let parquet_file = PathBuf::from("testdata/sample_no_header.parquet");
-        assert!(parquet_file.exists());
+        fs::remove_file(output_file).unwrap();
+    }
 
-        // Optionally, clean up the parquet file
-        fs::remove_file(parquet_file).unwrap();
+    #[test]
+    fn test_parse_column_types() {
+        let column_types = parse_column_types("a=int32,b=String,c=timestamp").unwrap();
+        assert_eq!(column_types["a"], DataType::Int32);
+        assert_eq!(column_types["b"], DataType::Utf8);
+        assert_eq!(
+            column_types["c"],
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+        assert!(parse_column_types("").unwrap().is_empty());
+        assert!(parse_column_types("a").is_err());
+        let column_types = parse_column_types("a=decimal(18,2),b=int8").unwrap();
+        assert_eq!(column_types["a"], DataType::Decimal128(18, 2));
+        assert_eq!(column_types["b"], DataType::Int8);
+        assert!(parse_column_types("a=decimal(39,2)").is_err());
+        assert!(parse_column_types("a=decimal(4,6)").is_err());
+        assert!(parse_column_types("a=money").is_err());
     }
 
     #[test]
@@ -330,11 +7698,359 @@ mod tests {
         assert_eq!(clean_column_name("ab c "), "ab c ");
     }
 
+    #[test]
+    fn test_parse_compression() {
+        assert_eq!(
+            parse_compression("none").unwrap(),
+            Compression::UNCOMPRESSED
+        );
+        assert_eq!(parse_compression("SNAPPY").unwrap(), Compression::SNAPPY);
+        assert_eq!(parse_compression("lz4").unwrap(), Compression::LZ4_RAW);
+        assert_eq!(
+            parse_compression("zstd:9").unwrap(),
+            Compression::ZSTD(ZstdLevel::try_new(9).unwrap())
+        );
+        assert_eq!(
+            parse_compression("Brotli:5").unwrap(),
+            Compression::BROTLI(BrotliLevel::try_new(5).unwrap())
+        );
+        assert!(parse_compression("gzip").is_ok());
+        assert!(parse_compression("zstd:99").is_err());
+        assert!(parse_compression("zstd:fast").is_err());
+        assert!(parse_compression("snappy:1").is_err());
+        assert!(parse_compression("rar").is_err());
+    }
+
+    #[test]
+    fn test_output_file_name() {
+        let file = Path::new("data/2024/jan.csv.gz");
+        assert_eq!(
+            output_file_name("{stem}", file, DEFAULT_OUTPUT_EXTENSION).unwrap(),
+            "jan.parquet"
+        );
+        assert_eq!(
+            output_file_name("{parent}_{stem}_{ext}", file, DEFAULT_OUTPUT_EXTENSION).unwrap(),
+            "2024_jan_csv.gz.parquet"
+        );
+        assert_eq!(
+            output_file_name("out.parquet", Path::new("a.csv"), DEFAULT_OUTPUT_EXTENSION).unwrap(),
+            "out.parquet"
+        );
+        assert!(output_file_name("{stem", file, DEFAULT_OUTPUT_EXTENSION).is_err());
+        assert!(output_file_name("{parent}/{stem}", file, DEFAULT_OUTPUT_EXTENSION).is_err());
+        assert!(output_file_name("", file, DEFAULT_OUTPUT_EXTENSION).is_err());
+        assert_eq!(output_file_name("{stem}.pq", file, "pq").unwrap(), "jan.pq");
+    }
+
     #[test]
     fn test_find_files() {
-        assert_eq!(find_files("testdata/sample.csv").len(), 1);
-        assert_eq!(find_files("testdata/*.csv").len(), 4);
-        assert_eq!(find_files("not-exist/*.csv").len(), 0);
-        assert_eq!(find_files("testdata/*delimi*.csv").len(), 1);
+        assert_eq!(find_files("testdata/sample.csv").unwrap().len(), 1);
+        assert_eq!(find_files("testdata/*.csv").unwrap().len(), 5);
+        assert_eq!(find_files("not-exist/*.csv").unwrap().len(), 0);
+        assert_eq!(find_files("testdata/*delimi*.csv").unwrap().len(), 1);
+        assert_eq!(find_files("testdata/*.CSV").unwrap().len(), 5);
+        assert_eq!(
+            find_files("testdata/sample.csv, testdata/*.csv")
+                .unwrap()
+                .len(),
+            5
+        );
+        assert_eq!(
+            find_files("testdata/sample{,_no_header}.csv")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(find_files("testdata/**/*.csv").unwrap().len(), 7);
+        assert_eq!(find_files("testdata").unwrap().len(), 5);
+        assert_eq!(find_files("testdata/").unwrap().len(), 5);
+        assert_eq!(
+            find_files("testdata/nested,testdata/sample.csv")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            find_files("a[").unwrap_err(),
+            "invalid pattern 'a[': invalid range pattern"
+        );
+        assert!(find_files_recursive("testdata/{a,b", None).is_err());
+
+        // only data files are returned unless any extension is asked for
+        let dir = std::env::temp_dir().join("cc2p_test_find_files");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.csv", "UPPER.CSV", "notes.txt", "x.parquet"] {
+            std::fs::write(dir.join(name), "a\n1\n").unwrap();
+        }
+        let pattern = dir.join("*").display().to_string();
+        let mut files = find_files(&pattern).unwrap();
+        files.sort();
+        assert_eq!(files, vec![dir.join("UPPER.CSV"), dir.join("a.csv")]);
+        assert_eq!(
+            find_files_with(std::slice::from_ref(&pattern), true, true)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(find_files_recursive(&pattern, None).unwrap().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_zip_entries() {
+        let archive = Path::new("testdata/sample.zip");
+        let entries = find_zip_entries(archive, &["*.csv".to_string()], false).unwrap();
+        assert_eq!(
+            entries,
+            vec![archive.join("sales.csv"), archive.join("2024/q1.csv")]
+        );
+        assert!(find_zip_entries(archive, &["*.CSV".to_string()], false)
+            .unwrap()
+            .is_empty());
+        assert!(find_zip_entries(Path::new("testdata/sample.csv"), &[], false).is_err());
+
+        // the deflated entry is read twice for the full sample strategy
+        let output_file = std::env::temp_dir().join("cc2p_test_convert_zip_entries.parquet");
+        let options = ConvertOptions::new()
+            .sample_strategy(SampleStrategy::Full)
+            .output(&output_file);
+        let summary = convert_to_parquet(&entries[0], &options).unwrap();
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.schema.field(1).data_type(), &DataType::Float64);
+
+        let inspection = inspect_csv(&entries[1], &ConvertOptions::new()).unwrap();
+        assert_eq!(inspection.estimated_rows, Some(2));
+        assert!(convert_to_parquet(&archive.join("missing.csv"), &options).is_err());
+
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_filter_modified_after() {
+        let dir = std::env::temp_dir().join("cc2p_test_filter_modified_after");
+        fs::create_dir_all(&dir).unwrap();
+        let cutoff = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let old_file = dir.join("old.csv");
+        let new_file = dir.join("new.csv");
+        let missing_file = dir.join("missing.csv");
+        for (file, modified) in [
+            (&old_file, cutoff - Duration::from_secs(60)),
+            (&new_file, cutoff + Duration::from_secs(60)),
+        ] {
+            File::create(file).unwrap().set_modified(modified).unwrap();
+        }
+
+        let (kept, skipped) = filter_modified_after(
+            vec![old_file.clone(), missing_file.clone(), new_file.clone()],
+            cutoff,
+        );
+        assert_eq!(kept, vec![new_file.clone()]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, missing_file);
+        assert_eq!(skipped[0].1.kind(), std::io::ErrorKind::NotFound);
+
+        // a file modified exactly at the cutoff is not newer
+        File::options()
+            .write(true)
+            .open(&old_file)
+            .unwrap()
+            .set_modified(cutoff)
+            .unwrap();
+        assert_eq!(filter_modified_after(vec![old_file], cutoff).0.len(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Records the warnings logged by the library.
+    struct WarningLogger {
+        warnings: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl log::Log for WarningLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.warnings
+                    .lock()
+                    .unwrap()
+                    .push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_log_warnings() {
+        static LOGGER: WarningLogger = WarningLogger {
+            warnings: std::sync::Mutex::new(Vec::new()),
+        };
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let files = find_files_recursive("cc2p-missing-directory/*.csv", None).unwrap();
+        assert!(files.is_empty());
+        assert!(LOGGER
+            .warnings
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|warning| warning.contains("cc2p-missing-directory")));
+    }
+
+    #[test]
+    fn test_find_files_escaped_directory() {
+        // the names of the directories hold wildcards which are matched literally
+        let base = std::env::temp_dir().join("cc2p_test_find_files_escaped");
+        let dir = base.join("a[b");
+        fs::create_dir_all(dir.join("x{y")).unwrap();
+        fs::write(dir.join("one.csv"), "a\n1\n").unwrap();
+        fs::write(dir.join("x{y").join("two.csv"), "a\n1\n").unwrap();
+
+        let directory = dir.display().to_string();
+        assert_eq!(find_files(&directory).unwrap(), vec![dir.join("one.csv")]);
+        let pattern = format!("{}/*.csv", escape_pattern(&directory));
+        assert_eq!(find_files(&pattern).unwrap(), vec![dir.join("one.csv")]);
+        assert_eq!(search_root(&pattern), dir);
+        assert_eq!(find_files_recursive(&directory, None).unwrap().len(), 2);
+        assert_eq!(find_files_recursive(&pattern, Some(1)).unwrap().len(), 1);
+        let nested = escape_pattern(&dir.join("x{y").display().to_string());
+        assert_eq!(
+            find_files_recursive(&format!("{}/*.csv", nested), None).unwrap(),
+            vec![dir.join("x{y").join("two.csv")]
+        );
+
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn test_find_files_recursive() {
+        assert_eq!(
+            find_files_recursive("testdata/*.csv", None).unwrap().len(),
+            7
+        );
+        assert_eq!(find_files_recursive("testdata", None).unwrap().len(), 7);
+        assert_eq!(
+            find_files_recursive("testdata/*.csv", Some(1))
+                .unwrap()
+                .len(),
+            5
+        );
+        assert_eq!(
+            find_files_recursive("testdata/*.csv", Some(2))
+                .unwrap()
+                .len(),
+            6
+        );
+        assert_eq!(
+            find_files_recursive("testdata/nested/*.csv", None)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            find_files_recursive("testdata/*deeper*.csv", None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            find_files_recursive("testdata/{nested,missing}/*.csv", None)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            find_files_recursive("not-exist/*.csv", None).unwrap().len(),
+            0
+        );
+        assert_eq!(
+            find_files_recursive_with("testdata/*.CSV", None, false, false)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_target_file_path() {
+        let file = Path::new("data/2024/jan.csv");
+        let output_dir = Path::new("out");
+
+        assert_eq!(
+            target_file_path(file, None, None, DEFAULT_OUTPUT_EXTENSION),
+            PathBuf::from("data/2024/jan.parquet")
+        );
+        assert_eq!(
+            target_file_path(file, None, Some(output_dir), DEFAULT_OUTPUT_EXTENSION),
+            PathBuf::from("out/jan.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                file,
+                Some(Path::new("data")),
+                Some(output_dir),
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("out/2024/jan.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                file,
+                Some(Path::new("other")),
+                Some(output_dir),
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("out/jan.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                Path::new("data/jan.csv.gz"),
+                None,
+                Some(output_dir),
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("out/jan.parquet")
+        );
+
+        // a file which is not known to be CSV data keeps its whole name
+        assert_eq!(
+            target_file_path(Path::new("data/dump"), None, None, DEFAULT_OUTPUT_EXTENSION),
+            PathBuf::from("data/dump.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                Path::new("data/dump.dat"),
+                None,
+                None,
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("data/dump.dat.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                Path::new("data/dump.parquet"),
+                None,
+                None,
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("data/dump.parquet.parquet")
+        );
+        assert_eq!(
+            target_file_path(
+                Path::new("data/JAN.CSV"),
+                None,
+                None,
+                DEFAULT_OUTPUT_EXTENSION
+            ),
+            PathBuf::from("data/JAN.parquet")
+        );
+        assert_eq!(
+            target_file_path(Path::new("data/dump.dat"), None, Some(output_dir), "pq"),
+            PathBuf::from("out/dump.dat.pq")
+        );
     }
 }
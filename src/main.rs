@@ -1,13 +1,42 @@
 extern crate core;
 
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::File;
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
-use clap::{arg, Parser};
-use indicatif::{ProgressBar, ProgressStyle};
+use arrow_schema::{DataType, Schema, SchemaRef};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::Parser;
+use encoding_rs::Encoding;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use parquet::basic::Compression;
+use parquet::file::properties::{EnabledStatistics, WriterVersion};
+use serde::Serialize;
 use tokio::runtime;
+use tokio::sync::Semaphore;
+use walkdir::WalkDir;
 
-use cc2p::{convert_to_parquet, find_files};
+use cc2p::{
+    append_to_dataset, convert_reader_to_writer, convert_to_parquet, convert_to_writer,
+    escape_pattern, expand_patterns, filter_modified_after, find_files_recursive_with,
+    find_files_with, find_zip_entries, inspect_csv, inspect_reader, is_zip_file, merge_to_parquet,
+    output_file_name, parse_column_types, parse_compression, parse_delimiter,
+    parse_dictionary_columns, parse_encoding, parse_null_column_type, parse_null_values,
+    parse_number_format, parse_output_extension, parse_parquet_version, parse_renames,
+    parse_statistics, part_file_name, preview_csv, preview_parquet, read_column_list, read_dataset,
+    read_manifest, read_schema_file, search_root, target_file_path, temporary_file_path,
+    validate_special_characters, write_checksums, write_file_atomically, write_schema_file,
+    ConversionSummary, ConvertOptions, Delimiter, EmptyAs, IfExists, InputCompression, IntSizing,
+    Locale, Manifest, NormalizeHeader, NumberFormat, OnDuplicateHeader, OnEmpty, OnInvalidUtf8,
+    OnRagged, SampleStrategy, Trim, DEFAULT_BATCH_SIZE, DEFAULT_DECIMAL_PRECISION,
+    DEFAULT_DECIMAL_SCALE, DEFAULT_MAX_PARTITIONS, DEFAULT_OUTPUT_EXTENSION,
+    DEFAULT_READ_BUFFER_SIZE, STDIN_OUTPUT_NAME, STDIN_PATH,
+};
 
 /// A command line parser for processing CSV files with specified parameters.
 ///
@@ -18,36 +47,633 @@ use cc2p::{convert_to_parquet, find_files};
 ///
 /// # Arguments
 ///
-/// * `path` - Represents the folder path for CSV search. Default value is "*.csv".
-/// * `delimiter` - Represents the delimiter used in CSV files. Default value is ",".
+/// * `path` - Represents the folder paths or ZIP archives for CSV search, or "-" for standard input. Default value is "*.csv".
+/// * `delimiter` - Represents the delimiter used in CSV files, a character, a string such as "||" or "auto". Default value is ",", or a tab for .tsv files.
+/// * `tsv` - Use a tab as the delimiter. Default value is `false`.
+/// * `psv` - Use a pipe as the delimiter. Default value is `false`.
 /// * `no_header` - Represents whether to include the header in the CSV search column. Default value is `false`.
-/// * `worker` - Number of worker threads to use for performing the task. Default value is 1.
+/// * `column_prefix` - Prefix of the column names generated with `no_header`. Default value is "column_".
+/// * `header_names` - Comma-separated column names of files without a header. Default is generated names.
+/// * `quote` - Quote character enclosing fields which contain delimiters. Default value is `"`.
+/// * `no_quoting` - Treat quote characters as regular data. Default value is `false`.
+/// * `comment` - Character starting comment lines which are skipped. Default is none.
+/// * `skip_rows` - Number of lines discarded before the header is read. Default value is 0.
+/// * `header_row` - 0-based row holding the column names, counted after the skipped lines. Default value is 0.
+/// * `skip_footer` - Number of data rows discarded at the end of each file. Default value is 0.
+/// * `limit` - Maximum number of data rows converted from each file.
+/// * `input_compression` - Compression of the CSV input: auto, gzip or none. Default value is "auto".
+/// * `encoding` - Character encoding of the CSV input, such as "latin1". Default value is "utf-8".
+/// * `manifest` - CSV or JSON file with the delimiter, header and encoding of individual files. Default is none.
+/// * `worker` - Number of worker threads of the runtime scheduling the conversions. Default value is 1.
+/// * `max_concurrent_files` - Maximum number of files converted at once. Default is the number of workers.
+/// * `timeout` - Maximum number of seconds a single file may take to convert. Default is no limit.
+/// * `retries` - Number of times a file is converted again after a transient I/O error. Default value is 0.
+/// * `intra_file_parallelism` - Number of row groups of a file encoded in parallel. Default value is 1.
+/// * `schema` - JSON schema file in the `emit_schema` format used instead of inferring the schema. Default is none.
 /// * `sampling` - Number of rows to sample for inferring the schema. Default value is 100.
+/// * `sample_strategy` - Which rows are sampled: head, random or full. Default value is "head".
+/// * `all_strings` - Write every column as a string column without inferring types. Default value is `false`.
+/// * `null_column_type` - Type of the columns without any value in the sample. Default value is "utf8".
+/// * `compression` - Compression codec used for the Parquet output. Default value is "lz4".
+/// * `row_group_size` - Maximum number of rows in each Parquet row group. Default value is 1048576.
+/// * `flush_every` - Number of rows after which a row group is closed and flushed to the output. Default is none.
+/// * `dictionary` - Dictionary encode columns: on or off. Default value is "on".
+/// * `dictionary_columns` - Comma-separated per-column dictionary settings, such as "country=on,id=off". Default is none.
+/// * `page_size` - Approximate maximum size in bytes of a Parquet data page. Default value is 1048576.
+/// * `parquet_version` - Version of the Parquet format: 1.0 or 2.0. Default value is "1.0".
+/// * `statistics` - Column statistics written: none, chunk or page. Default value is "page".
+/// * `batch_size` - Number of rows read and converted to a record batch at a time, also `--arrow-batch-size`. Default value is 1024.
+/// * `read_buffer_size` - Capacity in bytes of the buffers the CSV files are read through. Default value is 8192.
+/// * `parse_dates` - Infer date and timestamp columns from values matching `date_format`. Default value is `false`.
+/// * `date_format` - strftime-style format of the dates, such as "%d/%m/%Y". Default value is "%Y-%m-%d".
+/// * `parse_bools` - Infer boolean columns from `true_values` and `false_values`. Default value is `false`.
+/// * `true_values` - Comma-separated tokens of true values. Default value is "true,yes,y,1".
+/// * `false_values` - Comma-separated tokens of false values. Default value is "false,no,n,0".
+/// * `trim` - Remove leading and trailing whitespace from: none, fields, headers or both. Default value is "none".
+/// * `normalize_headers` - Comma-separated normalizations of the header names: lower, snake and trim. Default is none.
+/// * `columns` - Comma-separated columns or column indexes written to the Parquet files, in this order. Default is all columns.
+/// * `exclude_columns` - Comma-separated columns left out of the Parquet files. Default is none.
+/// * `columns_from` - Text file listing the columns written to the Parquet files. Default is none.
+/// * `rename` - Comma-separated column renames, such as "cust_id=customer_id". Default is none.
+/// * `on_duplicate_header` - What to do with duplicate column names: error, rename or first. Default value is "rename".
+/// * `empty_as` - How empty fields of string columns are written: null or string. Default value is "null".
+/// * `on_empty` - What to do with a file without data rows: skip, error or write-empty. Default value is "write-empty".
+/// * `int_sizing` - Type of integer columns: fixed64 or minimal. Default value is "fixed64", or "minimal" with `promote_on_overflow`.
+/// * `promote_on_overflow` - Infer minimal integer types and convert a file again with an overflowing column as int64. Default value is `false`.
+/// * `strict_leading_zeros` - Infer a column as strings if a sampled number has a leading zero. Default value is `false`.
+/// * `on_ragged` - What to do with rows with more or fewer fields than the header: skip, error, pad or truncate. Default value is "error".
+/// * `on_invalid_utf8` - What to do with rows with invalid UTF-8 data: error, replace or skip-row. Default value is "error".
+/// * `lenient_newlines` - Join rows with too few fields, split by line breaks in unquoted fields. Default value is `false`.
+/// * `null_values` - Comma-separated tokens written as null, such as "NA,NULL". Default is none.
+/// * `number_format` - The number 1234.56 written in the format of the numbers, such as "1.234,56". Default is plain numbers.
+/// * `locale` - Preset of the delimiter and number format, "eu" for ";" and "1.234,56". Default is none.
+/// * `output_dir` - Directory for the Parquet output files, or "-" for standard output. Default is next to each CSV file.
+/// * `checksums` - File to which the SHA-256 hash of every Parquet file written is written. Default is none.
+/// * `success_marker` - Write an empty "_SUCCESS" file to the output directory if no file failed. Default value is `false`.
+/// * `output` - Parquet file of a single input, or an output directory if it ends with "/" or is a directory. Default is none.
+/// * `stdout` - Write the Parquet data of a single input to standard output. Default value is `false`.
+/// * `column_types` - Comma-separated column type overrides, such as "zip=utf8,amount=float64". Default is none.
+/// * `decimal_columns` - Comma-separated columns written as decimals. Default is none.
+/// * `decimal_precision` - Total number of digits of the decimal columns. Default value is 18.
+/// * `decimal_scale` - Number of fractional digits of the decimal columns. Default value is 2.
+/// * `output_name_template` - Template for the Parquet file names, such as "{stem}_converted". Default is the CSV file name.
+/// * `output_extension` - Extension of the Parquet files, such as "pq". Default value is "parquet".
+/// * `emit_schema` - Write a JSON document describing the schema next to every Parquet file. Default value is `false`.
+/// * `no_metadata` - Do not record the conversion in the key-value metadata of the Parquet files. Default value is `false`.
+/// * `reproducible` - Write byte-identical Parquet files when the same data is converted twice. Default value is `false`.
+/// * `if_exists` - What to do when a Parquet output file already exists: overwrite, skip or error. Default value is "overwrite".
+/// * `error_report` - Path of a JSON report with the status of every file. Default is no report.
+/// * `fail_fast` - Stop converting the remaining files after the first error. Default value is `false`.
+/// * `max_errors` - Stop converting the remaining files once this many files failed. Default is no limit.
+/// * `partition_by` - Column whose values split the rows into Hive-style `column=value` directories. Default is none.
+/// * `keep_partition_column` - Keep the partition column in the partitioned files. Default value is `false`.
+/// * `max_partitions` - Maximum number of partitions of a single file. Default value is 1000.
+/// * `merge` - Merge all CSV files into this single Parquet file. Default is one Parquet file per CSV file.
+/// * `abort_on_mismatch` - Fail the merge if a file has a different schema instead of excluding it. Default value is `false`.
+/// * `row_group_per_file` - Write every merged file as one row group and record its path in the metadata. Default value is `false`.
+/// * `append` - Append every CSV file as a new part file to this Parquet dataset directory. Default is none.
+/// * `dry_run` - Only infer and print the schema of every file, without writing Parquet files. Default value is `false`.
+/// * `preview` - Number of rows of every Parquet file written printed as a table. Default is none.
+/// * `list` - Only print the absolute path of every matched file. Default value is `false`.
+/// * `color` - When to draw the progress bar in color: auto, always or never. Default value is "auto".
+/// * `stats` - Print the conversion time and throughput of every file. Default value is `false`.
+/// * `format` - Format of the summary: text or json. Default value is "text".
+/// * `quiet` - Only print errors, without the arguments, progress bar and summary. Default value is `false`.
+/// * `no_progress` - Hide the progress bars, keeping the arguments and summary. Default value is `false`.
+/// * `verbose` - Print the schema of every file to standard error before it is converted. Default value is `false`.
+/// * `log_level` - Level of the messages logged to standard error. Default is the filter of `RUST_LOG`, otherwise off.
+/// * `modified_after` - Only convert CSV files modified after this RFC 3339 time. Default is all files.
+/// * `recursive` - Search subdirectories for CSV files. Default value is `false`.
+/// * `max_depth` - Maximum number of directory levels to descend with `recursive`. Default is unlimited.
+/// * `patterns` - File name patterns matched in every directory path. Default is "*.csv".
+/// * `case_sensitive` - Match the paths and patterns case-sensitively. Default value is `false`.
+/// * `force` - Convert every file of a directory path, whatever its extension. Default value is `false`.
 ///
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Represents the folder path for CSV search.
-    #[arg(default_value_t = String::from("*.csv"))]
-    path: String,
+    /// Represents the folder path for CSV search, a directory or a pattern such as "data/*.csv". Several
+    /// comma-separated patterns are matched as well. Patterns support "?", "*", "**" for any number
+    /// of directories such as "data/**/*.csv", "[...]" and alternatives in braces such as
+    /// "{2023,2024}/*.csv". The path of an existing file is converted whatever its extension, apart
+    /// from a .zip archive, whose entries matching --pattern are converted into the output directory
+    /// or a directory named after the archive. Use "-" to read CSV data from standard input.
+    #[arg(default_values_t = [String::from("*.csv")])]
+    path: Vec<String>,
 
-    /// Represents the delimiter used in CSV files.
-    #[arg(short, long, default_value_t = String::from(","))]
-    delimiter: String,
+    /// Represents the delimiter used in CSV files, a character or a string such as "||". "\t" stands for a tab,
+    /// and "auto" detects ",", ";", tab or "|" from the first lines of every file.
+    /// Defaults to "," or to a tab for files with a .tsv extension, unless the file starts with a line
+    /// such as "sep=;".
+    #[arg(short, long, value_parser = parse_delimiter)]
+    delimiter: Option<Delimiter>,
+
+    /// Use a tab as the delimiter, the same as --delimiter "\t".
+    #[arg(long, default_value_t = false, conflicts_with_all = ["delimiter", "psv"])]
+    tsv: bool,
+
+    /// Use a pipe as the delimiter, the same as --delimiter "|".
+    #[arg(long, default_value_t = false, conflicts_with = "delimiter")]
+    psv: bool,
 
     /// Represents whether to include the header in the CSV search column.
     #[arg(short, long, default_value_t = false)]
     no_header: bool,
 
-    /// Number of worker threads to use for performing the task.
-    #[arg(short, long, default_value_t = 1)]
-    worker: u8,
+    /// Prefix of the column names generated with --no-header, followed by the column position starting at 0.
+    #[arg(long, default_value = "column_", requires = "no_header")]
+    column_prefix: String,
+
+    /// Comma-separated column names of files without a header, such as "id,name". A file with a
+    /// different number of columns fails.
+    #[arg(long, value_delimiter = ',', requires = "no_header")]
+    header_names: Vec<String>,
+
+    /// Quote character enclosing fields which contain delimiters. A quote inside a quoted field is escaped by doubling it.
+    #[arg(long, default_value_t = '"')]
+    quote: char,
+
+    /// Disable quoting, quote characters are kept as part of the field values.
+    #[arg(long, default_value_t = false)]
+    no_quoting: bool,
+
+    /// Skip lines beginning with this character, such as "#", anywhere in the file. Must differ from the delimiter and the quote character.
+    #[arg(long)]
+    comment: Option<char>,
+
+    /// Number of lines discarded at the start of each file, before the header is read.
+    #[arg(long, default_value_t = 0)]
+    skip_rows: usize,
+
+    /// 0-based row holding the column names, counted after the --skip-rows lines. The rows above
+    /// it, such as spreadsheet titles, are discarded; unlike skipped lines they are parsed as CSV.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        conflicts_with = "no_header"
+    )]
+    header_row: usize,
+
+    /// Number of data rows discarded at the end of each file, such as a totals line.
+    #[arg(long, default_value_t = 0)]
+    skip_footer: usize,
+
+    /// Maximum number of data rows converted from each file.
+    #[arg(long)]
+    limit: Option<u64>,
+
+    /// Compression of the CSV input: auto, gzip or none. auto detects gzip from a .gz extension.
+    #[arg(long, default_value = "auto")]
+    input_compression: InputCompression,
+
+    /// Character encoding of the CSV input, such as "utf-8", "latin1" or "windows-1252". Invalid bytes fail the file.
+    #[arg(long, value_parser = parse_encoding)]
+    encoding: Option<&'static Encoding>,
+
+    /// CSV or JSON file mapping file names or glob patterns, such as "eu_*.csv", to the delimiter,
+    /// header and encoding of the matching files. A file without an entry uses the options of the run.
+    #[arg(long, value_name = "FILE", conflicts_with = "merge")]
+    manifest: Option<PathBuf>,
+
+    /// Number of worker threads of the runtime scheduling the conversions, and the number of files
+    /// converted at once unless --max-concurrent-files is set. Each conversion runs on its own
+    /// blocking thread, so more workers than files do not convert any faster.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    worker: usize,
+
+    /// Maximum number of files converted at once, defaults to the number of workers. A file only
+    /// starts once another one finishes, so directories with thousands of files are never all
+    /// in flight at the same time.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_concurrent_files: Option<usize>,
+
+    /// Maximum number of seconds a single file may take to convert. A file which takes longer fails
+    /// with a timeout error and its partial output is deleted.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Number of times a file is converted again after a transient I/O error, such as on a network
+    /// mount, waiting 1, 2, 4... seconds in between. Parse and schema errors are never retried.
+    #[arg(long, default_value_t = 0)]
+    retries: u32,
+
+    /// Number of row groups of a single file converted and encoded in parallel, for large files.
+    /// Memory use grows with this value times --row-group-size.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    intra_file_parallelism: u64,
+
+    /// JSON schema file in the --emit-schema format whose column names and types are used instead of
+    /// inferring the schema. No rows are sampled.
+    #[arg(long)]
+    schema: Option<PathBuf>,
+
+    /// Write every column as a string column, skipping type inference. No rows are sampled and the
+    /// values are written as they are, for casting them downstream. --column-types still apply.
+    #[arg(long, default_value_t = false, conflicts_with = "schema")]
+    all_strings: bool,
+
+    /// Type of the columns without any value in the sample, such as "int64", or "null" to keep them
+    /// null. Values further down the file are parsed with it. --column-types override it.
+    #[arg(long, value_name = "TYPE", default_value = "utf8", value_parser = parse_null_column_type, conflicts_with = "schema")]
+    null_column_type: DataType,
 
     /// Number of rows to sample for inferring the schema.
     #[arg(short, long, default_value_t = 100)]
     sampling: u16,
+
+    /// Which rows are sampled for inferring the schema: head (the first rows), random (rows picked
+    /// from the whole file) or full (every row). random and full read every file twice and are not
+    /// supported for standard input.
+    #[arg(long, default_value = "head")]
+    sample_strategy: SampleStrategy,
+
+    /// Compression codec for the Parquet output: none, snappy, gzip, zstd, lz4 or brotli.
+    /// zstd and brotli accept an optional level, e.g. zstd:9.
+    #[arg(short, long, default_value = "lz4", value_parser = parse_compression)]
+    compression: Compression,
+
+    /// Maximum number of rows in each Parquet row group. The rows of a group are buffered in memory
+    /// while writing, so larger groups use more memory but are often faster to read.
+    #[arg(long, default_value_t = 1024 * 1024, value_parser = clap::value_parser!(u64).range(1..))]
+    row_group_size: u64,
+
+    /// Close a row group and flush it to the output every N rows, so the output of a long conversion
+    /// grows steadily. The smaller of this and --row-group-size limits the row groups.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with = "row_group_per_file"
+    )]
+    flush_every: Option<usize>,
+
+    /// Dictionary encode columns: on or off. Dictionary encoding shrinks columns with few distinct values.
+    #[arg(long, default_value = "on", value_parser = parse_switch, action = clap::ArgAction::Set)]
+    dictionary: bool,
+
+    /// Comma-separated per-column dictionary settings overriding --dictionary, such as "country=on,id=off".
+    #[arg(long, value_parser = parse_dictionary_columns)]
+    dictionary_columns: Option<HashMap<String, bool>>,
+
+    /// Approximate maximum size in bytes of a Parquet data page, at most 2147483647.
+    #[arg(long, default_value_t = 1024 * 1024, value_parser = clap::value_parser!(u64).range(1..=i32::MAX as u64))]
+    page_size: u64,
+
+    /// Version of the Parquet format: 1.0, readable by most readers, or 2.0 with data page v2 and
+    /// delta encodings, which is often more compact but not supported by older readers.
+    #[arg(long, default_value = "1.0", value_parser = parse_parquet_version)]
+    parquet_version: WriterVersion,
+
+    /// Column statistics written for query engines to skip data: none, chunk (per column chunk) or
+    /// page (per column chunk and page). The minimum and maximum reveal values of the column.
+    #[arg(long, default_value = "page", value_parser = parse_statistics)]
+    statistics: EnabledStatistics,
+
+    /// Number of rows read and converted to an Arrow record batch at a time, independent of
+    /// --row-group-size: batches are collected into row groups of that size. The file is streamed
+    /// in batches of this size, so memory use does not grow with the file size.
+    #[arg(long, visible_alias = "arrow-batch-size", default_value_t = DEFAULT_BATCH_SIZE as u64, value_parser = clap::value_parser!(u64).range(1..))]
+    batch_size: u64,
+
+    /// Capacity in bytes of the buffers each CSV file is read through. A larger buffer, such as
+    /// 1048576, may convert large files faster on fast disks.
+    #[arg(long, default_value_t = DEFAULT_READ_BUFFER_SIZE as u64, value_parser = clap::value_parser!(u64).range(1..))]
+    read_buffer_size: u64,
+
+    /// Infer date and timestamp columns from values matching --date-format. Columns where only some sampled values match are written as strings.
+    #[arg(long, default_value_t = false)]
+    parse_dates: bool,
+
+    /// strftime-style format used with --parse-dates, such as "%d/%m/%Y" or "%d/%m/%Y %H:%M". A format with a time infers timestamps.
+    #[arg(long, default_value = "%Y-%m-%d", requires = "parse_dates")]
+    date_format: String,
+
+    /// Infer columns whose sampled values are all --true-values or --false-values as booleans.
+    #[arg(long, default_value_t = false)]
+    parse_bools: bool,
+
+    /// Comma-separated tokens of true values used with --parse-bools, compared case-insensitively.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "true,yes,y,1",
+        requires = "parse_bools"
+    )]
+    true_values: Vec<String>,
+
+    /// Comma-separated tokens of false values used with --parse-bools, compared case-insensitively.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "false,no,n,0",
+        requires = "parse_bools"
+    )]
+    false_values: Vec<String>,
+
+    /// Remove leading and trailing whitespace from field values and header names: none, fields, headers or both.
+    #[arg(long, default_value = "none")]
+    trim: Trim,
+
+    /// Comma-separated normalizations of the header names: lower, snake and trim, such as "trim,snake"
+    /// for "First Name" to "first_name". Other column options use the normalized names, and names
+    /// which become equal are handled by --on-duplicate-header.
+    #[arg(long, value_delimiter = ',')]
+    normalize_headers: Vec<NormalizeHeader>,
+
+    /// Comma-separated columns written to the Parquet files, in this order, such as "id,name".
+    /// A number which is not a column name is the 0-based index of a column, such as "0,3,5".
+    /// A column which does not exist fails the file.
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// Comma-separated columns left out of the Parquet files, by name or index. A column which does not exist fails the file.
+    #[arg(long, value_delimiter = ',', conflicts_with = "columns")]
+    exclude_columns: Vec<String>,
+
+    /// Text file listing the columns written to the Parquet files, in this order, one per line or
+    /// comma-separated, an alternative to --columns for long selections. Blank lines and lines
+    /// starting with "#" are skipped.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["columns", "exclude_columns"])]
+    columns_from: Option<PathBuf>,
+
+    /// Comma-separated column renames, such as "cust_id=customer_id,amt=amount", applied after --columns.
+    /// Renaming which results in duplicate names fails the file.
+    #[arg(long, value_parser = parse_renames)]
+    rename: Option<HashMap<String, String>>,
+
+    /// What to do with rows with more or fewer fields than the header: skip, error, pad (fill missing
+    /// fields with nulls) or truncate (drop extra fields).
+    #[arg(long, default_value = "error")]
+    on_ragged: OnRagged,
+
+    /// What to do with rows with invalid UTF-8 data: error, replace (substitute U+FFFD for invalid
+    /// bytes) or skip-row. The number of affected rows is printed per file.
+    #[arg(long, default_value = "error")]
+    on_invalid_utf8: OnInvalidUtf8,
+
+    /// Join a row with fewer fields than the header with the following rows, for exports with line
+    /// breaks inside unquoted fields. Rows which cannot be joined to the number of fields of the
+    /// header are handled by --on-ragged.
+    #[arg(long, default_value_t = false)]
+    lenient_newlines: bool,
+
+    /// What to do with a column whose name repeats an earlier column name: error, rename (append a
+    /// suffix such as _1) or first (keep the first column and drop the later ones).
+    #[arg(long, default_value = "rename")]
+    on_duplicate_header: OnDuplicateHeader,
+
+    /// How empty fields of string columns are written: null or string (an empty string). Empty
+    /// fields of other columns are always null.
+    #[arg(long, default_value = "null")]
+    empty_as: EmptyAs,
+
+    /// What to do with a file without data rows, such as an empty file or one with only a header:
+    /// skip (write no Parquet file and count it as skipped), error (fail the file) or write-empty
+    /// (write a Parquet file with the columns of the header and no rows).
+    #[arg(long, default_value = "write-empty")]
+    on_empty: OnEmpty,
+
+    /// Type of integer columns: fixed64 (always int64) or minimal (the narrowest type covering the
+    /// sampled values, such as uint8 for values from 0 to 200). Defaults to fixed64, or minimal with
+    /// --promote-on-overflow.
+    #[arg(long)]
+    int_sizing: Option<IntSizing>,
+
+    /// Infer the narrowest integer types as --int-sizing minimal does, and convert a file again with
+    /// a column as int64 when one of its values does not fit the inferred type, instead of failing.
+    #[arg(long, default_value_t = false)]
+    promote_on_overflow: bool,
+
+    /// Infer a column as strings if a sampled number has a leading zero, such as "007", so codes and
+    /// identifiers keep their zeros. --column-types still override the type.
+    #[arg(long, default_value_t = false)]
+    strict_leading_zeros: bool,
+
+    /// Comma-separated tokens written as null, such as "NA,NULL,N/A". Empty fields are null unless --empty-as is string.
+    #[arg(long)]
+    null_values: Option<String>,
+
+    /// Format of the numbers, given as the number 1234.56 written in it, such as "1,234.56" for a
+    /// thousands separator or "1.234,56" and "1234,56" for a decimal comma.
+    #[arg(long, value_parser = parse_number_format)]
+    number_format: Option<NumberFormat>,
+
+    /// Preset of the delimiter and number format: eu for ";" as the delimiter and numbers such as
+    /// "1.234,56". --delimiter, --tsv, --psv and --number-format override the preset.
+    #[arg(long)]
+    locale: Option<Locale>,
+
+    /// Directory for the Parquet output files, or "-" for standard output. Required when reading from standard input.
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+
+    /// Parquet file of a single input, such as "out/data.parquet". A path ending with "/" or naming an
+    /// existing directory is an output directory instead, the same as --output-dir.
+    #[arg(short, long, conflicts_with_all = ["output_dir", "stdout", "merge", "output_name_template"])]
+    output: Option<PathBuf>,
+
+    /// Write the Parquet data to standard output. Only a single input is supported.
+    #[arg(long, default_value_t = false)]
+    stdout: bool,
+
+    /// Write the SHA-256 hash of every Parquet file written to this checksum file, in the format of
+    /// sha256sum, for verifying the files after transferring them.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["stdout", "dry_run", "list"])]
+    checksums: Option<PathBuf>,
+
+    /// Write an empty "_SUCCESS" file to the output directory once every file converted without an
+    /// error, as Hadoop and Spark jobs do. A marker of an earlier run is removed when the run starts.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["dry_run", "list"])]
+    success_marker: bool,
+
+    /// Comma-separated column types overriding the inferred schema, such as "zip=utf8,amount=float64".
+    /// Supported types: utf8, int8-int64, uint8-uint64, float32, float64, bool, date, timestamp,
+    /// decimal(precision,scale).
+    #[arg(long, value_parser = parse_column_types)]
+    column_types: Option<HashMap<String, DataType>>,
+
+    /// Comma-separated columns written as decimals with --decimal-precision and --decimal-scale.
+    #[arg(long, value_delimiter = ',')]
+    decimal_columns: Vec<String>,
+
+    /// Total number of digits of the --decimal-columns, from 1 to 38.
+    #[arg(long, default_value_t = DEFAULT_DECIMAL_PRECISION, value_parser = clap::value_parser!(u8).range(1..=38), requires = "decimal_columns")]
+    decimal_precision: u8,
+
+    /// Number of fractional digits of the --decimal-columns, at most the precision.
+    #[arg(long, default_value_t = DEFAULT_DECIMAL_SCALE, value_parser = clap::value_parser!(i8).range(0..=38), requires = "decimal_columns")]
+    decimal_scale: i8,
+
+    /// Template for the Parquet file names, with the placeholders {stem}, {ext} and {parent}, such as "{stem}_converted".
+    /// The --output-extension is added if missing.
+    #[arg(long)]
+    output_name_template: Option<String>,
+
+    /// Extension of the Parquet files, such as "pq" or ".pq". It is not applied to an --output file
+    /// or the part files of --append.
+    #[arg(long, default_value = DEFAULT_OUTPUT_EXTENSION, value_parser = parse_output_extension)]
+    output_extension: String,
+
+    /// Write a JSON document with the name, type and nullability of every column next to every Parquet file,
+    /// such as data.parquet.schema.json for data.parquet.
+    #[arg(long, default_value_t = false, conflicts_with = "stdout")]
+    emit_schema: bool,
+
+    /// Do not record the source file, conversion time, cc2p version, delimiter and row count in the
+    /// key-value metadata of the Parquet files, so converting the same data twice writes identical files.
+    #[arg(long, default_value_t = false)]
+    no_metadata: bool,
+
+    /// Write byte-identical Parquet files when the same data is converted twice: the conversion time
+    /// is left out of the metadata and row groups are encoded on a single thread, ignoring
+    /// --intra-file-parallelism.
+    #[arg(long, default_value_t = false)]
+    reproducible: bool,
+
+    /// What to do when a Parquet output file already exists: overwrite, skip or error.
+    #[arg(long, default_value = "overwrite")]
+    if_exists: IfExists,
+
+    /// Write a JSON report with the status, error and timestamps of every file to this path.
+    #[arg(long)]
+    error_report: Option<PathBuf>,
+
+    /// Stop converting the remaining files after the first error.
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    /// Stop converting the remaining files once this many files failed, a more tolerant
+    /// --fail-fast. The files in progress are finished and the report covers every file.
+    #[arg(long, conflicts_with = "fail_fast", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_errors: Option<usize>,
+
+    /// Column whose values split the rows into Hive-style directories, such as out/region=US/data.parquet.
+    #[arg(long, conflicts_with_all = ["stdout", "merge"])]
+    partition_by: Option<String>,
+
+    /// Keep the partition column in the partitioned files, instead of dropping it as its value is part of the path.
+    #[arg(long, default_value_t = false, requires = "partition_by")]
+    keep_partition_column: bool,
+
+    /// Maximum number of partitions of a single file. A file with more distinct values fails.
+    #[arg(long, default_value_t = DEFAULT_MAX_PARTITIONS, requires = "partition_by")]
+    max_partitions: usize,
+
+    /// Merge all CSV files, which must share the same columns, into this single Parquet file.
+    /// Files with a different schema are reported and excluded.
+    #[arg(long, conflicts_with_all = ["stdout", "output_dir"])]
+    merge: Option<PathBuf>,
+
+    /// Fail the merge if a file has a different schema, instead of excluding it.
+    #[arg(long, default_value_t = false, requires = "merge")]
+    abort_on_mismatch: bool,
+
+    /// Write every CSV file as exactly one row group of the merged file, whatever its number of rows,
+    /// and list the source file of every row group in the "cc2p:row_group_files" key-value metadata.
+    #[arg(long, default_value_t = false, requires = "merge")]
+    row_group_per_file: bool,
+
+    /// Append every CSV file as a new part file, such as "part-0001.parquet", to this Parquet dataset
+    /// directory, keeping its existing parts. A file whose schema does not fit the dataset fails.
+    #[arg(
+        long,
+        value_name = "DATASET_DIR",
+        conflicts_with_all = ["output_dir", "output", "stdout", "merge", "partition_by", "output_name_template", "dry_run"]
+    )]
+    append: Option<PathBuf>,
+
+    /// Only infer and print the schema and estimated row count of every file, without writing Parquet files.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Print the first N rows of every Parquet file written as a table, read back from the file.
+    /// With --dry-run, the rows are converted in memory instead.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with_all = ["stdout", "list"]
+    )]
+    preview: Option<usize>,
+
+    /// Only print the absolute path of every matched file, one per line, without converting them.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["dry_run", "format"])]
+    list: bool,
+
+    /// When to draw the progress bar in color: auto (if standard error is a terminal and NO_COLOR is
+    /// not set), always or never. Without colors, a plain ASCII bar is drawn.
+    #[arg(long, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Print the conversion time and the throughput in rows and megabytes of output per second of every file.
+    #[arg(long, default_value_t = false)]
+    stats: bool,
+
+    /// Format of the summary: text, or json for a single JSON object with the totals and the outcome
+    /// of every file printed to standard output instead of the progress bar and the text summary.
+    #[arg(long, default_value = "text", conflicts_with_all = ["stdout", "merge", "dry_run"])]
+    format: OutputFormat,
+
+    /// Only print errors, without the program arguments, progress bar and summary.
+    #[arg(short, long, default_value_t = false)]
+    quiet: bool,
+
+    /// Hide the progress bars while keeping the program arguments and summary, for logs of runs
+    /// without a terminal.
+    #[arg(long, default_value_t = false)]
+    no_progress: bool,
+
+    /// Print the schema of every file, with the type and nullability of each column, to standard
+    /// error before it is converted.
+    #[arg(short, long, default_value_t = false, conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Log messages up to this level to standard error: off, error (failed files), warn (warnings
+    /// and retries), info (every file started and finished), debug (arguments and target files) or
+    /// trace. Defaults to the filters of the RUST_LOG environment variable, otherwise off.
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<log::LevelFilter>,
+
+    /// Only convert CSV files modified after this RFC 3339 time, such as "2024-01-15T00:00:00Z".
+    #[arg(long, value_parser = parse_modified_after)]
+    modified_after: Option<SystemTime>,
+
+    /// Search subdirectories for CSV files. With --output-dir, the directory structure is preserved.
+    #[arg(short, long, default_value_t = false)]
+    recursive: bool,
+
+    /// Maximum number of directory levels to descend with --recursive. 1 only searches the base directory.
+    #[arg(long, requires = "recursive")]
+    max_depth: Option<usize>,
+
+    /// File name pattern matched in every directory path, such as "*.tsv" or "*.{csv,txt}". Repeat it
+    /// or separate patterns with commas to match several. Defaults to "*.csv".
+    #[arg(long = "pattern", value_name = "PATTERN")]
+    patterns: Vec<String>,
+
+    /// Match the paths and patterns case-sensitively, so "*.csv" no longer matches DATA.CSV.
+    #[arg(long, default_value_t = false)]
+    case_sensitive: bool,
+
+    /// Convert every file of a directory path whatever its extension, instead of the files matching
+    /// --pattern. Parquet files are never converted.
+    #[arg(long, default_value_t = false, conflicts_with = "patterns")]
+    force: bool,
 }
 
+/// Exit code used when at least one file failed to convert.
+const EXIT_CONVERSION_FAILED: i32 = 2;
+
+/// Exit code used when the conversion was interrupted with Ctrl-C, as for a shell killed by SIGINT.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// The name of the marker file written by --success-marker.
+const SUCCESS_MARKER: &str = "_SUCCESS";
+
+/// The time waited before the first retry of a file, doubled before every further retry.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
 /// A structure to hold error information related to CSV file processing.
 ///
 /// This struct is designed to capture and store error details that occur during
@@ -76,57 +702,1467 @@ struct ErrorData {
     error: String,
 }
 
+/// The logger of the run, which prints the records of the `log` crate above the progress bar.
+///
+/// The progress bar is cleared while a line is written and drawn again below it, so log lines
+/// and the bar do not interleave.
+struct ProgressLogger {
+    inner: env_logger::Logger,
+    progress: OnceLock<MultiProgress>,
+}
+
+impl log::Log for ProgressLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        match self.progress.get() {
+            Some(progress) => progress.suspend(|| self.inner.log(record)),
+            None => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// The logger of the run, set once the arguments are parsed.
+static LOGGER: OnceLock<ProgressLogger> = OnceLock::new();
+
+/// Installs the logger of the run, logging up to `level` or else as set by `RUST_LOG`, and
+/// nothing if neither is set.
+///
+/// Every line starts with the time and the level.
+fn init_logger(level: Option<log::LevelFilter>) {
+    let mut builder = env_logger::Builder::new();
+    builder
+        .filter_level(log::LevelFilter::Off)
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "{} {:<5} {}",
+                Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                record.level(),
+                record.args()
+            )
+        });
+    match level {
+        Some(level) => {
+            builder.filter_level(level);
+        }
+        None => {
+            if let Ok(filters) = std::env::var("RUST_LOG") {
+                builder.parse_filters(&filters);
+            }
+        }
+    }
+
+    let inner = builder.build();
+    let max_level = inner.filter();
+    let logger = LOGGER.get_or_init(|| ProgressLogger {
+        inner,
+        progress: OnceLock::new(),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// When the progress bar is drawn in color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorChoice {
+    /// Use colors if standard error is a terminal and `NO_COLOR` is not set.
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors, drawing a plain ASCII bar.
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "unknown color choice '{}', expected one of: auto, always, never",
+                value
+            )),
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Returns whether colors are used, see <https://no-color.org> for `NO_COLOR`.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                !no_color && std::io::stderr().is_terminal()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The outcome of converting a single file, as written to the error report.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum FileStatus {
+    /// The file was converted successfully.
+    Ok,
+    /// The conversion failed.
+    Failed,
+    /// The conversion was skipped.
+    Skipped,
+}
+
+/// An entry of the JSON error report describing the conversion of a single file.
+///
+/// # Fields
+///
+/// * `file_path` - The path of the CSV file.
+/// * `status` - The outcome of the conversion.
+/// * `error` - A description of the error, if the conversion failed.
+/// * `started_at` - When the conversion started, in RFC 3339 format.
+/// * `finished_at` - When the conversion finished, in RFC 3339 format.
+#[derive(Serialize)]
+struct ReportEntry {
+    /// The path of the CSV file.
+    file_path: String,
+
+    /// The outcome of the conversion.
+    status: FileStatus,
+
+    /// A description of the error, if the conversion failed.
+    error: Option<String>,
+
+    /// When the conversion started, in RFC 3339 format.
+    started_at: String,
+
+    /// When the conversion finished, in RFC 3339 format.
+    finished_at: String,
+}
+
+/// How the summary of a run is printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Print the arguments, the progress bar and a line per file.
+    Text,
+    /// Print a single JSON object to standard output once all files are done.
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "unknown output format '{}', expected one of: text, json",
+                value
+            )),
+        }
+    }
+}
+
+/// The summary of a whole run, printed with `--format json`.
+#[derive(Serialize)]
+struct RunSummary {
+    /// The number of files which were converted, skipped or failed.
+    files: usize,
+
+    /// The number of files converted successfully.
+    succeeded: usize,
+
+    /// The number of files skipped because their Parquet file already exists or they have no data rows.
+    skipped: usize,
+
+    /// The number of files which failed to convert.
+    failed: usize,
+
+    /// Whether the run was interrupted with Ctrl-C.
+    interrupted: bool,
+
+    /// The number of files which were not started because the run was interrupted or too many files failed.
+    not_started: usize,
+
+    /// The number of rows written to all Parquet files.
+    rows: u64,
+
+    /// The number of bytes written to all Parquet files.
+    bytes: u64,
+
+    /// The duration of the whole run in milliseconds.
+    elapsed_ms: u64,
+
+    /// The outcome of every file, sorted by file path.
+    results: Vec<FileSummary>,
+}
+
+/// The outcome of a single file in the [`RunSummary`].
+#[derive(Serialize)]
+struct FileSummary {
+    /// The path of the CSV file.
+    file_path: String,
+
+    /// The outcome of the conversion.
+    status: FileStatus,
+
+    /// The path of the Parquet file, `None` if the conversion failed or wrote to standard output.
+    output_path: Option<String>,
+
+    /// The number of rows written.
+    rows: u64,
+
+    /// The number of bytes written.
+    bytes: u64,
+
+    /// A description of the error, if the conversion failed.
+    error: Option<String>,
+
+    /// Problems which did not stop the conversion.
+    warnings: Vec<String>,
+}
+
+/// Parses an RFC 3339 time, such as `2024-01-15T00:00:00Z`.
+fn parse_modified_after(value: &str) -> Result<SystemTime, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(SystemTime::from)
+        .map_err(|e| format!("invalid RFC 3339 time '{}': {}", value, e))
+}
+
+/// Formats the rows and the megabytes (10^6 bytes) of Parquet output written per second.
+fn throughput(rows: u64, bytes: u64, elapsed: Duration) -> String {
+    // a conversion of a few rows may finish within the resolution of the timer
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    format!(
+        "{:.0} rows/s, {:.2} MB/s",
+        rows as f64 / seconds,
+        bytes as f64 / 1_000_000.0 / seconds
+    )
+}
+
+/// Parses an `on` or `off` setting.
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("invalid setting '{}', expected on or off", value)),
+    }
+}
+
+/// Writes the JSON report of every file, sorted by file path.
+fn write_error_report(
+    path: &Path,
+    report: &mut [ReportEntry],
+) -> Result<(), Box<dyn std::error::Error>> {
+    report.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    serde_json::to_writer_pretty(File::create(path)?, report)?;
+
+    Ok(())
+}
+
+/// Infers and prints the schema of every file without writing any Parquet data.
+///
+/// Exits with [`EXIT_CONVERSION_FAILED`] if the sample of a file cannot be read.
+fn dry_run(
+    jobs: &[(PathBuf, PathBuf)],
+    options: &ConvertOptions,
+    manifest: &Manifest,
+    read_stdin: bool,
+    preview: Option<usize>,
+    error_report: Option<&Path>,
+    mut out: Box<dyn Write>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = vec![];
+    let mut failed = false;
+
+    for (file, _) in jobs {
+        let started_at = Utc::now().to_rfc3339();
+        let options = manifest.options_for(file, options);
+        let result = if read_stdin {
+            inspect_reader(std::io::stdin().lock(), &options)
+        } else {
+            inspect_csv(file, &options)
+        };
+
+        let error = match result {
+            Ok(inspection) => {
+                log::info!("inspected {}", file.display());
+                // the count is exact when the whole file fits in the sample
+                let rows = match inspection.estimated_rows {
+                    Some(rows) if rows == inspection.sampled_rows as u64 => rows.to_string(),
+                    Some(rows) => format!("~{}", rows),
+                    None => String::from("unknown"),
+                };
+                writeln!(
+                    out,
+                    "File: {}  Columns: {}  Rows: {}  Sampled: {}",
+                    file.display(),
+                    inspection.schema.fields().len(),
+                    rows,
+                    inspection.sampled_rows
+                )?;
+                let width = inspection
+                    .schema
+                    .fields()
+                    .iter()
+                    .map(|field| field.name().len())
+                    .max()
+                    .unwrap_or_default();
+                if let Some(delimiter) = inspection.detected_delimiter {
+                    writeln!(out, "  Detected delimiter: {:?}", delimiter)?;
+                }
+                for field in inspection.schema.fields() {
+                    writeln!(out, "  {:width$}  {}", field.name(), field.data_type())?;
+                }
+                for warning in &inspection.warnings {
+                    writeln!(out, "  Warning: {}", warning)?;
+                }
+                // standard input was consumed by the inspection
+                if let Some(rows) = preview.filter(|_| !read_stdin) {
+                    write_preview(&mut out, preview_csv(file, &options, rows))?;
+                }
+                None
+            }
+            Err(err) => {
+                failed = true;
+                log::error!("failed {}: {}", file.display(), err);
+                eprintln!("File: {}  Error: {:?}", file.display(), err.to_string());
+                Some(err.to_string())
+            }
+        };
+
+        report.push(ReportEntry {
+            file_path: file.to_str().unwrap().to_string(),
+            status: if error.is_some() {
+                FileStatus::Failed
+            } else {
+                FileStatus::Ok
+            },
+            error,
+            started_at,
+            finished_at: Utc::now().to_rfc3339(),
+        });
+    }
+
+    if let Some(error_report) = error_report {
+        write_error_report(error_report, &mut report)?;
+    }
+
+    if failed {
+        out.flush()?;
+        std::process::exit(EXIT_CONVERSION_FAILED);
+    }
+
+    Ok(())
+}
+
+/// Merges all files into a single Parquet file and prints the files which were excluded.
+///
+/// Returns the merged file, unless it was skipped. Exits with [`EXIT_CONVERSION_FAILED`] if a
+/// file was excluded or the merge failed.
+fn merge(
+    files: &[PathBuf],
+    output: &Path,
+    options: &ConvertOptions,
+    abort_on_mismatch: bool,
+    error_report: Option<&Path>,
+    out: &mut dyn Write,
+    err_out: &mut dyn Write,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let started_at = Utc::now().to_rfc3339();
+    create_parent_dir(output)?;
+    log::info!("merging {} files into {}", files.len(), output.display());
+    let result = merge_to_parquet(files, &options.clone().output(output), abort_on_mismatch);
+    let finished_at = Utc::now().to_rfc3339();
+
+    let mut report = vec![];
+    let failed = match &result {
+        Ok(merge) => {
+            let summary = &merge.summary;
+            if summary.skipped {
+                writeln!(
+                    out,
+                    "Skipped: {} already exists",
+                    summary.output_path.display()
+                )?;
+            }
+            for (file, rows) in &merge.merged {
+                writeln!(out, "File: {}  Rows: {}", file.display(), rows)?;
+            }
+            for warning in &summary.warnings {
+                writeln!(out, "Warning: {}", warning)?;
+            }
+            for (file, reason) in &merge.excluded {
+                log::warn!("excluded {}: {}", file.display(), reason);
+                writeln!(
+                    err_out,
+                    "File: {}  Excluded: {:?}\n",
+                    file.display(),
+                    reason
+                )?;
+            }
+            if !summary.skipped {
+                writeln!(
+                    out,
+                    "Total: {} files merged, {} excluded, {} rows, {} bytes  Output: {}",
+                    merge.merged.len(),
+                    merge.excluded.len(),
+                    summary.rows_written,
+                    summary.bytes_written,
+                    summary.output_path.display()
+                )?;
+            }
+
+            let status = if summary.skipped {
+                FileStatus::Skipped
+            } else {
+                FileStatus::Ok
+            };
+            for file in files {
+                let error = merge
+                    .excluded
+                    .iter()
+                    .find(|(excluded, _)| excluded == file)
+                    .map(|(_, reason)| reason.clone());
+                report.push(ReportEntry {
+                    file_path: file.to_str().unwrap().to_string(),
+                    status: if error.is_some() {
+                        FileStatus::Failed
+                    } else {
+                        status
+                    },
+                    error,
+                    started_at: started_at.clone(),
+                    finished_at: finished_at.clone(),
+                });
+            }
+            !merge.excluded.is_empty()
+        }
+        Err(err) => {
+            log::error!("failed merge {}: {}", output.display(), err);
+            writeln!(
+                err_out,
+                "Merge: {}  Error: {:?}\n",
+                output.display(),
+                err.to_string()
+            )?;
+            for file in files {
+                report.push(ReportEntry {
+                    file_path: file.to_str().unwrap().to_string(),
+                    status: FileStatus::Failed,
+                    error: Some(err.to_string()),
+                    started_at: started_at.clone(),
+                    finished_at: finished_at.clone(),
+                });
+            }
+            true
+        }
+    };
+
+    if let Some(error_report) = error_report {
+        write_error_report(error_report, &mut report)?;
+    }
+
+    if failed {
+        out.flush()?;
+        err_out.flush()?;
+        std::process::exit(EXIT_CONVERSION_FAILED);
+    }
+
+    Ok(result
+        .ok()
+        .filter(|merge| !merge.summary.skipped)
+        .map(|merge| merge.summary.output_path))
+}
+
+/// Converts a single file, or standard input, to its target file or standard output, or appends
+/// it as the target part file to a dataset with the given schema.
+fn convert_file(
+    file: &Path,
+    target_file: PathBuf,
+    options: ConvertOptions,
+    read_stdin: bool,
+    write_stdout: bool,
+    emit_schema: bool,
+    dataset_schema: Option<&SchemaRef>,
+) -> Result<ConversionSummary, Box<dyn std::error::Error>> {
+    if let Some(dataset_schema) = dataset_schema {
+        append_to_dataset(file, &target_file, dataset_schema, &options)
+    } else if read_stdin && write_stdout {
+        convert_reader_to_writer(std::io::stdin().lock(), std::io::stdout(), &options)
+    } else if read_stdin {
+        let summary = write_file_atomically(&target_file, |target| {
+            convert_reader_to_writer(std::io::stdin().lock(), target, &options)
+        })?;
+        if emit_schema {
+            write_schema_file(&target_file, &summary.schema)?;
+        }
+        Ok(ConversionSummary {
+            output_path: target_file,
+            ..summary
+        })
+    } else if write_stdout {
+        convert_to_writer(file, &options, std::io::stdout())
+    } else {
+        create_parent_dir(&target_file)?;
+        convert_to_parquet(file, &options.output(&target_file))
+    }
+}
+
+/// Prints a preview table indented below the file it belongs to, or why it failed.
+fn write_preview(
+    out: &mut dyn Write,
+    preview: Result<String, Box<dyn std::error::Error>>,
+) -> std::io::Result<()> {
+    match preview {
+        Ok(table) => {
+            for line in table.lines() {
+                writeln!(out, "  {}", line)?;
+            }
+            Ok(())
+        }
+        Err(err) => writeln!(out, "  Preview failed: {}", err),
+    }
+}
+
+/// Previews the Parquet file written to `output_path`, or the first partition file below it if
+/// the output is partitioned, see [`preview_parquet`].
+fn preview_output(
+    output_path: &Path,
+    extension: &str,
+    rows: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let file = if output_path.is_dir() {
+        WalkDir::new(output_path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .find(|entry| {
+                entry.file_type().is_file()
+                    && entry.path().extension() == Some(std::ffi::OsStr::new(extension))
+            })
+            .ok_or("no partition file was written")?
+            .into_path()
+    } else {
+        output_path.to_path_buf()
+    };
+
+    preview_parquet(File::open(file)?, rows)
+}
+
+/// Returns the Parquet files written by a conversion to the target file, which are the files of
+/// every partition below the output directory of partitioned output.
+fn output_files(summary: &ConversionSummary, target_file: &Path) -> Vec<PathBuf> {
+    if !summary.output_path.is_dir() {
+        return vec![summary.output_path.clone()];
+    }
+
+    let mut files: Vec<PathBuf> = WalkDir::new(&summary.output_path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| Some(entry.file_name()) == target_file.file_name())
+        .map(|entry| entry.into_path())
+        .collect();
+    files.sort();
+
+    files
+}
+
+/// Runs a conversion, running it again up to `retries` times after a transient I/O error.
+///
+/// The first retry waits [`RETRY_BACKOFF`], and every further retry waits twice as long as the
+/// previous one. A cancelled conversion is not retried.
+fn convert_with_retries(
+    retries: u32,
+    cancel_flag: &AtomicBool,
+    mut convert: impl FnMut() -> Result<ConversionSummary, Box<dyn std::error::Error>>,
+) -> Result<ConversionSummary, String> {
+    let mut attempt = 0;
+    loop {
+        match convert() {
+            Ok(mut summary) => {
+                if attempt > 0 {
+                    summary
+                        .warnings
+                        .push(format!("converted after {} retries", attempt));
+                }
+                return Ok(summary);
+            }
+            Err(err)
+                if attempt < retries
+                    && is_transient_error(err.as_ref())
+                    && !cancel_flag.load(Ordering::SeqCst) =>
+            {
+                let backoff = RETRY_BACKOFF.saturating_mul(1 << attempt.min(16));
+                log::warn!(
+                    "retrying in {} s after a transient error: {}",
+                    backoff.as_secs(),
+                    err
+                );
+                std::thread::sleep(backoff);
+                attempt += 1;
+            }
+            Err(err) if attempt > 0 => {
+                return Err(format!("{} (failed after {} retries)", err, attempt))
+            }
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+/// Returns whether the error is caused by an I/O error which may not occur again, such as a
+/// network mount which did not respond.
+///
+/// Invalid data, such as bytes which are invalid in the encoding or a truncated gzip stream, and
+/// missing or unreadable files fail the same way every time.
+fn is_transient_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<std::io::Error>() {
+            return !matches!(
+                error.kind(),
+                ErrorKind::NotFound
+                    | ErrorKind::PermissionDenied
+                    | ErrorKind::InvalidInput
+                    | ErrorKind::InvalidData
+                    | ErrorKind::UnexpectedEof
+            );
+        }
+        source = error.source();
+    }
+
+    false
+}
+
+/// Formats the schema of a file for --verbose, a line for every column with its type and nullability.
+fn schema_lines(file_name: &str, schema: &Schema) -> String {
+    let width = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().len())
+        .max()
+        .unwrap_or_default();
+    let types: Vec<String> = schema
+        .fields()
+        .iter()
+        .map(|field| field.data_type().to_string())
+        .collect();
+    let type_width = types.iter().map(String::len).max().unwrap_or_default();
+
+    let mut lines = format!("Schema of {}:\n", file_name);
+    for (field, data_type) in schema.fields().iter().zip(&types) {
+        lines.push_str(&format!(
+            "  {:width$}  {:type_width$}  {}\n",
+            field.name(),
+            data_type,
+            if field.is_nullable() {
+                "nullable"
+            } else {
+                "not null"
+            }
+        ));
+    }
+
+    lines
+}
+
+/// Deletes the temporary Parquet file of a conversion which did not finish.
+///
+/// An existing Parquet file is left unchanged, it is only replaced once a conversion is complete.
+fn remove_partial_output(target_file: &Path) {
+    let _ = fs::remove_file(temporary_file_path(target_file));
+}
+
+/// Creates the parent directory of the given file if it does not exist yet.
+fn create_parent_dir(file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether the file has a `.parquet` extension or the extension of the output files.
+fn is_parquet_file(file: &Path, output_extension: &str) -> bool {
+    let name = file.to_string_lossy().to_lowercase();
+    name.ends_with(".parquet") || name.ends_with(&format!(".{}", output_extension.to_lowercase()))
+}
+
+/// Returns whether the --output path names a directory: it ends with a path separator or is an
+/// existing directory.
+fn is_directory_path(path: &Path) -> bool {
+    path.as_os_str()
+        .to_string_lossy()
+        .ends_with(std::path::is_separator)
+        || path.is_dir()
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    init_logger(args.log_level);
+    log::debug!("arguments: {:?}", args);
     let start = Instant::now();
-    let path = args.path.as_str();
+    let path = args.path.join(", ");
     let sampling_size = args.sampling;
     let has_header = !args.no_header;
-    let delimiter = args.delimiter.chars().next().unwrap_or(',');
-    
-    println!(
-        "Program arguments\n path: {}\n delimiter: {:?}\n has header: {} \n worker count: {} \n sampling size {}",
+    let delimiter = if args.tsv {
+        Some(Delimiter::Char('\t'))
+    } else if args.psv {
+        Some(Delimiter::Char('|'))
+    } else {
+        args.delimiter
+            .clone()
+            .or_else(|| args.locale.map(Locale::delimiter))
+    };
+    let compression = args.compression;
+    let null_values = parse_null_values(args.null_values.as_deref().unwrap_or_default());
+
+    let (output_dir, output_file) = match args.output.clone() {
+        Some(output) if output == Path::new(STDIN_PATH) || is_directory_path(&output) => {
+            (Some(output), None)
+        }
+        Some(output) => (None, Some(output)),
+        None => (args.output_dir.clone(), None),
+    };
+    let write_stdout = args.stdout || output_dir.as_deref() == Some(Path::new(STDIN_PATH));
+    let output_dir = output_dir.filter(|_| !write_stdout);
+
+    let read_stdin = args.path.iter().any(|p| p == STDIN_PATH);
+    // the characters are checked before any file is opened, a .tsv file checks its tab when read
+    validate_special_characters(
+        delimiter.as_ref().unwrap_or(&Delimiter::Char(',')),
+        (!args.no_quoting).then_some(args.quote),
+        args.comment,
+    )?;
+    if read_stdin && args.path.len() > 1 {
+        return Err("'-' (standard input) cannot be combined with other paths".into());
+    }
+    if read_stdin && args.append.is_some() {
+        return Err(
+            "standard input cannot be appended to a dataset, pass the CSV files instead".into(),
+        );
+    }
+    if read_stdin && args.partition_by.is_some() {
+        return Err("partitioned output cannot be written from standard input".into());
+    }
+    if args.promote_on_overflow && args.int_sizing == Some(IntSizing::Fixed64) {
+        return Err("--promote-on-overflow requires --int-sizing minimal".into());
+    }
+    if args.success_marker && output_dir.is_none() {
+        return Err("--success-marker requires an output directory".into());
+    }
+    if read_stdin && output_dir.is_none() && output_file.is_none() && !write_stdout {
+        return Err(
+            "reading from standard input requires --output, --output-dir or --stdout".into(),
+        );
+    }
+
+    let json = args.format == OutputFormat::Json;
+    if json && write_stdout {
+        return Err(
+            "the json format cannot be combined with writing Parquet data to standard output"
+                .into(),
+        );
+    }
+
+    // standard output carries the Parquet data, so messages go to standard error instead
+    let mut out: Box<dyn Write> = if args.quiet || json || args.list {
+        Box::new(std::io::sink())
+    } else if write_stdout {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+    // errors are still reported in quiet mode
+    let mut err_out: Box<dyn Write> = if args.quiet || write_stdout || json {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+
+    writeln!(
+        out,
+        "Program arguments\n path: {}\n delimiter: {:?}\n has header: {} \n worker count: {} \n max concurrent files: {} \n sampling size {} \n compression: {:?} \n null values: {:?}",
         path,
-        delimiter,
+        delimiter
+            .as_ref()
+            .map_or_else(|| String::from(", (tab for .tsv files)"), |d| d.to_string()),
         has_header,
         args.worker,
-        sampling_size
-    );
+        args.max_concurrent_files.unwrap_or(args.worker),
+        sampling_size,
+        compression,
+        null_values
+    )?;
     let errors = Arc::new(Mutex::new(Vec::<ErrorData>::new()));
+    let summaries = Arc::new(Mutex::new(
+        Vec::<(String, ConversionSummary, Duration)>::new(),
+    ));
+    let report = Arc::new(Mutex::new(Vec::<ReportEntry>::new()));
+    let failures = Arc::new(AtomicUsize::new(0));
+    let max_errors = if args.fail_fast {
+        Some(1)
+    } else {
+        args.max_errors
+    };
+    let verbose = args.verbose;
+    let emit_schema = args.emit_schema;
+
+    let mut options = ConvertOptions::new()
+        .has_header(has_header)
+        .column_prefix(args.column_prefix)
+        .header_names(args.header_names)
+        .emit_schema(args.emit_schema)
+        .output_extension(args.output_extension.clone())
+        .metadata(!args.no_metadata)
+        .reproducible(args.reproducible)
+        .on_ragged(args.on_ragged)
+        .on_invalid_utf8(args.on_invalid_utf8)
+        .lenient_newlines(args.lenient_newlines)
+        .on_duplicate_header(args.on_duplicate_header)
+        .empty_as(args.empty_as)
+        .on_empty(args.on_empty)
+        .int_sizing(args.int_sizing.unwrap_or(if args.promote_on_overflow {
+            IntSizing::Minimal
+        } else {
+            IntSizing::Fixed64
+        }))
+        .promote_on_overflow(args.promote_on_overflow)
+        .strict_leading_zeros(args.strict_leading_zeros)
+        .quote(args.quote)
+        .quoting(!args.no_quoting)
+        .comment(args.comment)
+        .skip_rows(args.skip_rows)
+        .header_row(args.header_row)
+        .skip_footer(args.skip_footer)
+        .limit(args.limit)
+        .row_group_per_file(args.row_group_per_file)
+        .input_compression(args.input_compression)
+        .date_format(args.parse_dates.then_some(args.date_format))
+        .parse_bools(args.parse_bools)
+        .bool_values(args.true_values, args.false_values)
+        .trim(args.trim)
+        .normalize_headers(args.normalize_headers)
+        .columns(args.columns)
+        .exclude_columns(args.exclude_columns)
+        .renames(args.rename.unwrap_or_default())
+        .partition_by(args.partition_by)
+        .keep_partition_column(args.keep_partition_column)
+        .max_partitions(args.max_partitions)
+        .sampling(sampling_size)
+        .sample_strategy(args.sample_strategy)
+        .all_strings(args.all_strings)
+        .null_column_type(args.null_column_type)
+        .null_values(null_values)
+        .number_format(
+            args.number_format
+                .or_else(|| args.locale.map(Locale::number_format)),
+        )
+        .compression(compression)
+        .row_group_size(args.row_group_size as usize)
+        .flush_every(args.flush_every)
+        .batch_size(args.batch_size as usize)
+        .read_buffer_size(args.read_buffer_size as usize)
+        .page_size(args.page_size as usize)
+        .parquet_version(args.parquet_version)
+        .statistics(args.statistics)
+        .dictionary(args.dictionary)
+        .dictionary_columns(args.dictionary_columns.unwrap_or_default())
+        .parallelism(args.intra_file_parallelism as usize)
+        .if_exists(args.if_exists)
+        .column_types(args.column_types.unwrap_or_default())
+        .decimal_columns(
+            args.decimal_columns,
+            args.decimal_precision,
+            args.decimal_scale,
+        );
+    if let Some(delimiter) = delimiter {
+        options = options.delimiter(delimiter);
+    }
+    if let Some(columns_file) = &args.columns_from {
+        options = options.columns(read_column_list(columns_file)?);
+    }
+    if let Some(schema_file) = &args.schema {
+        options = options.schema(Some(Arc::new(read_schema_file(schema_file)?)));
+    }
+    if let Some(encoding) = args.encoding {
+        options = options.encoding(encoding);
+    }
+    // files without an entry in the manifest keep the options above
+    let manifest = match &args.manifest {
+        Some(manifest_file) => read_manifest(manifest_file)?,
+        None => Manifest::default(),
+    };
+
+    if let Some(output_dir) = output_dir.as_ref().filter(|_| !args.list) {
+        fs::create_dir_all(output_dir)?;
+        // the marker of an earlier run must not mark this run as complete before it is
+        if args.success_marker {
+            match fs::remove_file(output_dir.join(SUCCESS_MARKER)) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+    }
+
+    // each file is paired with the directory it was searched from when walking recursively,
+    // so the relative directory structure can be preserved under the output directory
+    // a directory path and a ZIP archive are searched with the file name patterns
+    let ignore_case = !args.case_sensitive;
+    // other files than CSV files are only searched for when asked to
+    let any_extension = args.force || !args.patterns.is_empty();
+    let patterns = if args.force {
+        vec![String::from("*")]
+    } else if args.patterns.is_empty() {
+        vec![String::from("*.csv")]
+    } else {
+        args.patterns.clone()
+    };
+    let mut files: Vec<(PathBuf, Option<PathBuf>)> = if read_stdin {
+        vec![(PathBuf::from(STDIN_PATH), None)]
+    } else {
+        // the path of an existing file is converted as it is, even if it is no valid pattern
+        let (explicit_files, search_paths): (Vec<&String>, Vec<&String>) =
+            args.path.iter().partition(|p| Path::new(p).is_file());
+        let patterns = patterns
+            .iter()
+            .map(|pattern| expand_patterns(pattern))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        let mut search_patterns = vec![];
+        for p in search_paths {
+            if Path::new(p).is_dir() {
+                // the directory is matched literally, even if its name holds wildcards
+                let directory = escape_pattern(p);
+                search_patterns.extend(
+                    patterns
+                        .iter()
+                        .map(|pattern| Path::new(&directory).join(pattern).display().to_string()),
+                );
+            } else {
+                search_patterns.extend(expand_patterns(p)?);
+            }
+        }
+
+        let mut found: Vec<(PathBuf, Option<PathBuf>)> = if args.recursive {
+            let mut found = vec![];
+            for p in &search_patterns {
+                let root = search_root(p);
+                let files =
+                    find_files_recursive_with(p, args.max_depth, ignore_case, any_extension)?;
+                found.extend(files.into_iter().map(|file| (file, Some(root.clone()))));
+            }
+            found
+        } else if search_patterns.is_empty() {
+            vec![]
+        } else {
+            find_files_with(&search_patterns, ignore_case, any_extension)?
+                .into_iter()
+                .map(|file| (file, None))
+                .collect()
+        };
+        // the output of an earlier run is no CSV data
+        found.retain(|(file, _)| !is_parquet_file(file, &args.output_extension));
+
+        let mut seen = HashSet::new();
+        explicit_files
+            .into_iter()
+            .map(|file| (PathBuf::from(file), None))
+            .chain(found)
+            .filter(|(file, _)| seen.insert(file.clone()))
+            .collect()
+    };
+    if let Some(modified_after) = args.modified_after.filter(|_| !read_stdin) {
+        let paths = files.iter().map(|(file, _)| file.clone()).collect();
+        let (kept, skipped) = filter_modified_after(paths, modified_after);
+        for (file, e) in skipped {
+            eprintln!("skipping {}: {}", file.display(), e);
+        }
+        let kept: HashSet<PathBuf> = kept.into_iter().collect();
+        files.retain(|(file, _)| kept.contains(file));
+    }
+    // the matching entries of a ZIP archive are converted instead of the archive, paired with the
+    // archive so their directories are preserved under the output directory
+    if !read_stdin {
+        let mut expanded = Vec::with_capacity(files.len());
+        for (file, base_dir) in files {
+            if is_zip_file(&file) && file.is_file() {
+                let entries = find_zip_entries(&file, &patterns, ignore_case)?;
+                expanded.extend(entries.into_iter().map(|entry| (entry, Some(file.clone()))));
+            } else {
+                expanded.push((file, base_dir));
+            }
+        }
+        files = expanded;
+    }
+
+    if args.list {
+        let mut stdout = std::io::stdout().lock();
+        for (file, base_dir) in &files {
+            let path = match base_dir {
+                _ if read_stdin => file.clone(),
+                Some(archive) if is_zip_file(archive) && archive.is_file() => {
+                    fs::canonicalize(archive)?.join(file.strip_prefix(archive)?)
+                }
+                _ => fs::canonicalize(file)?,
+            };
+            writeln!(stdout, "{}", path.display())?;
+        }
+        return Ok(());
+    }
+
+    if let Some(output) = &args.merge {
+        if read_stdin {
+            return Err("standard input cannot be merged, pass the CSV files instead".into());
+        }
+        let files: Vec<PathBuf> = files.into_iter().map(|(file, _)| file).collect();
+        if args.dry_run {
+            let jobs: Vec<_> = files
+                .into_iter()
+                .map(|file| (file, output.clone()))
+                .collect();
+            return dry_run(
+                &jobs,
+                &options,
+                &Manifest::default(),
+                false,
+                args.preview,
+                args.error_report.as_deref(),
+                out,
+            );
+        }
+        let merged = merge(
+            &files,
+            output,
+            &options,
+            args.abort_on_mismatch,
+            args.error_report.as_deref(),
+            &mut out,
+            &mut err_out,
+        )?;
+        if let Some((rows, merged)) = args.preview.zip(merged.as_ref()) {
+            writeln!(out, "Preview:")?;
+            write_preview(
+                &mut out,
+                File::open(merged)
+                    .map_err(Into::into)
+                    .and_then(|file| preview_parquet(file, rows)),
+            )?;
+        }
+        if let Some((checksums_file, merged)) = args.checksums.zip(merged) {
+            write_checksums(&checksums_file, &[merged])?;
+            writeln!(
+                out,
+                "Checksums: 1 files written to {}",
+                checksums_file.display()
+            )?;
+        }
+        return Ok(());
+    }
 
-    let files = find_files(path);
+    if output_file.is_some() && files.len() != 1 {
+        return Err(format!(
+            "an output file only supports a single input, but {} files matched. End the path with '/' to write to a directory",
+            files.len()
+        )
+        .into());
+    }
+    if write_stdout && files.len() != 1 {
+        return Err(format!(
+            "standard output mode only supports a single input, but {} files matched",
+            files.len()
+        )
+        .into());
+    }
 
-    let bar = ProgressBar::new(files.len().try_into().unwrap());
+    // the Parquet file of every input is resolved up front, so two inputs writing the same file
+    // are reported before anything is converted
+    let mut jobs: Vec<(PathBuf, PathBuf)> = Vec::with_capacity(files.len());
+    let mut targets: HashMap<PathBuf, PathBuf> = HashMap::new();
+    for (file, base_dir) in files {
+        // the part file is named once the conversion starts, see below
+        if let Some(dataset_dir) = &args.append {
+            jobs.push((file, dataset_dir.clone()));
+            continue;
+        }
+        if let Some(output_file) = &output_file {
+            jobs.push((file, output_file.clone()));
+            continue;
+        }
+        if read_stdin || write_stdout {
+            let target_file = output_dir
+                .clone()
+                .unwrap_or_default()
+                .join(Path::new(STDIN_OUTPUT_NAME).with_extension(&args.output_extension));
+            jobs.push((file, target_file));
+            continue;
+        }
 
-    bar.set_style(
-        ProgressStyle::with_template(
-            "[{elapsed_precise}] {bar:40.yellow/blue} {pos:>7}/{len:7} {msg}",
+        // without an output directory, the entries of an archive are written to a directory
+        // named after it, such as data/ for data.zip
+        let archive_dir = base_dir
+            .as_deref()
+            .filter(|base_dir| is_zip_file(base_dir) && base_dir.is_file())
+            .map(|archive| archive.with_extension(""));
+        let mut target_file = target_file_path(
+            &file,
+            base_dir.as_deref(),
+            output_dir.as_deref().or(archive_dir.as_deref()),
+            &args.output_extension,
+        );
+        if let Some(template) = &args.output_name_template {
+            target_file.set_file_name(output_file_name(template, &file, &args.output_extension)?);
+        }
+        if let Some(other) = targets.insert(target_file.clone(), file.clone()) {
+            return Err(format!(
+                "{} and {} would both be written to {}",
+                other.display(),
+                file.display(),
+                target_file.display()
+            )
+            .into());
+        }
+        jobs.push((file, target_file));
+    }
+    for (file, target_file) in &jobs {
+        log::debug!("target of {}: {}", file.display(), target_file.display());
+    }
+
+    if args.dry_run {
+        return dry_run(
+            &jobs,
+            &options,
+            &manifest,
+            read_stdin,
+            args.preview,
+            args.error_report.as_deref(),
+            out,
+        );
+    }
+
+    // an aggregate bar over all files, followed by a line for every file in flight
+    let progress = if args.quiet || args.no_progress || json {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    } else {
+        MultiProgress::new()
+    };
+    let bar = progress.add(ProgressBar::new(jobs.len().try_into().unwrap()));
+    if let Some(logger) = LOGGER.get() {
+        let _ = logger.progress.set(progress.clone());
+    }
+
+    let colors = args.color.enabled();
+    console::set_colors_enabled_stderr(colors);
+    let (style, file_style, spinner_style) = if colors {
+        (
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] {bar:40.yellow/blue} {pos:>7}/{len:7} ETA {eta} {msg}",
+            )
+            .unwrap(),
+            ProgressStyle::with_template(
+                "  {spinner:.green} [{elapsed_precise}] {bar:20.cyan/blue} {bytes:>10}/{total_bytes:10} {msg}",
+            )
+            .unwrap(),
+            ProgressStyle::with_template("  {spinner:.green} [{elapsed_precise}] {msg}").unwrap(),
         )
-        .unwrap(),
-    );
-    let bar = Arc::new(Mutex::new(bar));
+    } else {
+        (
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] [{bar:40}] {pos:>7}/{len:7} ETA {eta} {msg}",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+            ProgressStyle::with_template(
+                "  {spinner} [{elapsed_precise}] [{bar:20}] {bytes:>10}/{total_bytes:10} {msg}",
+            )
+            .unwrap()
+            .progress_chars("#>-")
+            .tick_chars("|/-\\ "),
+            ProgressStyle::with_template("  {spinner} [{elapsed_precise}] {msg}")
+                .unwrap()
+                .tick_chars("|/-\\ "),
+        )
+    };
+    bar.set_style(style);
 
     let runtime = runtime::Builder::new_multi_thread()
-        .worker_threads(args.worker as usize)
+        .worker_threads(args.worker)
         .enable_all()
         .build()?;
 
+    // a conversion runs on a blocking thread, so the permits bound the number of files converted at once
+    let permits = Arc::new(Semaphore::new(
+        args.max_concurrent_files.unwrap_or(args.worker),
+    ));
+
+    // the schema of a new dataset is the one of its first file
+    let dataset = match &args.append {
+        Some(dataset_dir) => {
+            let dataset = read_dataset(dataset_dir)?;
+            let schema = match (dataset.schema, jobs.first()) {
+                (Some(schema), _) => Some(schema),
+                (None, Some((file, _))) => Some(
+                    inspect_csv(file, &manifest.options_for(file, &options))
+                        .map_err(|err| format!("{}: {}", file.display(), err))?
+                        .schema,
+                ),
+                (None, None) => None,
+            };
+            fs::create_dir_all(dataset_dir)?;
+            schema.map(|schema| (schema, Arc::new(AtomicU64::new(dataset.last_part + 1))))
+        }
+        None => None,
+    };
+    let timeout = args.timeout.map(Duration::from_secs);
+    // standard input and output cannot be read or written again
+    let retries = if read_stdin || write_stdout {
+        0
+    } else {
+        args.retries
+    };
+
+    // the first Ctrl-C stops starting files, the second one also cancels the files in flight
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let cancel_flags = Arc::new(Mutex::new(Vec::<Arc<AtomicBool>>::new()));
+    let not_started = Arc::new(AtomicUsize::new(0));
+    // the Parquet files written, hashed once every conversion finished
+    let written = Arc::new(Mutex::new(Vec::<PathBuf>::new()));
+    let checksums = args.checksums.is_some();
+
     runtime.block_on(async {
+        let signal_handler = {
+            let interrupted = Arc::clone(&interrupted);
+            let cancel_flags = Arc::clone(&cancel_flags);
+            let progress = progress.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                interrupted.store(true, Ordering::SeqCst);
+                progress.suspend(|| {
+                    eprintln!("Interrupted, finishing the files in progress. Press Ctrl-C again to cancel them.")
+                });
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                for cancel_flag in cancel_flags.lock().unwrap().iter() {
+                    cancel_flag.store(true, Ordering::SeqCst);
+                }
+            })
+        };
         let mut handles = vec![];
 
-        for file in files {
-            let bar = Arc::clone(&bar);
+        let job_count = jobs.len();
+        for (index, (file, target_file)) in jobs.into_iter().enumerate() {
+            // a file is only spawned once it may start, so the number of tasks stays bounded
+            let permit = Arc::clone(&permits).acquire_owned().await.unwrap();
+            if max_errors.is_some_and(|max| failures.load(Ordering::SeqCst) >= max) {
+                not_started.fetch_add(job_count - index, Ordering::SeqCst);
+                break;
+            }
+            let bar = bar.clone();
+            let progress = progress.clone();
+            let file_style = file_style.clone();
+            let spinner_style = spinner_style.clone();
             let errors_clone = Arc::clone(&errors);
+            let summaries_clone = Arc::clone(&summaries);
+            let written = Arc::clone(&written);
+            let report_clone = Arc::clone(&report);
+            let failures = Arc::clone(&failures);
+            let interrupted = Arc::clone(&interrupted);
+            let not_started = Arc::clone(&not_started);
+            let dataset = dataset.clone();
+            let cancel_flag = Arc::new(AtomicBool::new(false));
+            cancel_flags.lock().unwrap().push(Arc::clone(&cancel_flag));
+            let options = manifest
+                .options_for(&file, &options)
+                .cancel_flag(Arc::clone(&cancel_flag));
             let h = tokio::spawn(async move {
-                if let Err(err) = convert_to_parquet(&file, delimiter, has_header, sampling_size) {
-                    let mut errors = errors_clone.lock().unwrap();
+                let stopped = max_errors.is_some_and(|max| failures.load(Ordering::SeqCst) >= max);
+                if stopped || interrupted.load(Ordering::SeqCst) {
+                    not_started.fetch_add(1, Ordering::SeqCst);
+                    return;
+                }
+
+                // the part number is taken once the file starts, so parts are numbered in the
+                // order the files start, whichever worker converts them
+                let target_file = match &dataset {
+                    Some((_, next_part)) => {
+                        target_file.join(part_file_name(next_part.fetch_add(1, Ordering::SeqCst)))
+                    }
+                    None => target_file,
+                };
+                let dataset_schema = dataset.map(|(schema, _)| schema);
 
-                    errors.push(ErrorData {
-                        file_path: file.to_str().unwrap().to_string(),
-                        error: err.to_string(),
+                let file_name = file.display().to_string();
+                let file_size = if read_stdin {
+                    None
+                } else {
+                    fs::metadata(&file).ok().map(|metadata| metadata.len())
+                };
+                let file_bar = progress.add(ProgressBar::new(file_size.unwrap_or(0)));
+                match file_size {
+                    Some(_) => file_bar.set_style(file_style),
+                    // the size of standard input is unknown, its line only shows the time spent
+                    None => file_bar.set_style(spinner_style),
+                }
+                file_bar.set_message(file_name.clone());
+                file_bar.enable_steady_tick(Duration::from_millis(100));
+                let mut options = {
+                    let file_bar = file_bar.clone();
+                    options.progress(move |bytes| file_bar.set_position(bytes))
+                };
+                if verbose {
+                    let progress = progress.clone();
+                    let file_name = file_name.clone();
+                    options = options.on_schema(move |schema| {
+                        let lines = schema_lines(&file_name, schema);
+                        progress.suspend(|| eprint!("{}", lines));
                     });
                 }
-                bar.lock().unwrap().inc(1);
+
+                let started_at = Utc::now().to_rfc3339();
+                let file_start = Instant::now();
+                log::info!("converting {}", file_name);
+                let timed_out = Arc::new(AtomicBool::new(false));
+                let mut conversion = {
+                    let file = file.clone();
+                    let target_file = target_file.clone();
+                    let cancel_flag = Arc::clone(&cancel_flag);
+                    let timed_out = Arc::clone(&timed_out);
+                    let failures = Arc::clone(&failures);
+                    // the permit is held until the conversion stops, even after it timed out,
+                    // so the files it has open count against the limit
+                    tokio::task::spawn_blocking(move || {
+                        let _permit = permit;
+                        let result = convert_with_retries(retries, &cancel_flag, || {
+                            convert_file(
+                                &file,
+                                target_file.clone(),
+                                options.clone(),
+                                read_stdin,
+                                write_stdout,
+                                emit_schema,
+                                dataset_schema.as_ref(),
+                            )
+                        });
+                        // the failure is counted before the permit is released, so the next
+                        // file only starts once it is known whether the threshold was reached,
+                        // a file cancelled with Ctrl-C is no failure
+                        if result.is_err()
+                            && (timed_out.load(Ordering::SeqCst)
+                                || !cancel_flag.load(Ordering::SeqCst))
+                        {
+                            failures.fetch_add(1, Ordering::SeqCst);
+                        }
+                        result
+                    })
+                };
+                let result = match timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, &mut conversion).await {
+                        Ok(joined) => joined.unwrap_or_else(|err| Err(err.to_string())),
+                        Err(_) => {
+                            // the conversion stops at its next row, but it may also finish
+                            // before, so its own result decides what happened to the file
+                            timed_out.store(true, Ordering::SeqCst);
+                            cancel_flag.store(true, Ordering::SeqCst);
+                            match conversion.await.unwrap_or_else(|err| Err(err.to_string())) {
+                                Ok(summary) => Ok(summary),
+                                Err(_) => {
+                                    if !write_stdout {
+                                        remove_partial_output(&target_file);
+                                    }
+                                    Err(format!("timed out after {} seconds", timeout.as_secs()))
+                                }
+                            }
+                        }
+                    },
+                    None => conversion.await.unwrap_or_else(|err| Err(err.to_string())),
+                };
+
+                let (status, error) = match &result {
+                    Ok(summary) if summary.skipped => (FileStatus::Skipped, None),
+                    Ok(_) => (FileStatus::Ok, None),
+                    Err(err) => (FileStatus::Failed, Some(err.to_string())),
+                };
+                report_clone.lock().unwrap().push(ReportEntry {
+                    file_path: file.to_str().unwrap().to_string(),
+                    status,
+                    error,
+                    started_at,
+                    finished_at: Utc::now().to_rfc3339(),
+                });
+
+                file_bar.finish_and_clear();
+                progress.remove(&file_bar);
+                if matches!(&result, Ok(summary) if summary.skipped) {
+                    bar.set_message(format!("skipped {}", file_name));
+                }
+
+                match &result {
+                    Ok(summary) if summary.skipped => log::info!("skipped {}: {}",
+                        file_name,
+                        summary.warnings.first().cloned().unwrap_or_else(|| format!(
+                            "{} already exists",
+                            summary.output_path.display()
+                        ))
+                    ),
+                    Ok(summary) => {
+                        for warning in &summary.warnings {
+                            log::warn!("{}: {}", file_name, warning);
+                        }
+                        log::info!("converted {}: {} rows, {} bytes in {} ms, output {}",
+                            file_name,
+                            summary.rows_written,
+                            summary.bytes_written,
+                            file_start.elapsed().as_millis(),
+                            summary.output_path.display()
+                        );
+                    }
+                    Err(err) => log::error!("failed {}: {}", file_name, err),
+                }
+
+                match result {
+                    Ok(summary) => {
+                        if checksums && !summary.skipped {
+                            written
+                                .lock()
+                                .unwrap()
+                                .extend(output_files(&summary, &target_file));
+                        }
+                        let mut summaries = summaries_clone.lock().unwrap();
+
+                        summaries.push((
+                            file.to_str().unwrap().to_string(),
+                            summary,
+                            file_start.elapsed(),
+                        ));
+                    }
+                    Err(err) => {
+                        let mut errors = errors_clone.lock().unwrap();
+
+                        errors.push(ErrorData {
+                            file_path: file.to_str().unwrap().to_string(),
+                            error: err.to_string(),
+                        });
+                    }
+                }
+                bar.inc(1);
             });
 
             handles.push(h);
@@ -135,20 +2171,208 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         for handle in handles {
             let _ = handle.await;
         }
+        signal_handler.abort();
     });
+    // every conversion has stopped, including the ones which timed out
+    drop(runtime);
+
+    bar.finish();
 
-    bar.lock().unwrap().finish();
+    let mut summaries = summaries.lock().unwrap();
+    summaries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (file_path, summary, elapsed) in &*summaries {
+        if summary.skipped {
+            // a file skipped for another reason than its existing output says why in a warning
+            match summary.warnings.first() {
+                Some(reason) => writeln!(out, "File: {}  Skipped: {}", file_path, reason)?,
+                None => writeln!(
+                    out,
+                    "File: {}  Skipped: {} already exists",
+                    file_path,
+                    summary.output_path.display()
+                )?,
+            }
+            continue;
+        }
+
+        let output_path = if summary.output_path.as_os_str().is_empty() {
+            String::from("stdout")
+        } else {
+            summary.output_path.display().to_string()
+        };
+        writeln!(
+            out,
+            "File: {}  Rows: {}  Columns: {}  Size: {} bytes  Output: {}",
+            file_path, summary.rows_written, summary.columns, summary.bytes_written, output_path
+        )?;
+        if args.stats {
+            writeln!(
+                out,
+                "File: {}  Time: {} ms  Throughput: {}",
+                file_path,
+                elapsed.as_millis(),
+                throughput(summary.rows_written, summary.bytes_written, *elapsed)
+            )?;
+        }
+        if let Some(delimiter) = summary.detected_delimiter {
+            writeln!(
+                out,
+                "File: {}  Detected delimiter: {:?}",
+                file_path, delimiter
+            )?;
+        }
+        if summary.ragged_rows > 0 {
+            writeln!(
+                out,
+                "File: {}  Ragged rows: {}",
+                file_path, summary.ragged_rows
+            )?;
+        }
+        if summary.invalid_utf8_rows > 0 {
+            let handled = match args.on_invalid_utf8 {
+                OnInvalidUtf8::SkipRow => "skipped",
+                _ => "replaced",
+            };
+            writeln!(
+                out,
+                "File: {}  Invalid UTF-8 rows: {} ({})",
+                file_path, summary.invalid_utf8_rows, handled
+            )?;
+        }
+        for warning in &summary.warnings {
+            writeln!(out, "File: {}  Warning: {}", file_path, warning)?;
+        }
+        if let Some(rows) = args.preview {
+            writeln!(out, "File: {}  Preview:", file_path)?;
+            write_preview(
+                &mut out,
+                preview_output(&summary.output_path, &args.output_extension, rows),
+            )?;
+        }
+    }
 
     let errors = errors.lock().unwrap();
     for err_data in &*errors {
-        println!(
+        writeln!(
+            err_out,
             "File: {}  Error: {:?}\n",
             err_data.file_path, err_data.error
-        );
+        )?;
+    }
+
+    let skipped = summaries.iter().filter(|(_, s, _)| s.skipped).count();
+    let total_rows: u64 = summaries.iter().map(|(_, s, _)| s.rows_written).sum();
+    let total_bytes: u64 = summaries.iter().map(|(_, s, _)| s.bytes_written).sum();
+    writeln!(
+        out,
+        "Total: {} files converted, {} skipped, {} rows, {} bytes",
+        summaries.len() - skipped,
+        skipped,
+        total_rows,
+        total_bytes
+    )?;
+
+    if let Some(error_report) = &args.error_report {
+        write_error_report(error_report, &mut report.lock().unwrap())?;
+    }
+
+    if interrupted.load(Ordering::SeqCst) {
+        writeln!(
+            out,
+            "Interrupted: {} files not started",
+            not_started.load(Ordering::SeqCst)
+        )?;
+    } else if not_started.load(Ordering::SeqCst) > 0 {
+        writeln!(
+            out,
+            "Stopped after {} errors: {} files not started",
+            errors.len(),
+            not_started.load(Ordering::SeqCst)
+        )?;
     }
 
     let elapsed = start.elapsed();
-    println!("Elapsed time {} ms", elapsed.as_millis());
+    writeln!(out, "Elapsed time {} ms", elapsed.as_millis())?;
+    writeln!(
+        out,
+        "Throughput: {}",
+        throughput(total_rows, total_bytes, elapsed)
+    )?;
+
+    if json {
+        let mut results: Vec<FileSummary> = summaries
+            .iter()
+            .map(|(file_path, summary, _)| FileSummary {
+                file_path: file_path.clone(),
+                status: if summary.skipped {
+                    FileStatus::Skipped
+                } else {
+                    FileStatus::Ok
+                },
+                output_path: Some(summary.output_path.display().to_string())
+                    .filter(|path| !path.is_empty()),
+                rows: summary.rows_written,
+                bytes: summary.bytes_written,
+                error: None,
+                warnings: summary.warnings.clone(),
+            })
+            .chain(errors.iter().map(|err_data| FileSummary {
+                file_path: err_data.file_path.clone(),
+                status: FileStatus::Failed,
+                output_path: None,
+                rows: 0,
+                bytes: 0,
+                error: Some(err_data.error.clone()),
+                warnings: vec![],
+            }))
+            .collect();
+        results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        let run = RunSummary {
+            files: results.len(),
+            succeeded: summaries.len() - skipped,
+            skipped,
+            failed: errors.len(),
+            interrupted: interrupted.load(Ordering::SeqCst),
+            not_started: not_started.load(Ordering::SeqCst),
+            rows: total_rows,
+            bytes: total_bytes,
+            elapsed_ms: elapsed.as_millis() as u64,
+            results,
+        };
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer(&mut stdout, &run)?;
+        writeln!(stdout)?;
+    }
+
+    if let Some(checksums_file) = &args.checksums {
+        let mut written = written.lock().unwrap();
+        written.sort();
+        let count = write_checksums(checksums_file, &written)?;
+        writeln!(
+            out,
+            "Checksums: {} files written to {}",
+            count,
+            checksums_file.display()
+        )?;
+    }
+
+    if let Some(output_dir) = output_dir.filter(|_| args.success_marker) {
+        if errors.is_empty() && !interrupted.load(Ordering::SeqCst) {
+            File::create(output_dir.join(SUCCESS_MARKER))?;
+        }
+    }
+
+    if interrupted.load(Ordering::SeqCst) {
+        out.flush()?;
+        err_out.flush()?;
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+    if !errors.is_empty() {
+        out.flush()?;
+        err_out.flush()?;
+        std::process::exit(EXIT_CONVERSION_FAILED);
+    }
 
     Ok(())
 }
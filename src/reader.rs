@@ -0,0 +1,1619 @@
+//! Reading CSV records and converting them to Arrow record batches.
+//!
+//! The records are tokenized with the `csv` crate instead of `arrow-csv`, whose reader could not be
+//! extended for the options of cc2p: it always interprets quotes, so `--no-quoting` cannot be
+//! supported, and its schema inference and reading each tokenize a fresh reader. Standard input
+//! can only be read once, so the sampled records have to be replayed in front of the remaining
+//! ones, and sampling lines instead of records split quoted fields with line breaks. Owning
+//! the records also lets the source skip, join, pad and project them before they are converted.
+
+use crate::decode::DecodeReader;
+use crate::delimiter::{detect_delimiter, DelimiterReader, UNIT_SEPARATOR};
+use crate::{
+    clean_column_name, ConvertOptions, Delimiter, EmptyAs, IntSizing, NormalizeHeader,
+    NumberFormat, OnDuplicateHeader, OnInvalidUtf8, OnRagged, SampleStrategy, CANCELLED_ERROR,
+};
+use arrow_array::{
+    new_null_array, Array, ArrayRef, BooleanArray, Date32Array, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_cast::{cast_with_options, CastOptions};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::StringRecord;
+use regex::{Regex, RegexSet};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Strict casting, so a value which cannot be parsed is an error instead of a null.
+const CAST_OPTIONS: CastOptions = CastOptions {
+    safe: false,
+    format_options: arrow_cast::display::FormatOptions::new(),
+};
+
+/// Reads CSV records from a reader and turns them into Arrow record batches.
+///
+/// The first `sampling` records are buffered to infer the schema and are then replayed in front of
+/// the remaining records, so the reader is consumed only once. The last `skip_footer` records are
+/// held back while reading, so they are never sampled or written. Once `limit` records have been
+/// returned, no more are read.
+pub(crate) struct CsvSource<R: Read> {
+    reader: csv::Reader<DelimiterReader<BufReader<DecodeReader<BufReader<R>>>>>,
+    headers: Vec<String>,
+    pending: VecDeque<StringRecord>,
+    sample_size: usize,
+    scanned_rows: Option<u64>,
+    skip_footer: usize,
+    remaining: Option<u64>,
+    trim_fields: bool,
+    projection: Option<Vec<usize>>,
+    width: usize,
+    on_ragged: OnRagged,
+    ragged_rows: u64,
+    on_invalid_utf8: OnInvalidUtf8,
+    invalid_utf8_rows: u64,
+    lenient_newlines: bool,
+    held: Option<StringRecord>,
+    delimiter: Delimiter,
+    detected_delimiter: Option<char>,
+    warnings: Vec<String>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    batch_size: usize,
+    finished: bool,
+    builder: BatchBuilder,
+}
+
+/// Converts the string fields of CSV records to record batches.
+///
+/// It is separate from [`CsvSource`] so that record batches can be built on other threads while
+/// the source keeps reading.
+#[derive(Clone)]
+pub(crate) struct BatchBuilder {
+    null_regex: Regex,
+    empty_as: EmptyAs,
+    bool_values: Option<Arc<BoolValues>>,
+    int_sizing: IntSizing,
+    strict_leading_zeros: bool,
+    numbers: Option<Arc<NumberParser>>,
+    date_format: Option<String>,
+    date_columns: Vec<bool>,
+}
+
+impl<R: Read> CsvSource<R> {
+    /// Skips the leading lines and reads the header and the sample records from the reader.
+    ///
+    /// The input is transcoded to UTF-8 from the configured encoding. A leading byte order mark is
+    /// removed, so it does not end up in the first column name. A leading `sep=;` line, as written
+    /// by Excel, is removed as well and sets the delimiter unless the options set one.
+    pub(crate) fn new(
+        reader: R,
+        options: &ConvertOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // invalid UTF-8 data is handled record by record unless it fails the conversion
+        let encoding = options.encoding.filter(|&encoding| {
+            encoding != encoding_rs::UTF_8 || options.on_invalid_utf8 == OnInvalidUtf8::Error
+        });
+        let reader = DecodeReader::new(
+            BufReader::with_capacity(options.read_buffer_size, reader),
+            encoding,
+        )?;
+        let mut reader = BufReader::new(reader);
+        let directive = sep_directive(&mut reader)?;
+        for _ in 0..options.skip_rows {
+            if !skip_line(&mut reader)? {
+                break;
+            }
+        }
+
+        let mut warnings = vec![];
+        let mut detected_delimiter = None;
+        let delimiter = match (directive, &options.delimiter) {
+            (Some(delimiter), None | Some(Delimiter::Auto)) => {
+                if let Delimiter::Char(delimiter) = delimiter {
+                    detected_delimiter = Some(delimiter);
+                }
+                delimiter
+            }
+            _ => options.delimiter_or_default(),
+        };
+        let delimiter = match delimiter {
+            Delimiter::Auto => {
+                let quote = Some(options.quote).filter(|_| options.quoting);
+                let comment = options.comment.filter(char::is_ascii);
+                let detected = detect_delimiter(
+                    reader.fill_buf()?,
+                    quote.filter(char::is_ascii).map(|quote| quote as u8),
+                    comment.map(|comment| comment as u8),
+                );
+                if detected.is_none() {
+                    warnings.push(String::from(
+                        "the delimiter could not be detected, using ','",
+                    ));
+                }
+                detected_delimiter = detected;
+                Delimiter::Char(detected.unwrap_or(','))
+            }
+            delimiter => delimiter,
+        };
+
+        let builder = csv_reader_builder(options, &delimiter)?;
+        let multi_character = match &delimiter {
+            Delimiter::Str(delimiter) => Some(delimiter.as_str()),
+            _ => None,
+        };
+        let quote = Some(options.quote as u8).filter(|_| options.quoting);
+        let mut reader = builder.from_reader(DelimiterReader::new(reader, multi_character, quote));
+
+        if options.header_row > 0 && !options.has_header {
+            return Err("a header row requires a header, it cannot be used without one".into());
+        }
+        // the header and the rows above it are replaced rather than skipped
+        let header_policy = match options.on_invalid_utf8 {
+            OnInvalidUtf8::SkipRow if options.has_header => OnInvalidUtf8::Replace,
+            policy => policy,
+        };
+        let mut invalid_utf8_rows = 0;
+        let mut first = StringRecord::new();
+        let mut has_first = read_record(
+            &mut reader,
+            &mut first,
+            header_policy,
+            &mut invalid_utf8_rows,
+        )?;
+        // the rows above the header row, such as titles, are discarded
+        for _ in 0..options.header_row {
+            if !has_first {
+                break;
+            }
+            has_first = read_record(
+                &mut reader,
+                &mut first,
+                header_policy,
+                &mut invalid_utf8_rows,
+            )?;
+        }
+        if options.has_header {
+            // only data rows are counted
+            invalid_utf8_rows = 0;
+        }
+
+        let mut pending = VecDeque::new();
+        let headers: Vec<String> = if options.has_header {
+            first
+                .iter()
+                .map(|name| {
+                    let name = if options.trim.headers() {
+                        name.trim()
+                    } else {
+                        name
+                    };
+                    normalize_header(name, &options.normalize_headers)
+                })
+                .collect()
+        } else {
+            if options.trim.fields() {
+                first.trim();
+            }
+            if options.header_names.is_empty() {
+                (0..first.len())
+                    .map(|i| format!("{}{}", options.column_prefix, i))
+                    .collect()
+            } else if has_first && options.header_names.len() != first.len() {
+                return Err(format!(
+                    "{} header names given, but the file has {} columns",
+                    options.header_names.len(),
+                    first.len()
+                )
+                .into());
+            } else {
+                options.header_names.clone()
+            }
+        };
+
+        let projection = projection(&headers, options)?;
+        let headers = match &projection {
+            Some(columns) => columns.iter().map(|&i| headers[i].clone()).collect(),
+            None => headers,
+        };
+        let (headers, projection) = match options.schema {
+            // the names of a declared schema replace the header
+            Some(_) => (headers, projection),
+            None => duplicate_headers(headers, projection, options, &mut warnings)?,
+        };
+        let headers = rename(headers, options)?;
+        let width = first.len();
+        if !options.has_header && has_first {
+            pending.push_back(project(first, projection.as_deref()));
+        }
+
+        let mut source = Self {
+            reader,
+            headers,
+            pending,
+            sample_size: 0,
+            scanned_rows: None,
+            skip_footer: options.skip_footer,
+            remaining: options.limit,
+            trim_fields: options.trim.fields(),
+            projection,
+            width,
+            on_ragged: options.on_ragged,
+            ragged_rows: 0,
+            on_invalid_utf8: options.on_invalid_utf8,
+            invalid_utf8_rows,
+            lenient_newlines: options.lenient_newlines,
+            held: None,
+            delimiter,
+            detected_delimiter,
+            warnings,
+            cancel_flag: options.cancel_flag.clone(),
+            batch_size: options.batch_size,
+            finished: !has_first,
+            builder: BatchBuilder {
+                null_regex: null_regex(&options.null_values)?,
+                empty_as: options.empty_as,
+                bool_values: BoolValues::new(options).map(Arc::new),
+                int_sizing: options.int_sizing,
+                strict_leading_zeros: options.strict_leading_zeros,
+                numbers: options.number_format.map(NumberParser::new).map(Arc::new),
+                date_format: options.date_format.clone(),
+                date_columns: vec![],
+            },
+        };
+
+        // a declared schema or string columns are used as they are, so no rows are sampled
+        let sampling = if options.schema.is_some() || options.all_strings {
+            0
+        } else {
+            options.sampling as usize
+        };
+        let sampling = source.capped(sampling);
+        source.fill(sampling + options.skip_footer)?;
+        source.sample_size = source.capped(source.pending.len().saturating_sub(source.skip_footer));
+
+        let skipping = options.skip_rows > 0 || options.header_row > 0 || options.skip_footer > 0;
+        if skipping && source.sample_size == 0 && source.finished {
+            return Err("skipping rows leaves no data rows".into());
+        }
+
+        Ok(source)
+    }
+
+    /// Uses a declared schema instead of inferring one from the sample.
+    ///
+    /// The declared columns are matched by position and their names replace the names of the header.
+    /// With a date format, the declared date and timestamp columns are parsed with it.
+    pub(crate) fn declared_schema(&mut self, schema: &Schema) -> Result<Schema, String> {
+        if schema.fields().len() != self.headers.len() {
+            return Err(format!(
+                "the schema declares {} columns, but the file has {} columns",
+                schema.fields().len(),
+                self.headers.len()
+            ));
+        }
+
+        self.builder.date_columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                matches!(
+                    field.data_type(),
+                    DataType::Date32 | DataType::Timestamp(..)
+                )
+            })
+            .collect();
+
+        Ok(schema.clone())
+    }
+
+    /// Infers the schema from the sample records. Every field is nullable, as only a sample is inspected.
+    ///
+    /// With a date format, a column whose sampled values all match it is a date or timestamp column,
+    /// while a column where only some values match falls back to strings.
+    pub(crate) fn infer_schema(&mut self) -> Schema {
+        let mut inference = Inference::new(self.headers.len());
+        for record in self.pending.iter().take(self.sample_size) {
+            inference.update(record, &self.builder);
+        }
+
+        self.finish_inference(inference)
+    }
+
+    /// Returns a schema with a string column for every column of the header, without inspecting any records.
+    pub(crate) fn string_schema(&mut self) -> Schema {
+        self.builder.date_columns = vec![false; self.headers.len()];
+
+        Schema::new(
+            self.headers
+                .iter()
+                .map(|name| Field::new(name, DataType::Utf8, true))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Infers the schema from a pass over `scan`, another source of the same data, instead of the sample.
+    ///
+    /// With [`SampleStrategy::Full`] every record is inspected, otherwise `sampling` records
+    /// picked at random from the whole data.
+    pub(crate) fn infer_schema_from<S: Read>(
+        &mut self,
+        mut scan: CsvSource<S>,
+        strategy: SampleStrategy,
+        sampling: usize,
+    ) -> Result<Schema, Box<dyn std::error::Error>> {
+        let mut inference = Inference::new(self.headers.len());
+        let mut reservoir = Reservoir::new(sampling);
+        let mut rows = 0;
+        loop {
+            let records = scan.next_records(self.batch_size)?;
+            if records.is_empty() {
+                break;
+            }
+            rows += records.len() as u64;
+            for record in records {
+                if strategy == SampleStrategy::Full {
+                    inference.update(&record, &self.builder);
+                } else {
+                    reservoir.offer(record);
+                }
+            }
+        }
+        for record in &reservoir.records {
+            inference.update(record, &self.builder);
+        }
+
+        self.sample_size = if strategy == SampleStrategy::Full {
+            rows as usize
+        } else {
+            reservoir.records.len()
+        };
+        self.scanned_rows = Some(rows);
+
+        Ok(self.finish_inference(inference))
+    }
+
+    /// Turns the inspected values into the inferred schema and prepares the builder for its date columns.
+    fn finish_inference(&mut self, inference: Inference) -> Schema {
+        let mut date_columns = vec![false; self.headers.len()];
+        let fields: Vec<Field> = inference
+            .column_types
+            .iter()
+            .zip(&inference.temporal_types)
+            .zip(&self.headers)
+            .enumerate()
+            .map(|(i, ((inferred, temporal), name))| {
+                let data_type = match temporal.get() {
+                    // every sampled value is a boolean token
+                    _ if inference.bool_types[i] == Some(true) => DataType::Boolean,
+                    Some(data_type) => {
+                        date_columns[i] = data_type != DataType::Utf8;
+                        data_type
+                    }
+                    None => inferred.get(self.builder.int_sizing),
+                };
+                let data_type = match data_type {
+                    // empty fields are strings rather than missing values
+                    DataType::Null if inference.empty_strings[i] => DataType::Utf8,
+                    data_type => data_type,
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        self.builder.date_columns = date_columns;
+
+        Schema::new(fields)
+    }
+
+    /// Reads the next batch of records and converts them to the given schema.
+    ///
+    /// Returns `None` once all records have been read.
+    pub(crate) fn next_batch(
+        &mut self,
+        schema: &SchemaRef,
+    ) -> Result<Option<RecordBatch>, Box<dyn std::error::Error>> {
+        let rows = self.next_records(self.batch_size)?;
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.builder.build(schema, &rows)?))
+    }
+
+    /// Reads up to `count` records, in batches to pass to [`BatchBuilder::build`].
+    ///
+    /// Returns no batches once all records have been read.
+    pub(crate) fn next_record_batches(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<Vec<StringRecord>>, Box<dyn std::error::Error>> {
+        let mut batches = vec![];
+        let mut remaining = count;
+        while remaining > 0 {
+            let rows = self.next_records(remaining.min(self.batch_size))?;
+            if rows.is_empty() {
+                break;
+            }
+            remaining -= rows.len();
+            batches.push(rows);
+        }
+
+        Ok(batches)
+    }
+
+    /// Returns the number of data rows used for inferring the schema.
+    pub(crate) fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+
+    /// Estimates the number of data rows from `size`, the number of bytes of the data, and the
+    /// average size of the sampled rows.
+    ///
+    /// The count is exact if all rows were sampled, and `None` if it is unknown.
+    pub(crate) fn estimate_rows(&self, size: Option<u64>) -> Option<u64> {
+        if let Some(rows) = self.scanned_rows {
+            return Some(rows);
+        }
+        if self.finished
+            || self
+                .remaining
+                .is_some_and(|limit| limit <= self.sample_size as u64)
+        {
+            return Some(self.sample_size as u64);
+        }
+
+        let first = self.pending.front()?.position()?.byte();
+        let last = self.pending.back()?.position()?.byte();
+        let rows = self.pending.len() as u64 - 1;
+        if rows == 0 || last <= first {
+            return None;
+        }
+
+        let estimate = size?.saturating_sub(first) * rows / (last - first);
+        let estimate = estimate.saturating_sub(self.skip_footer as u64);
+        Some(self.remaining.map_or(estimate, |limit| estimate.min(limit)))
+    }
+
+    /// Returns the number of records read so far whose number of fields differs from the header.
+    pub(crate) fn ragged_rows(&self) -> u64 {
+        self.ragged_rows
+    }
+
+    /// Returns the number of records read so far with invalid UTF-8 data, which were replaced or skipped.
+    pub(crate) fn invalid_utf8_rows(&self) -> u64 {
+        self.invalid_utf8_rows
+    }
+
+    /// Returns the delimiter the data is read with, as set, detected or defaulted.
+    pub(crate) fn delimiter(&self) -> &Delimiter {
+        &self.delimiter
+    }
+
+    /// Returns the delimiter detected from the data, if it was detected.
+    pub(crate) fn detected_delimiter(&self) -> Option<char> {
+        self.detected_delimiter
+    }
+
+    /// Takes the problems found while reading the data which do not stop the conversion.
+    pub(crate) fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Returns the builder converting the records to the inferred schema.
+    pub(crate) fn builder(&self) -> &BatchBuilder {
+        &self.builder
+    }
+
+    /// Reads up to `count` records, holding back the footer records.
+    fn next_records(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<StringRecord>, Box<dyn std::error::Error>> {
+        let count = self.capped(count);
+        let mut rows = Vec::with_capacity(count);
+        while rows.len() < count {
+            self.fill(self.skip_footer + 1)?;
+            if self.pending.len() <= self.skip_footer {
+                break;
+            }
+            rows.extend(self.pending.pop_front());
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= rows.len() as u64;
+        }
+
+        Ok(rows)
+    }
+
+    /// Caps `count` at the number of records left before the limit.
+    fn capped(&self, count: usize) -> usize {
+        match self.remaining {
+            Some(remaining) => count.min(usize::try_from(remaining).unwrap_or(usize::MAX)),
+            None => count,
+        }
+    }
+
+    /// Reads records until `count` records are pending or the reader is exhausted.
+    fn fill(&mut self, count: usize) -> Result<(), Box<dyn std::error::Error>> {
+        while !self.finished && self.pending.len() < count {
+            if let Some(cancel_flag) = &self.cancel_flag {
+                if cancel_flag.load(Ordering::Relaxed) {
+                    return Err(CANCELLED_ERROR.into());
+                }
+            }
+            let mut record = StringRecord::new();
+            // a record read ahead while joining fragments comes first
+            let read = match self.held.take() {
+                Some(held) => {
+                    record = held;
+                    true
+                }
+                None => read_record(
+                    &mut self.reader,
+                    &mut record,
+                    self.on_invalid_utf8,
+                    &mut self.invalid_utf8_rows,
+                )?,
+            };
+            if read {
+                if self.lenient_newlines && record.len() < self.width {
+                    record = self.join_fragments(record)?;
+                }
+                if record.len() != self.width {
+                    self.ragged_rows += 1;
+                    match (self.on_ragged, record.len() < self.width) {
+                        (OnRagged::Skip, _) => continue,
+                        (OnRagged::Pad, true) => {
+                            while record.len() < self.width {
+                                record.push_field("");
+                            }
+                        }
+                        (OnRagged::Truncate, false) => record.truncate(self.width),
+                        _ => {
+                            return Err(format!(
+                                "line {} has {} fields, expected {}",
+                                record.position().map_or(0, |position| position.line()),
+                                record.len(),
+                                self.width
+                            )
+                            .into());
+                        }
+                    }
+                }
+                if self.trim_fields {
+                    record.trim();
+                }
+                self.pending
+                    .push_back(project(record, self.projection.as_deref()));
+            } else {
+                self.finished = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Joins the following records to a record with too few fields, as long as the joined record
+    /// has no more fields than the header.
+    ///
+    /// The last field of a fragment and the first field of the next one are one field split by a
+    /// line break. A record which would make the joined record too long is held for the next read.
+    fn join_fragments(&mut self, mut record: StringRecord) -> Result<StringRecord, csv::Error> {
+        while record.len() < self.width {
+            let mut next = StringRecord::new();
+            if !read_record(
+                &mut self.reader,
+                &mut next,
+                self.on_invalid_utf8,
+                &mut self.invalid_utf8_rows,
+            )? {
+                self.finished = true;
+                break;
+            }
+            if record.len() + next.len().saturating_sub(1) > self.width {
+                self.held = Some(next);
+                break;
+            }
+
+            let last = record.len() - 1;
+            let mut joined = StringRecord::with_capacity(
+                record.as_slice().len() + next.as_slice().len() + 1,
+                self.width,
+            );
+            for field in record.iter().take(last) {
+                joined.push_field(field);
+            }
+            joined.push_field(&format!(
+                "{}\n{}",
+                &record[last],
+                next.get(0).unwrap_or_default()
+            ));
+            for field in next.iter().skip(1) {
+                joined.push_field(field);
+            }
+            joined.set_position(record.position().cloned());
+            record = joined;
+        }
+
+        Ok(record)
+    }
+}
+
+/// Reads the next record, replacing or skipping a record with invalid UTF-8 data as configured.
+///
+/// Replaced and skipped records are counted in `invalid_rows`. With [`OnInvalidUtf8::Error`] the
+/// record is read as it is, so invalid data fails with the error of the CSV reader.
+fn read_record<R: Read>(
+    reader: &mut csv::Reader<R>,
+    record: &mut StringRecord,
+    on_invalid_utf8: OnInvalidUtf8,
+    invalid_rows: &mut u64,
+) -> csv::Result<bool> {
+    if on_invalid_utf8 == OnInvalidUtf8::Error {
+        return reader.read_record(record);
+    }
+
+    loop {
+        let mut bytes = csv::ByteRecord::new();
+        if !reader.read_byte_record(&mut bytes)? {
+            return Ok(false);
+        }
+        if bytes
+            .iter()
+            .any(|field| std::str::from_utf8(field).is_err())
+        {
+            *invalid_rows += 1;
+            if on_invalid_utf8 == OnInvalidUtf8::SkipRow {
+                continue;
+            }
+        }
+        let position = bytes.position().cloned();
+        *record = StringRecord::from_byte_record_lossy(bytes);
+        record.set_position(position);
+        return Ok(true);
+    }
+}
+
+/// Consumes a leading `sep=<delimiter>` line and returns its delimiter, or `None` if the data does
+/// not start with one.
+fn sep_directive<R: BufRead>(reader: &mut R) -> std::io::Result<Option<Delimiter>> {
+    let buffer = reader.fill_buf()?;
+    let end = buffer
+        .iter()
+        .position(|&byte| byte == b'\n' || byte == b'\r')
+        .unwrap_or(buffer.len());
+    let line = String::from_utf8_lossy(&buffer[..end]);
+    let value = match (line.get(..4), line.get(4..)) {
+        (Some(prefix), Some(value)) if prefix.eq_ignore_ascii_case("sep=") => value,
+        _ => return Ok(None),
+    };
+
+    match crate::parse_delimiter(value) {
+        Ok(Delimiter::Auto) | Err(_) => Ok(None),
+        Ok(delimiter) => {
+            skip_line(reader)?;
+            Ok(Some(delimiter))
+        }
+    }
+}
+
+/// Consumes a line ending with `\n`, `\r\n` or a lone `\r`, like the line terminators of the
+/// tokenizer. Returns `false` at the end of the data.
+fn skip_line<R: BufRead>(reader: &mut R) -> std::io::Result<bool> {
+    let mut read = false;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(read);
+        }
+        read = true;
+
+        match buffer
+            .iter()
+            .position(|&byte| byte == b'\n' || byte == b'\r')
+        {
+            Some(end) => {
+                let carriage_return = buffer[end] == b'\r';
+                reader.consume(end + 1);
+                if carriage_return && reader.fill_buf()?.first() == Some(&b'\n') {
+                    reader.consume(1);
+                }
+                return Ok(true);
+            }
+            None => {
+                let length = buffer.len();
+                reader.consume(length);
+            }
+        }
+    }
+}
+
+/// Returns the indices of the selected columns in output order, or `None` if all columns are kept.
+fn projection(headers: &[String], options: &ConvertOptions) -> Result<Option<Vec<usize>>, String> {
+    if options.columns.is_empty() && options.exclude_columns.is_empty() {
+        return Ok(None);
+    }
+
+    // the first column of a name wins, as with a linear search, without one for every name
+    let mut positions: HashMap<&str, usize> = HashMap::with_capacity(headers.len());
+    for (i, header) in headers.iter().enumerate().rev() {
+        positions.insert(header, i);
+    }
+
+    // a name which is not in the header but a number is the 0-based position of the column
+    let index = |name: &String| match positions.get(name.as_str()) {
+        Some(&i) => Ok(i),
+        None => match name.parse::<usize>() {
+            Ok(i) if i < headers.len() => Ok(i),
+            Ok(i) => Err(format!(
+                "column index {} is out of range, the file has {} columns",
+                i,
+                headers.len()
+            )),
+            Err(_) => Err(format!(
+                "column '{}' does not exist, available columns: {}",
+                name,
+                headers.join(", ")
+            )),
+        },
+    };
+
+    let columns = if options.columns.is_empty() {
+        let excluded = options
+            .exclude_columns
+            .iter()
+            .map(index)
+            .collect::<Result<HashSet<_>, _>>()?;
+        (0..headers.len())
+            .filter(|i| !excluded.contains(i))
+            .collect()
+    } else {
+        options
+            .columns
+            .iter()
+            .map(index)
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    Ok(Some(columns))
+}
+
+/// Applies the duplicate header policy of the options to the headers.
+///
+/// With [`OnDuplicateHeader::First`] the later duplicates are dropped from the headers and the
+/// projection. With [`OnDuplicateHeader::Rename`] the headers are kept, as the column names are
+/// deduplicated once the schema is inferred.
+fn duplicate_headers(
+    headers: Vec<String>,
+    projection: Option<Vec<usize>>,
+    options: &ConvertOptions,
+    warnings: &mut Vec<String>,
+) -> Result<(Vec<String>, Option<Vec<usize>>), String> {
+    if options.on_duplicate_header == OnDuplicateHeader::Rename {
+        return Ok((headers, projection));
+    }
+
+    let mut positions: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut kept = vec![];
+    for (i, name) in headers.iter().enumerate() {
+        // empty names are replaced with generated names
+        if name.is_empty() {
+            kept.push(i);
+            continue;
+        }
+        let cleaned = clean_column_name(name);
+        if let Some(first) = positions.get(&cleaned) {
+            if options.on_duplicate_header == OnDuplicateHeader::Error {
+                return Err(format!(
+                    "duplicate column name '{}' in columns {} and {}",
+                    name,
+                    first + 1,
+                    i + 1
+                ));
+            }
+            warnings.push(format!(
+                "duplicate column '{}' in column {} is dropped",
+                name,
+                i + 1
+            ));
+            continue;
+        }
+        positions.insert(cleaned, i);
+        kept.push(i);
+    }
+
+    if kept.len() == headers.len() {
+        return Ok((headers, projection));
+    }
+    let projection = kept
+        .iter()
+        .map(|&i| projection.as_ref().map_or(i, |columns| columns[i]))
+        .collect();
+    let headers = kept.into_iter().map(|i| headers[i].clone()).collect();
+
+    Ok((headers, Some(projection)))
+}
+
+/// Applies the header normalizations to the name.
+fn normalize_header(name: &str, normalizations: &[NormalizeHeader]) -> String {
+    let mut name = name.to_string();
+    if normalizations.contains(&NormalizeHeader::Trim) {
+        name = name.trim().to_string();
+    }
+    if normalizations.contains(&NormalizeHeader::Snake) {
+        name = snake_case(&name);
+    }
+    if normalizations.contains(&NormalizeHeader::Lower) {
+        name = name.to_lowercase();
+    }
+
+    name
+}
+
+/// Converts the name to lowercase snake case.
+///
+/// Words are separated by any character which is not alphanumeric, and by a change from a lowercase
+/// letter or a digit to an uppercase letter, so `HTTPStatus code` becomes `http_status_code`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            continue;
+        }
+
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if boundary && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+
+    snake.trim_end_matches('_').to_string()
+}
+
+/// Applies the column renames of the options to the selected columns.
+fn rename(mut headers: Vec<String>, options: &ConvertOptions) -> Result<Vec<String>, String> {
+    let mut renames: Vec<_> = options.renames.iter().collect();
+    renames.sort();
+    let names: HashSet<&String> = headers.iter().collect();
+    for (old, _) in &renames {
+        if !names.contains(old) {
+            return Err(format!(
+                "column '{}' does not exist, available columns: {}",
+                old,
+                headers.join(", ")
+            ));
+        }
+    }
+
+    for header in headers.iter_mut() {
+        if let Some(new) = options.renames.get(header) {
+            header.clone_from(new);
+        }
+    }
+    let mut counts: HashMap<&String, usize> = HashMap::with_capacity(headers.len());
+    for header in &headers {
+        *counts.entry(header).or_default() += 1;
+    }
+    for (_, new) in renames {
+        if counts.get(new).is_some_and(|&count| count > 1) {
+            return Err(format!(
+                "renaming results in the duplicate column name '{}'",
+                new
+            ));
+        }
+    }
+
+    Ok(headers)
+}
+
+/// Keeps the selected fields of the record, in the order of the projection.
+fn project(record: StringRecord, projection: Option<&[usize]>) -> StringRecord {
+    match projection {
+        Some(columns) => {
+            let mut projected: StringRecord = columns
+                .iter()
+                .map(|&i| record.get(i).unwrap_or_default())
+                .collect();
+            // the position is kept for error messages
+            projected.set_position(record.position().cloned());
+            projected
+        }
+        None => record,
+    }
+}
+
+/// Configures the CSV tokenizer from the conversion options.
+fn csv_reader_builder(
+    options: &ConvertOptions,
+    delimiter: &Delimiter,
+) -> Result<csv::ReaderBuilder, String> {
+    crate::validate_special_characters(
+        delimiter,
+        options.quoting.then_some(options.quote),
+        options.comment,
+    )?;
+    // a number containing the delimiter has to be quoted
+    if let (Delimiter::Char(delimiter), Some(number_format), false) =
+        (delimiter, options.number_format, options.quoting)
+    {
+        if number_format.thousands_separator == Some(*delimiter)
+            || number_format.decimal_point == *delimiter
+        {
+            return Err(format!(
+                "the number format uses the delimiter {:?}, which requires quoting",
+                delimiter
+            ));
+        }
+    }
+    let delimiter = match delimiter {
+        Delimiter::Char(delimiter) => ascii_byte("delimiter", *delimiter)?,
+        Delimiter::Str(_) => UNIT_SEPARATOR,
+        Delimiter::Auto => unreachable!("the delimiter is detected before tokenizing"),
+    };
+
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .buffer_capacity(options.read_buffer_size)
+        .has_headers(false)
+        .delimiter(delimiter)
+        .flexible(true)
+        .quoting(options.quoting)
+        .quote(ascii_byte("quote", options.quote)?)
+        .double_quote(true)
+        .comment(
+            options
+                .comment
+                .map(|comment| ascii_byte("comment", comment))
+                .transpose()?,
+        );
+
+    Ok(builder)
+}
+
+/// Returns the byte of an ASCII character, as the CSV tokenizer only supports single-byte special characters.
+fn ascii_byte(name: &str, value: char) -> Result<u8, String> {
+    if value.is_ascii() {
+        Ok(value as u8)
+    } else {
+        Err(format!("{} {:?} must be an ASCII character", name, value))
+    }
+}
+
+/// Builds a regex matching empty fields and any of the given null tokens exactly.
+fn null_regex(null_values: &[String]) -> Result<Regex, regex::Error> {
+    let alternatives: Vec<String> = std::iter::once(String::new())
+        .chain(null_values.iter().map(|value| regex::escape(value)))
+        .collect();
+
+    Regex::new(&format!("^(?:{})$", alternatives.join("|")))
+}
+
+impl BatchBuilder {
+    /// Returns whether the field value is written as null.
+    pub(crate) fn is_null(&self, value: &str) -> bool {
+        self.null_regex.is_match(value)
+    }
+
+    /// Returns the value as a plain number if it is a number of the configured number format, or
+    /// `None` if it is a number which does not match the format, such as `1.5` for `1.234,56`.
+    fn plain_number<'a>(&self, value: &'a str) -> Option<Cow<'a, str>> {
+        match &self.numbers {
+            Some(numbers) => numbers.plain(value),
+            None => Some(Cow::Borrowed(value)),
+        }
+    }
+
+    /// Converts the string fields of the rows to a record batch with the given schema.
+    pub(crate) fn build(
+        &self,
+        schema: &SchemaRef,
+        rows: &[StringRecord],
+    ) -> Result<RecordBatch, ArrowError> {
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let keep_empty =
+                    self.empty_as == EmptyAs::String && field.data_type() == &DataType::Utf8;
+                let numeric = field.data_type().is_numeric();
+                let mut mismatch = None;
+                let values: StringArray = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(row_index, row)| {
+                        row.get(i)
+                            .filter(|value| {
+                                (keep_empty && value.is_empty()) || !self.null_regex.is_match(value)
+                            })
+                            .map(|value| {
+                                if !numeric {
+                                    return Cow::Borrowed(value);
+                                }
+                                self.plain_number(value).unwrap_or_else(|| {
+                                    mismatch.get_or_insert((row_index, value));
+                                    Cow::Borrowed(value)
+                                })
+                            })
+                    })
+                    .collect();
+                // Arrow would parse a number of another format, such as 1.5 for 1.234,56
+                if let Some((row_index, value)) = mismatch {
+                    return Err(ArrowError::ParseError(format!(
+                        "value {} for column '{}' at line {} does not match the number format",
+                        value,
+                        field.name(),
+                        rows[row_index]
+                            .position()
+                            .map_or(0, |position| position.line())
+                    )));
+                }
+                let date_format = self
+                    .date_format
+                    .as_deref()
+                    .filter(|_| self.date_columns.get(i) == Some(&true));
+                match (date_format, &self.bool_values) {
+                    (Some(date_format), _) => {
+                        build_temporal_column(field, &values, rows, date_format)
+                    }
+                    (None, Some(bool_values)) if field.data_type() == &DataType::Boolean => {
+                        build_bool_column(field, &values, rows, bool_values)
+                    }
+                    (None, _) => build_column(field, values, rows),
+                }
+            })
+            .collect::<Result<Vec<ArrayRef>, ArrowError>>()?;
+
+        RecordBatch::try_new(schema.clone(), columns)
+    }
+}
+
+/// Parses the string values of a column into the data type of the field.
+fn build_column(
+    field: &Field,
+    values: StringArray,
+    rows: &[StringRecord],
+) -> Result<ArrayRef, ArrowError> {
+    match field.data_type() {
+        DataType::Utf8 => Ok(Arc::new(values)),
+        DataType::Null => Ok(new_null_array(&DataType::Null, values.len())),
+        // Arrow rounds the digits beyond the scale, but decimals are written exactly
+        DataType::Decimal128(_, scale) => {
+            let rounded = values.iter().enumerate().find_map(|(i, value)| {
+                let value = value?;
+                (fractional_digits(value)? > *scale as i64).then_some((i, value))
+            });
+            if let Some((i, value)) = rounded {
+                return Err(ArrowError::ParseError(format!(
+                    "value {} for column '{}' at line {} has more fractional digits than the scale {}",
+                    value,
+                    field.name(),
+                    rows[i].position().map_or(0, |position| position.line()),
+                    scale
+                )));
+            }
+            cast_with_options(&values, field.data_type(), &CAST_OPTIONS)
+                .map_err(|error| parse_error(field, &values, rows, error))
+        }
+        data_type => cast_with_options(&values, data_type, &CAST_OPTIONS)
+            .map_err(|error| parse_error(field, &values, rows, error)),
+    }
+}
+
+/// Returns the number of digits after the decimal point a number needs to be exact, negative if
+/// it ends with zeros before the decimal point, or `None` if the value is no number or zero.
+///
+/// Trailing zeros after the decimal point are not needed, so `1.230` needs `2` digits and `1500`
+/// needs `-2`.
+fn fractional_digits(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if !integer
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if !fraction.is_empty() {
+        return Some(fraction.len() as i64);
+    }
+    let significant = integer.trim_end_matches('0');
+    if significant.trim_start_matches('0').is_empty() {
+        return None;
+    }
+
+    Some(-((integer.len() - significant.len()) as i64))
+}
+
+/// Rewrites the numbers of a [`NumberFormat`] to the plain format parsed by Arrow, such as
+/// `1.234,56` to `1234.56`.
+pub(crate) struct NumberParser {
+    pattern: Regex,
+    thousands_separator: Option<char>,
+    decimal_point: char,
+}
+
+impl NumberParser {
+    fn new(format: NumberFormat) -> Self {
+        // the thousands separator has to separate groups of three digits
+        let integer = match format.thousands_separator {
+            Some(separator) => format!(
+                r"\d{{1,3}}(?:{}\d{{3}})+|\d+",
+                regex::escape(&separator.to_string())
+            ),
+            None => String::from(r"\d+"),
+        };
+        let pattern = format!(
+            r"^-?(?:{})(?:{}\d+)?(?:[eE][-+]?\d+)?$",
+            integer,
+            regex::escape(&format.decimal_point.to_string())
+        );
+
+        Self {
+            pattern: Regex::new(&pattern).unwrap(),
+            thousands_separator: format.thousands_separator,
+            decimal_point: format.decimal_point,
+        }
+    }
+
+    /// Returns the value as a plain number if it is a number of the format, `None` if it is a
+    /// number of another format, such as `1.5` for `1.234,56`, and otherwise unchanged.
+    fn plain<'a>(&self, value: &'a str) -> Option<Cow<'a, str>> {
+        if !self.pattern.is_match(value) {
+            let number_like = value.chars().any(|c| c.is_ascii_digit())
+                && value.chars().all(|c| {
+                    c.is_ascii_digit()
+                        || "+-.,eE".contains(c)
+                        || c == self.decimal_point
+                        || Some(c) == self.thousands_separator
+                });
+            return (!number_like).then_some(Cow::Borrowed(value));
+        }
+
+        Some(
+            value
+                .chars()
+                .filter(|&c| Some(c) != self.thousands_separator)
+                .map(|c| if c == self.decimal_point { '.' } else { c })
+                .collect(),
+        )
+    }
+}
+
+/// The tokens of boolean values, compared case-insensitively.
+pub(crate) struct BoolValues {
+    true_values: Vec<String>,
+    false_values: Vec<String>,
+}
+
+impl BoolValues {
+    /// Returns the boolean tokens of the options, or `None` if booleans are not parsed.
+    fn new(options: &ConvertOptions) -> Option<Self> {
+        let lowercase = |values: &[String]| values.iter().map(|v| v.to_lowercase()).collect();
+        options.parse_bools.then(|| Self {
+            true_values: lowercase(&options.true_values),
+            false_values: lowercase(&options.false_values),
+        })
+    }
+
+    /// Returns the boolean of the value, or `None` if it is not a boolean token.
+    fn parse(&self, value: &str) -> Option<bool> {
+        let value = value.to_lowercase();
+        if self.true_values.contains(&value) {
+            Some(true)
+        } else if self.false_values.contains(&value) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses the string values of a boolean column with the boolean tokens.
+fn build_bool_column(
+    field: &Field,
+    values: &StringArray,
+    rows: &[StringRecord],
+    bool_values: &BoolValues,
+) -> Result<ArrayRef, ArrowError> {
+    let array: BooleanArray = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            value
+                .map(|value| {
+                    bool_values
+                        .parse(value)
+                        .ok_or_else(|| invalid_value(field, value, &rows[i]))
+                })
+                .transpose()
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Arc::new(array))
+}
+
+/// Parses the string values of a date or timestamp column with the given format.
+///
+/// Columns whose type was overridden to a non-temporal type are cast as usual.
+fn build_temporal_column(
+    field: &Field,
+    values: &StringArray,
+    rows: &[StringRecord],
+    date_format: &str,
+) -> Result<ArrayRef, ArrowError> {
+    let parse =
+        |i: usize, value: &str| match (parse_temporal(value, date_format), field.data_type()) {
+            (Some(Temporal::Date(days)), _) => Ok(days as i64),
+            (Some(Temporal::Timestamp(micros)), DataType::Date32) => {
+                Ok(micros.div_euclid(MICROS_PER_DAY))
+            }
+            (Some(Temporal::Timestamp(micros)), _) => Ok(micros),
+            (None, _) => Err(invalid_value(field, value, &rows[i])),
+        };
+
+    match field.data_type() {
+        DataType::Date32 => {
+            let array: Date32Array = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    value
+                        .map(|value| parse(i, value).map(|days| days as i32))
+                        .transpose()
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(Arc::new(array))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            let array: TimestampMicrosecondArray = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| value.map(|value| parse(i, value)).transpose())
+                .collect::<Result<_, _>>()?;
+            Ok(Arc::new(array))
+        }
+        _ => build_column(field, values.clone(), rows),
+    }
+}
+
+/// Builds the error for a value of the column which cannot be parsed.
+fn invalid_value(field: &Field, value: &str, row: &StringRecord) -> ArrowError {
+    let line = row.position().map_or(0, |position| position.line());
+    ArrowError::ParseError(format!(
+        "Error while parsing value {} for column '{}' at line {}",
+        value,
+        field.name(),
+        line
+    ))
+}
+
+/// Builds an error pointing at the first value of the column which cannot be parsed.
+fn parse_error(
+    field: &Field,
+    values: &StringArray,
+    rows: &[StringRecord],
+    error: ArrowError,
+) -> ArrowError {
+    let invalid = values.iter().enumerate().find_map(|(i, value)| {
+        let value = value?;
+        let single = StringArray::from(vec![value]);
+        cast_with_options(&single, field.data_type(), &CAST_OPTIONS)
+            .is_err()
+            .then_some((i, value))
+    });
+
+    match invalid {
+        Some((i, value))
+            if integer_range(field.data_type()).is_some() && value.parse::<i64>().is_ok() =>
+        {
+            ArrowError::ExternalError(Box::new(IntegerOverflow {
+                column: field.name().clone(),
+                message: format!(
+                    "value {} for column '{}' at line {} does not fit in {}",
+                    value,
+                    field.name(),
+                    rows[i].position().map_or(0, |position| position.line()),
+                    field.data_type().to_string().to_lowercase()
+                ),
+            }))
+        }
+        Some((i, value)) => invalid_value(field, value, &rows[i]),
+        None => error,
+    }
+}
+
+/// An integer value which does not fit the narrower integer type of its column.
+#[derive(Debug)]
+pub(crate) struct IntegerOverflow {
+    pub(crate) column: String,
+    message: String,
+}
+
+impl std::fmt::Display for IntegerOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for IntegerOverflow {}
+
+/// Returns the smallest and largest value of an integer type narrower than `int64`, or `None` for other types.
+pub(crate) fn integer_range(data_type: &DataType) -> Option<(i64, i64)> {
+    let range = match data_type {
+        DataType::Int8 => (i8::MIN as i64, i8::MAX as i64),
+        DataType::Int16 => (i16::MIN as i64, i16::MAX as i64),
+        DataType::Int32 => (i32::MIN as i64, i32::MAX as i64),
+        DataType::UInt8 => (0, u8::MAX as i64),
+        DataType::UInt16 => (0, u16::MAX as i64),
+        DataType::UInt32 => (0, u32::MAX as i64),
+        _ => return None,
+    };
+
+    Some(range)
+}
+
+/// Returns the narrowest integer type holding every value from `min` to `max`, an unsigned type
+/// if no value is negative.
+pub(crate) fn minimal_integer_type(min: i64, max: i64) -> DataType {
+    [
+        DataType::UInt8,
+        DataType::Int8,
+        DataType::UInt16,
+        DataType::Int16,
+        DataType::UInt32,
+        DataType::Int32,
+    ]
+    .into_iter()
+    .find(|data_type| {
+        let (low, high) = integer_range(data_type).expect("an integer type");
+        low <= min && max <= high
+    })
+    .unwrap_or(DataType::Int64)
+}
+
+/// The number of microseconds in a day.
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// A date or timestamp parsed with a date format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Temporal {
+    /// Days since the Unix epoch.
+    Date(i32),
+    /// Microseconds since the Unix epoch.
+    Timestamp(i64),
+}
+
+/// Parses a value with a strftime-style format, as a timestamp if the format has a time and as a date otherwise.
+fn parse_temporal(value: &str, date_format: &str) -> Option<Temporal> {
+    if let Ok(timestamp) = NaiveDateTime::parse_from_str(value, date_format) {
+        return Some(Temporal::Timestamp(timestamp.and_utc().timestamp_micros()));
+    }
+
+    let date = NaiveDate::parse_from_str(value, date_format).ok()?;
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    let days = date.signed_duration_since(epoch).num_days();
+    Some(Temporal::Date(days as i32))
+}
+
+/// The types of the columns inferred from the values inspected so far.
+struct Inference {
+    column_types: Vec<InferredDataType>,
+    temporal_types: Vec<TemporalMatches>,
+    bool_types: Vec<Option<bool>>,
+    empty_strings: Vec<bool>,
+}
+
+impl Inference {
+    fn new(columns: usize) -> Self {
+        Self {
+            column_types: vec![InferredDataType::default(); columns],
+            temporal_types: vec![TemporalMatches::default(); columns],
+            bool_types: vec![None; columns],
+            empty_strings: vec![false; columns],
+        }
+    }
+
+    /// Updates the column types with the values of the record.
+    fn update(&mut self, record: &StringRecord, builder: &BatchBuilder) {
+        for (i, value) in record.iter().enumerate().take(self.column_types.len()) {
+            if builder.null_regex.is_match(value) {
+                self.empty_strings[i] |= value.is_empty() && builder.empty_as == EmptyAs::String;
+                continue;
+            }
+            match builder.plain_number(value) {
+                Some(number) if !(builder.strict_leading_zeros && has_leading_zero(&number)) => {
+                    self.column_types[i].update(&number)
+                }
+                // a number of another format is kept as a string rather than misread
+                _ => self.column_types[i].update_string(),
+            }
+            if let Some(date_format) = &builder.date_format {
+                self.temporal_types[i].update(parse_temporal(value, date_format));
+            }
+            if let Some(bool_values) = &builder.bool_values {
+                let matches = bool_values.parse(value).is_some();
+                self.bool_types[i] = Some(self.bool_types[i].unwrap_or(true) && matches);
+            }
+        }
+    }
+}
+
+/// Keeps a uniform random sample of the records offered to it, with reservoir sampling.
+struct Reservoir {
+    capacity: usize,
+    offered: u64,
+    records: Vec<StringRecord>,
+    state: u64,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            offered: 0,
+            records: Vec::with_capacity(capacity),
+            // a fixed seed, so the schema inferred from a file is the same on every run
+            state: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// Keeps the record in place of a random kept record, with a chance of `capacity` in the
+    /// number of offered records.
+    fn offer(&mut self, record: StringRecord) {
+        self.offered += 1;
+        if self.records.len() < self.capacity {
+            self.records.push(record);
+            return;
+        }
+
+        let index = self.next_random() % self.offered;
+        if index < self.capacity as u64 {
+            self.records[index as usize] = record;
+        }
+    }
+
+    /// Returns the next number of an xorshift generator.
+    fn next_random(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+/// The sampled values of a column which match the date format.
+#[derive(Default, Clone, Copy)]
+struct TemporalMatches {
+    dates: usize,
+    timestamps: usize,
+    mismatches: usize,
+}
+
+impl TemporalMatches {
+    /// Records the result of parsing a value with the date format.
+    fn update(&mut self, parsed: Option<Temporal>) {
+        match parsed {
+            Some(Temporal::Date(_)) => self.dates += 1,
+            Some(Temporal::Timestamp(_)) => self.timestamps += 1,
+            None => self.mismatches += 1,
+        }
+    }
+
+    /// Returns the temporal type of the column, `Utf8` if only some values match and `None` if no value matches.
+    fn get(&self) -> Option<DataType> {
+        match (self.dates, self.timestamps, self.mismatches) {
+            (0, 0, _) => None,
+            (_, 0, 0) => Some(DataType::Date32),
+            (0, _, 0) => Some(DataType::Timestamp(TimeUnit::Microsecond, None)),
+            _ => Some(DataType::Utf8),
+        }
+    }
+}
+
+/// Returns whether the value is a number whose integer part starts with a redundant zero, such as `007` or `-01.5`.
+fn has_leading_zero(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let integer_part = digits.split(['.', 'e', 'E']).next().unwrap_or_default();
+    integer_part.len() > 1
+        && integer_part.starts_with('0')
+        && digits
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b".eE+-".contains(&b))
+}
+
+/// Patterns recognising the inferable types, in the order of the bits of [`InferredDataType`].
+fn type_patterns() -> &'static RegexSet {
+    static PATTERNS: OnceLock<RegexSet> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        RegexSet::new([
+            r"(?i)^(true)$|^(false)$(?-i)",
+            r"^-?(\d+)$",
+            r"^-?((\d*\.\d+|\d+\.\d*)([eE][-+]?\d+)?|\d+([eE][-+]?\d+))$",
+            r"^\d{4}-\d\d-\d\d$",
+            r"^\d{4}-\d\d-\d\d[T ]\d\d:\d\d:\d\d(?:[^\d\.].*)?$",
+            r"^\d{4}-\d\d-\d\d[T ]\d\d:\d\d:\d\d\.\d{1,3}(?:[^\d].*)?$",
+            r"^\d{4}-\d\d-\d\d[T ]\d\d:\d\d:\d\d\.\d{1,6}(?:[^\d].*)?$",
+            r"^\d{4}-\d\d-\d\d[T ]\d\d:\d\d:\d\d\.\d{1,9}(?:[^\d].*)?$",
+        ])
+        .unwrap()
+    })
+}
+
+/// The set of types seen in a column, following the inference rules of `arrow-csv`.
+///
+/// Each bit records one type: 0 boolean, 1 integer, 2 float, 3 date, 4-7 timestamps with
+/// second, millisecond, microsecond and nanosecond precision, 8 string.
+#[derive(Default, Clone, Copy)]
+struct InferredDataType {
+    packed: u16,
+    /// The smallest and largest integer seen.
+    range: Option<(i64, i64)>,
+}
+
+impl InferredDataType {
+    /// Returns the narrowest data type covering every value seen.
+    ///
+    /// Integers are `int64`, or the narrowest integer type covering their range with [`IntSizing::Minimal`].
+    fn get(&self, int_sizing: IntSizing) -> DataType {
+        match self.packed {
+            0 => DataType::Null,
+            1 => DataType::Boolean,
+            2 => match (int_sizing, self.range) {
+                (IntSizing::Minimal, Some((min, max))) => minimal_integer_type(min, max),
+                _ => DataType::Int64,
+            },
+            4 | 6 => DataType::Float64,
+            b if b != 0 && (b & !0b11111000) == 0 => match b.leading_zeros() {
+                // promote to the highest precision temporal type
+                8 => DataType::Timestamp(TimeUnit::Nanosecond, None),
+                9 => DataType::Timestamp(TimeUnit::Microsecond, None),
+                10 => DataType::Timestamp(TimeUnit::Millisecond, None),
+                11 => DataType::Timestamp(TimeUnit::Second, None),
+                12 => DataType::Date32,
+                _ => unreachable!(),
+            },
+            _ => DataType::Utf8,
+        }
+    }
+
+    /// Records a value which is a string whatever it looks like.
+    fn update_string(&mut self) {
+        self.packed |= 1 << 8;
+    }
+
+    /// Records the type of the given value.
+    fn update(&mut self, value: &str) {
+        self.packed |= match type_patterns().matches(value).into_iter().next() {
+            Some(1) => match value.parse::<i64>() {
+                Ok(integer) => {
+                    let (min, max) = self.range.unwrap_or((integer, integer));
+                    self.range = Some((min.min(integer), max.max(integer)));
+                    1 << 1
+                }
+                // integers which overflow i64 are kept as strings
+                Err(_) => 1 << 8,
+            },
+            Some(m) => 1 << m,
+            None => 1 << 8,
+        };
+    }
+}
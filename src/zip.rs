@@ -0,0 +1,368 @@
+use flate2::read::DeflateDecoder;
+use flate2::Crc;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// The signature of the end of central directory record.
+const END_SIGNATURE: u32 = 0x0605_4b50;
+
+/// The signature of a file header in the central directory.
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+
+/// The signature of the local header in front of the data of an entry.
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+
+/// The size of the end of central directory record without its comment.
+const END_RECORD_SIZE: usize = 22;
+
+/// The size of the local header without the file name and the extra field.
+const LOCAL_HEADER_SIZE: usize = 30;
+
+/// The entry data is stored without compression.
+const METHOD_STORED: u16 = 0;
+
+/// The entry data is compressed with deflate.
+const METHOD_DEFLATED: u16 = 8;
+
+/// An entry of the central directory of a ZIP archive.
+///
+/// Only the features written by common tools are supported: stored and deflated entries of
+/// archives without ZIP64 extensions or encryption.
+#[derive(Debug, Clone)]
+pub(crate) struct ZipEntry {
+    pub(crate) name: String,
+    method: u16,
+    encrypted: bool,
+    crc: u32,
+    compressed_size: u64,
+    pub(crate) size: u64,
+    header_offset: u64,
+}
+
+impl ZipEntry {
+    /// Returns whether the entry is a directory rather than a file.
+    pub(crate) fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Reads the entries of the central directory of a ZIP archive.
+pub(crate) fn read_entries<R: Read + Seek>(archive: &mut R) -> std::io::Result<Vec<ZipEntry>> {
+    let length = archive.seek(SeekFrom::End(0))?;
+    if length < END_RECORD_SIZE as u64 {
+        return Err(invalid("no ZIP archive, the file is too short"));
+    }
+    // the record is followed by a comment of up to 65535 bytes
+    let tail_length = length.min((END_RECORD_SIZE + u16::MAX as usize) as u64);
+    archive.seek(SeekFrom::Start(length - tail_length))?;
+    let mut tail = vec![0; tail_length as usize];
+    archive.read_exact(&mut tail)?;
+
+    let end = (0..=tail.len().saturating_sub(END_RECORD_SIZE))
+        .rev()
+        .find(|&i| u32_at(&tail, i) == END_SIGNATURE)
+        .ok_or_else(|| invalid("no ZIP archive, the end of the central directory is missing"))?;
+    let record = &tail[end..];
+    let count = u16_at(record, 10);
+    let directory_size = u32_at(record, 12);
+    let directory_offset = u32_at(record, 16);
+    if count == u16::MAX || directory_offset == u32::MAX {
+        return Err(invalid("ZIP64 archives are not supported"));
+    }
+    // a corrupt size would otherwise allocate up to 4 GiB
+    if directory_offset as u64 + directory_size as u64 > length {
+        return Err(invalid(
+            "the central directory of the ZIP archive is corrupt",
+        ));
+    }
+
+    archive.seek(SeekFrom::Start(directory_offset as u64))?;
+    let mut directory = vec![0; directory_size as usize];
+    archive.read_exact(&mut directory)?;
+
+    let mut entries = Vec::with_capacity(count as usize);
+    let mut position = 0;
+    for _ in 0..count {
+        let header = directory
+            .get(position..position + 46)
+            .filter(|header| u32_at(header, 0) == CENTRAL_SIGNATURE)
+            .ok_or_else(|| invalid("the central directory of the ZIP archive is corrupt"))?;
+        let name_length = u16_at(header, 28) as usize;
+        let extra_length = u16_at(header, 30) as usize;
+        let comment_length = u16_at(header, 32) as usize;
+        let name = directory
+            .get(position + 46..position + 46 + name_length)
+            .ok_or_else(|| invalid("the central directory of the ZIP archive is corrupt"))?;
+
+        let entry = ZipEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method: u16_at(header, 10),
+            encrypted: u16_at(header, 8) & 1 == 1,
+            crc: u32_at(header, 16),
+            compressed_size: u32_at(header, 20) as u64,
+            size: u32_at(header, 24) as u64,
+            header_offset: u32_at(header, 42) as u64,
+        };
+        if [entry.compressed_size, entry.size, entry.header_offset].contains(&(u32::MAX as u64)) {
+            return Err(invalid("ZIP64 archives are not supported"));
+        }
+        entries.push(entry);
+        position += 46 + name_length + extra_length + comment_length;
+    }
+
+    Ok(entries)
+}
+
+/// Returns the named entry of a ZIP archive.
+pub(crate) fn find_entry(archive_path: &Path, name: &str) -> std::io::Result<ZipEntry> {
+    read_entries(&mut File::open(archive_path)?)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("{} has no entry {}", archive_path.display(), name),
+            )
+        })
+}
+
+/// Opens the data of the named entry of a ZIP archive, decompressing it.
+///
+/// The checksum of the data is verified once it has been read to the end.
+pub(crate) fn open_entry(archive_path: &Path, name: &str) -> std::io::Result<Box<dyn Read>> {
+    let entry = find_entry(archive_path, name)?;
+    let mut archive = File::open(archive_path)?;
+    if entry.encrypted {
+        return Err(invalid(format!("the entry {} is encrypted", name)));
+    }
+
+    archive.seek(SeekFrom::Start(entry.header_offset))?;
+    let mut header = [0; LOCAL_HEADER_SIZE];
+    archive.read_exact(&mut header)?;
+    if u32_at(&header, 0) != LOCAL_SIGNATURE {
+        return Err(invalid(format!(
+            "the local header of the entry {} is corrupt",
+            name
+        )));
+    }
+    let skipped = u16_at(&header, 26) as i64 + u16_at(&header, 28) as i64;
+    archive.seek(SeekFrom::Current(skipped))?;
+
+    let data = archive.take(entry.compressed_size);
+    let inner: Box<dyn Read> = match entry.method {
+        METHOD_STORED => Box::new(data),
+        METHOD_DEFLATED => Box::new(DeflateDecoder::new(data)),
+        method => {
+            return Err(invalid(format!(
+                "the entry {} uses the unsupported compression method {}",
+                name, method
+            )))
+        }
+    };
+
+    Ok(Box::new(EntryReader {
+        inner,
+        crc: Crc::new(),
+        expected_crc: entry.crc,
+        expected_size: entry.size,
+    }))
+}
+
+/// A reader of the data of an entry which verifies its size and checksum at the end.
+struct EntryReader {
+    inner: Box<dyn Read>,
+    crc: Crc,
+    expected_crc: u32,
+    expected_size: u64,
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.crc.update(&buf[..length]);
+
+        let end = length == 0 && !buf.is_empty();
+        if end
+            && (self.crc.amount() as u64 != self.expected_size
+                || self.crc.sum() != self.expected_crc)
+        {
+            return Err(invalid(
+                "the data of the ZIP entry is corrupt, its checksum does not match",
+            ));
+        }
+
+        Ok(length)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, message.into())
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::{Cursor, Write};
+
+    const DATA: &[u8] = b"id,name\n1,Jane\n2,John\n";
+
+    /// Returns a ZIP archive of a single entry `data.csv` with the given method and data.
+    fn archive(method: u16, data: &[u8]) -> Vec<u8> {
+        let mut crc = Crc::new();
+        crc.update(DATA);
+        let name = b"data.csv";
+        let fields = |bytes: &mut Vec<u8>| {
+            bytes.extend_from_slice(&method.to_le_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&crc.sum().to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(DATA.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&[0; 2]);
+        };
+
+        let mut bytes = LOCAL_SIGNATURE.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[20, 0, 0, 0]);
+        fields(&mut bytes);
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(data);
+
+        let directory_offset = bytes.len() as u32;
+        bytes.extend_from_slice(&CENTRAL_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+        fields(&mut bytes);
+        bytes.extend_from_slice(&[0; 14]);
+        bytes.extend_from_slice(name);
+        let directory_size = bytes.len() as u32 - directory_offset;
+
+        bytes.extend_from_slice(&END_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        bytes.extend_from_slice(&directory_size.to_le_bytes());
+        bytes.extend_from_slice(&directory_offset.to_le_bytes());
+        bytes.extend_from_slice(&[0; 2]);
+        bytes
+    }
+
+    fn deflated() -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(DATA).unwrap();
+        archive(METHOD_DEFLATED, &encoder.finish().unwrap())
+    }
+
+    /// Writes the archive to a temporary file and reads its entry to the end.
+    fn read_entry(bytes: &[u8], file_name: &str) -> std::io::Result<Vec<u8>> {
+        let path = std::env::temp_dir().join(file_name);
+        std::fs::write(&path, bytes).unwrap();
+        let result = open_entry(&path, "data.csv").and_then(|mut entry| {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map(|_| data)
+        });
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_read_entry() {
+        let entries = read_entries(&mut Cursor::new(archive(METHOD_STORED, DATA))).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "data.csv");
+        assert_eq!(entries[0].size, DATA.len() as u64);
+
+        let stored = archive(METHOD_STORED, DATA);
+        assert_eq!(
+            read_entry(&stored, "cc2p_test_zip_stored.zip").unwrap(),
+            DATA
+        );
+        assert_eq!(
+            read_entry(&deflated(), "cc2p_test_zip_deflated.zip").unwrap(),
+            DATA
+        );
+    }
+
+    #[test]
+    fn test_read_truncated_archive() {
+        for bytes in [archive(METHOD_STORED, DATA), deflated()] {
+            for length in 0..bytes.len() {
+                assert!(read_entries(&mut Cursor::new(&bytes[..length])).is_err());
+            }
+        }
+
+        // a truncated deflate stream ends early and fails the checksum
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(DATA).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let bytes = archive(METHOD_DEFLATED, &compressed[..compressed.len() / 2]);
+        assert!(read_entry(&bytes, "cc2p_test_zip_truncated.zip").is_err());
+    }
+
+    #[test]
+    fn test_read_corrupt_archive() {
+        // every corrupt byte fails or reads the entry, without panicking
+        for bytes in [archive(METHOD_STORED, DATA), deflated()] {
+            for position in 0..bytes.len() {
+                let mut corrupt = bytes.clone();
+                corrupt[position] = !corrupt[position];
+                if read_entries(&mut Cursor::new(&corrupt)).is_ok() {
+                    let _ = read_entry(&corrupt, "cc2p_test_zip_corrupt.zip");
+                }
+            }
+        }
+
+        let mut bytes = archive(METHOD_STORED, DATA);
+        let directory_size = bytes.len() - 10;
+        bytes[directory_size..directory_size + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = read_entries(&mut Cursor::new(bytes)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the central directory of the ZIP archive is corrupt"
+        );
+    }
+
+    #[test]
+    fn test_read_checksum_mismatch() {
+        let mut data = DATA.to_vec();
+        data[0] = b'x';
+        let error =
+            read_entry(&archive(METHOD_STORED, &data), "cc2p_test_zip_crc.zip").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("its checksum does not match"));
+    }
+
+    #[test]
+    fn test_read_unsupported_archive() {
+        let error = read_entry(&archive(12, DATA), "cc2p_test_zip_method.zip").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the entry data.csv uses the unsupported compression method 12"
+        );
+
+        // the markers of ZIP64 extensions in the end record and in the central directory
+        let bytes = archive(METHOD_STORED, DATA);
+        let end = bytes.len() - END_RECORD_SIZE;
+        let mut count = bytes.clone();
+        count[end + 10..end + 12].copy_from_slice(&u16::MAX.to_le_bytes());
+        let central = end - 46 - "data.csv".len();
+        let mut size = bytes.clone();
+        size[central + 24..central + 28].copy_from_slice(&u32::MAX.to_le_bytes());
+        for bytes in [count, size] {
+            let error = read_entries(&mut Cursor::new(bytes)).unwrap_err();
+            assert_eq!(error.to_string(), "ZIP64 archives are not supported");
+        }
+
+        let mut encrypted = bytes.clone();
+        encrypted[central + 8] |= 1;
+        let error = read_entry(&encrypted, "cc2p_test_zip_encrypted.zip").unwrap_err();
+        assert_eq!(error.to_string(), "the entry data.csv is encrypted");
+    }
+}